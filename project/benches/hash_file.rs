@@ -0,0 +1,74 @@
+//! Throughput of file hashing for several file sizes, with 1, 2, 4, ... threads, up to the
+//! number of cores.
+//!
+//! Run with `cargo bench -p project --features parallel,blake3 --bench hash_file`. Tree mode
+//! should scale close to linearly with the thread count for every algorithm, and BLAKE3 with
+//! `hash_file` too, while the plain digest of other algorithms stays at single-core speed. Small
+//! files show the fixed cost of mapping the file and of spreading the work over the threads.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use cryptoapi::{file, Algorithm};
+use std::thread;
+
+const FILE_SIZES: [usize; 3] = [1 << 20, 16 << 20, 64 << 20];
+const CHUNK_SIZE: usize = 1 << 20;
+
+/// Thread counts to measure: powers of two up to the number of cores, and the number of cores.
+fn thread_counts() -> Vec<usize> {
+    let cores = thread::available_parallelism().map_or(1, |cores| cores.get());
+    let mut counts: Vec<usize> = (0..).map(|i| 1 << i).take_while(|&n| n < cores).collect();
+    counts.push(cores);
+    counts
+}
+
+fn hash_file(c: &mut Criterion) {
+    let path = std::env::temp_dir().join(format!("cryptoapi-bench-{}", std::process::id()));
+    let algorithms: Vec<Algorithm> = [Algorithm::Sha256, Algorithm::Sha3_256]
+        .into_iter()
+        .chain("BLAKE3".parse().ok())
+        .collect();
+    let pools: Vec<_> = thread_counts()
+        .into_iter()
+        .map(|threads| {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .unwrap();
+            (threads, pool)
+        })
+        .collect();
+
+    for file_size in FILE_SIZES {
+        let data: Vec<u8> = (0..file_size).map(|i| (i % 251) as u8).collect();
+        std::fs::write(&path, data).unwrap();
+        for &algorithm in &algorithms {
+            let mut group =
+                c.benchmark_group(format!("hash_file/{algorithm}/{} MiB", file_size >> 20));
+            group.throughput(Throughput::Bytes(file_size as u64));
+            group.sample_size(10);
+            for (threads, pool) in &pools {
+                let mut output = vec![0u8; algorithm.output_size()];
+                // SAFETY: the file is only written between benchmark groups.
+                group.bench_with_input(BenchmarkId::new("plain", threads), &path, |b, path| {
+                    b.iter(|| {
+                        pool.install(|| unsafe {
+                            file::hash_file(algorithm, path, &mut output).unwrap()
+                        })
+                    })
+                });
+                group.bench_with_input(BenchmarkId::new("tree", threads), &path, |b, path| {
+                    b.iter(|| {
+                        pool.install(|| unsafe {
+                            file::hash_file_tree(algorithm, path, CHUNK_SIZE, &mut output).unwrap()
+                        })
+                    })
+                });
+            }
+            group.finish();
+        }
+    }
+    let _ = std::fs::remove_file(path);
+}
+
+criterion_group!(benches, hash_file);
+criterion_main!(benches);
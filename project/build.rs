@@ -1,12 +1,13 @@
 use std::env;
-use std::path::PathBuf;
+use std::fs;
+use std::path::{Path, PathBuf};
 
 // Custom build
 fn main() {
     let crate_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
 
     let out_path = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
-    let gen = cbindgen::generate(crate_dir);
+    let gen = cbindgen::generate(&crate_dir);
     match gen {
         Err(e) => {
             println!("CBINDGEN Error: {:?},", e);
@@ -16,4 +17,39 @@ fn main() {
             bindings.write_to_file(out_path.join("src/api/crypto_api.h"));
         }
     }
+
+    link_shared_library(&out_path);
+    write_pkg_config(&out_path);
+}
+
+// The shared library gets an SONAME with the ABI major version. rustc already restricts its
+// exports to the crypto_* functions with a version script of its own, which GNU ld cannot combine
+// with a second one, so ours is only passed with the `symbol_versions` feature.
+fn link_shared_library(crate_path: &Path) {
+    if env::var("CARGO_CFG_TARGET_OS").unwrap() != "linux" {
+        return;
+    }
+    let major = env::var("CARGO_PKG_VERSION_MAJOR").unwrap();
+    println!("cargo:rustc-cdylib-link-arg=-Wl,-soname,libcryptoapi.so.{major}");
+    if env::var_os("CARGO_FEATURE_SYMBOL_VERSIONS").is_some() {
+        let version_script = crate_path.join("cryptoapi.map");
+        println!(
+            "cargo:rustc-cdylib-link-arg=-Wl,--version-script={}",
+            version_script.display()
+        );
+    }
+}
+
+// Generates OUT_DIR/cryptoapi.pc, for an install prefix given by CRYPTOAPI_PREFIX (default
+// /usr/local). Packaging scripts find OUT_DIR in the "build-script-executed" message of
+// `cargo build --message-format=json`.
+fn write_pkg_config(crate_path: &Path) {
+    let template_path = crate_path.join("cryptoapi.pc.in");
+    let prefix = env::var("CRYPTOAPI_PREFIX").unwrap_or_else(|_| "/usr/local".to_string());
+    let pkg_config = fs::read_to_string(template_path)
+        .unwrap()
+        .replace("@PREFIX@", &prefix)
+        .replace("@VERSION@", &env::var("CARGO_PKG_VERSION").unwrap());
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    fs::write(out_dir.join("cryptoapi.pc"), pkg_config).unwrap();
 }
@@ -0,0 +1,229 @@
+use crate::{
+    CryptoResult, AES_256_GCM_AEAD_ID, CHACHA20_POLY1305_AEAD_ID, CRYPTO_AEAD_KEY_SIZE,
+    CRYPTO_AEAD_NONCE_SIZE, CRYPTO_AEAD_TAG_SIZE,
+};
+use aes_gcm::Aes256Gcm;
+use chacha20poly1305::aead::{AeadInPlace, KeyInit, Nonce, Tag};
+use chacha20poly1305::ChaCha20Poly1305;
+use std::fmt;
+
+/// Authenticated encryption algorithms, with their C API IDs as discriminants.
+///
+/// Both take 32 byte keys and 12 byte nonces, and append 16 byte tags. A nonce must never be
+/// reused with the same key: it breaks both confidentiality and authenticity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+#[repr(u32)]
+pub enum AeadAlgorithm {
+    /// ChaCha20-Poly1305 (RFC 8439).
+    ChaCha20Poly1305 = CHACHA20_POLY1305_AEAD_ID,
+    /// AES-256 in Galois/Counter Mode (NIST SP 800-38D).
+    Aes256Gcm = AES_256_GCM_AEAD_ID,
+}
+
+impl AeadAlgorithm {
+    /// C API ID of the algorithm.
+    pub fn id(self) -> u32 {
+        self as u32
+    }
+
+    /// Name of the algorithm, e.g. "ChaCha20-Poly1305".
+    pub fn name(self) -> &'static str {
+        match self {
+            AeadAlgorithm::ChaCha20Poly1305 => "ChaCha20-Poly1305",
+            AeadAlgorithm::Aes256Gcm => "AES-256-GCM",
+        }
+    }
+}
+
+impl TryFrom<u32> for AeadAlgorithm {
+    type Error = CryptoResult;
+
+    /// Fails with `BadOrUnsupportedAlgorithm`, and the ID as error detail, for unknown IDs.
+    fn try_from(aead_id: u32) -> Result<Self, Self::Error> {
+        match aead_id {
+            CHACHA20_POLY1305_AEAD_ID => Ok(AeadAlgorithm::ChaCha20Poly1305),
+            AES_256_GCM_AEAD_ID => Ok(AeadAlgorithm::Aes256Gcm),
+            _ => Err(CryptoResult::BadOrUnsupportedAlgorithm
+                .with_detail(format_args!("AEAD algorithm ID {aead_id} is not supported"))),
+        }
+    }
+}
+
+impl fmt::Display for AeadAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Encrypts `plaintext` into `ciphertext`, which must be `CRYPTO_AEAD_TAG_SIZE` bytes longer:
+/// the encrypted plaintext is followed by the tag authenticating it and `associated_data`.
+///
+/// Parameters are checked before anything is written to `ciphertext`, which is zeroed if the
+/// encryption itself fails, so plaintext is never left in it.
+pub fn seal(
+    algorithm: AeadAlgorithm,
+    key: &[u8],
+    nonce: &[u8],
+    associated_data: &[u8],
+    plaintext: &[u8],
+    ciphertext: &mut [u8],
+) -> Result<(), CryptoResult> {
+    if ciphertext.len() != plaintext.len() + CRYPTO_AEAD_TAG_SIZE {
+        return Err(CryptoResult::bad_output_size(
+            plaintext.len() + CRYPTO_AEAD_TAG_SIZE,
+            ciphertext.len(),
+        ));
+    }
+    check_key_and_nonce(key, nonce)?;
+    let (buffer, tag) = ciphertext.split_at_mut(plaintext.len());
+    buffer.copy_from_slice(plaintext);
+    seal_in_place(algorithm, key, nonce, associated_data, buffer, tag)
+        .inspect_err(|_| ciphertext.fill(0))
+}
+
+/// Decrypts `ciphertext`, made of the encrypted data followed by the tag, into `plaintext`,
+/// which must be `CRYPTO_AEAD_TAG_SIZE` bytes shorter.
+///
+/// Fails with `VerificationFailed` if the tag does not authenticate the ciphertext and
+/// `associated_data`, in which case `plaintext` is zeroed.
+pub fn open(
+    algorithm: AeadAlgorithm,
+    key: &[u8],
+    nonce: &[u8],
+    associated_data: &[u8],
+    ciphertext: &[u8],
+    plaintext: &mut [u8],
+) -> Result<(), CryptoResult> {
+    if ciphertext.len() < CRYPTO_AEAD_TAG_SIZE {
+        return Err(CryptoResult::BadParameter.with_detail(format_args!(
+            "ciphertext is {} bytes, shorter than the tag",
+            ciphertext.len()
+        )));
+    }
+    let (encrypted, tag) = ciphertext.split_at(ciphertext.len() - CRYPTO_AEAD_TAG_SIZE);
+    if plaintext.len() != encrypted.len() {
+        return Err(CryptoResult::bad_output_size(
+            encrypted.len(),
+            plaintext.len(),
+        ));
+    }
+    plaintext.copy_from_slice(encrypted);
+    open_in_place(algorithm, key, nonce, associated_data, plaintext, tag)
+        .inspect_err(|_| plaintext.fill(0))
+}
+
+/// Encrypts `buffer` in place and writes the tag authenticating it and `associated_data` to
+/// `tag`, which must be `CRYPTO_AEAD_TAG_SIZE` bytes long.
+///
+/// Fails with `BadParameter` if the key or the nonce has the wrong length.
+pub fn seal_in_place(
+    algorithm: AeadAlgorithm,
+    key: &[u8],
+    nonce: &[u8],
+    associated_data: &[u8],
+    buffer: &mut [u8],
+    tag: &mut [u8],
+) -> Result<(), CryptoResult> {
+    check_key_and_nonce(key, nonce)?;
+    if tag.len() != CRYPTO_AEAD_TAG_SIZE {
+        return Err(CryptoResult::bad_output_size(
+            CRYPTO_AEAD_TAG_SIZE,
+            tag.len(),
+        ));
+    }
+    let computed = match algorithm {
+        AeadAlgorithm::ChaCha20Poly1305 => {
+            encrypt::<ChaCha20Poly1305>(key, nonce, associated_data, buffer)
+        }
+        AeadAlgorithm::Aes256Gcm => encrypt::<Aes256Gcm>(key, nonce, associated_data, buffer),
+    }?;
+    tag.copy_from_slice(&computed);
+    Ok(())
+}
+
+/// Decrypts `buffer` in place, after checking that `tag` authenticates it and
+/// `associated_data`.
+///
+/// Fails with `VerificationFailed` if it does not, in which case `buffer` is left unchanged, and
+/// with `BadParameter` if the key, the nonce or the tag has the wrong length.
+pub fn open_in_place(
+    algorithm: AeadAlgorithm,
+    key: &[u8],
+    nonce: &[u8],
+    associated_data: &[u8],
+    buffer: &mut [u8],
+    tag: &[u8],
+) -> Result<(), CryptoResult> {
+    check_key_and_nonce(key, nonce)?;
+    if tag.len() != CRYPTO_AEAD_TAG_SIZE {
+        return Err(CryptoResult::BadParameter.with_detail(format_args!(
+            "tag is {} bytes, expected {CRYPTO_AEAD_TAG_SIZE} bytes",
+            tag.len()
+        )));
+    }
+    match algorithm {
+        AeadAlgorithm::ChaCha20Poly1305 => {
+            decrypt::<ChaCha20Poly1305>(key, nonce, associated_data, buffer, tag)
+        }
+        AeadAlgorithm::Aes256Gcm => decrypt::<Aes256Gcm>(key, nonce, associated_data, buffer, tag),
+    }
+}
+
+/// Checks the key and nonce lengths, failing with `BadParameter` if either is wrong.
+pub fn check_key_and_nonce(key: &[u8], nonce: &[u8]) -> Result<(), CryptoResult> {
+    if key.len() != CRYPTO_AEAD_KEY_SIZE {
+        return Err(CryptoResult::BadParameter.with_detail(format_args!(
+            "key is {} bytes, expected {CRYPTO_AEAD_KEY_SIZE} bytes",
+            key.len()
+        )));
+    }
+    if nonce.len() != CRYPTO_AEAD_NONCE_SIZE {
+        return Err(CryptoResult::BadParameter.with_detail(format_args!(
+            "nonce is {} bytes, expected {CRYPTO_AEAD_NONCE_SIZE} bytes",
+            nonce.len()
+        )));
+    }
+    Ok(())
+}
+
+// Both ciphers take the key, nonce and tag sizes checked above, so building them and the
+// nonce and tag arrays from slices cannot fail.
+
+fn encrypt<C: KeyInit + AeadInPlace>(
+    key: &[u8],
+    nonce: &[u8],
+    associated_data: &[u8],
+    buffer: &mut [u8],
+) -> Result<Tag<C>, CryptoResult> {
+    let cipher = C::new_from_slice(key).map_err(|_| CryptoResult::Failure)?;
+    cipher
+        .encrypt_in_place_detached(Nonce::<C>::from_slice(nonce), associated_data, buffer)
+        .map_err(|_| {
+            CryptoResult::BadParameter.with_detail(format_args!(
+                "plaintext of {} bytes is too long",
+                buffer.len()
+            ))
+        })
+}
+
+fn decrypt<C: KeyInit + AeadInPlace>(
+    key: &[u8],
+    nonce: &[u8],
+    associated_data: &[u8],
+    buffer: &mut [u8],
+    tag: &[u8],
+) -> Result<(), CryptoResult> {
+    let cipher = C::new_from_slice(key).map_err(|_| CryptoResult::Failure)?;
+    cipher
+        .decrypt_in_place_detached(
+            Nonce::<C>::from_slice(nonce),
+            associated_data,
+            buffer,
+            Tag::<C>::from_slice(tag),
+        )
+        .map_err(|_| {
+            CryptoResult::VerificationFailed
+                .with_detail("ciphertext or associated data failed authentication")
+        })
+}
@@ -1,6 +1,7 @@
 #ifndef CRYPTO_API_H
 #define CRYPTO_API_H
 
+#include "stdbool.h"
 #include "stdint.h"
 #include "stddef.h"
 
@@ -26,10 +27,174 @@
 #define SHA3_512_ALG_ID 2
 #endif
 
+#define SHAKE128_ALG_ID 3
+
+#define SHAKE256_ALG_ID 4
+
+#if defined(BLAKE2)
+#define BLAKE2B_512_ALG_ID 5
+#endif
+
+#if defined(BLAKE2)
+#define BLAKE2S_256_ALG_ID 6
+#endif
+
+#if defined(BLAKE3)
+#define BLAKE3_ALG_ID 7
+#endif
+
+#if defined(SHA224)
+#define SHA224_ALG_ID 8
+#endif
+
+#if defined(SHA384)
+#define SHA384_ALG_ID 9
+#endif
+
+#if defined(SHA512)
+#define SHA512_ALG_ID 10
+#endif
+
+#if defined(SHA512_224)
+#define SHA512_224_ALG_ID 11
+#endif
+
+#if defined(SHA512_256)
+#define SHA512_256_ALG_ID 12
+#endif
+
+#if defined(AEAD)
+/**
+ * Authenticated encryption algorithms
+ *
+ * IDs of the AEAD algorithms of `crypto_aead_seal` and `crypto_aead_open`, which have their own
+ * ID space, separate from the hash algorithms.
+ */
+#define CHACHA20_POLY1305_AEAD_ID 0
+#endif
+
+#if defined(AEAD)
+#define AES_256_GCM_AEAD_ID 1
+#endif
+
+#if defined(AEAD)
+/**
+ * Byte length of the keys of every AEAD algorithm.
+ */
+#define CRYPTO_AEAD_KEY_SIZE 32
+#endif
+
+#if defined(AEAD)
+/**
+ * Byte length of the nonces of every AEAD algorithm.
+ */
+#define CRYPTO_AEAD_NONCE_SIZE 12
+#endif
+
+#if defined(AEAD)
+/**
+ * Byte length of the authentication tags of every AEAD algorithm.
+ */
+#define CRYPTO_AEAD_TAG_SIZE 16
+#endif
+
+#if defined(ED25519)
+/**
+ * Byte length of Ed25519 seeds, the private keys of RFC 8032.
+ */
+#define CRYPTO_ED25519_SEED_SIZE 32
+#endif
+
+#if defined(ED25519)
+/**
+ * Byte length of Ed25519 public keys.
+ */
+#define CRYPTO_ED25519_PUBLIC_KEY_SIZE 32
+#endif
+
+#if defined(ED25519)
+/**
+ * Byte length of Ed25519 signatures.
+ */
+#define CRYPTO_ED25519_SIGNATURE_SIZE 64
+#endif
+
+#if defined(X25519)
+/**
+ * Byte length of X25519 secret keys, public keys and shared secrets.
+ */
+#define CRYPTO_X25519_KEY_SIZE 32
+#endif
+
+/**
+ * Maximum number of bytes returned by a single `crypto_drbg_generate` call (2^19 bits, SP
+ * 800-90A table 2).
+ */
+#define CRYPTO_DRBG_MAX_REQUEST_SIZE 65536
+
+#if defined(ARGON2)
+/**
+ * Maximum Argon2id memory size accepted by `crypto_argon2id_hash` and `crypto_argon2id_verify`,
+ * in KiB.
+ *
+ * The cap (2 GiB, the first configuration recommended by RFC 9106) bounds the memory a single
+ * call can allocate, including when verifying a tampered PHC string.
+ */
+#define CRYPTO_ARGON2_MAX_MEMORY_KIB 2097152
+#endif
+
+#if defined(ARGON2)
+/**
+ * Maximum Argon2id iteration count accepted by `crypto_argon2id_hash` and
+ * `crypto_argon2id_verify`.
+ *
+ * Argon2id runs in time linear to the iteration count, so the count is capped, as the memory
+ * size is, to bound the time a tampered PHC string can make verification take. The cap (2^6)
+ * is well above the 1 to 3 iterations recommended by RFC 9106.
+ */
+#define CRYPTO_ARGON2_MAX_ITERATIONS 64
+#endif
+
+#if defined(ARGON2)
+/**
+ * Maximum Argon2id parallelism (number of lanes) accepted by `crypto_argon2id_hash` and
+ * `crypto_argon2id_verify`.
+ *
+ * Lanes are computed one after the other, so the cap bounds the work per call together with
+ * the memory size and iteration count. RFC 9106 recommends 4 lanes.
+ */
+#define CRYPTO_ARGON2_MAX_PARALLELISM 64
+#endif
+
+#if defined(ARGON2)
+/**
+ * Byte length of a buffer that holds any PHC string written by `crypto_argon2id_hash`,
+ * including the terminating null character.
+ */
+#define CRYPTO_ARGON2_PHC_MAX_SIZE 256
+#endif
+
+/**
+ * Maximum iteration count accepted by `crypto_pbkdf2`.
+ *
+ * PBKDF2 runs in time linear to the iteration count, so the count is capped to bound the time a
+ * single call can take on constrained devices. The cap (2^24) still covers every RFC 6070 test
+ * case.
+ */
+#define CRYPTO_PBKDF2_MAX_ITERATIONS 16777216
+
 /**
  * Byte length of Context internal state.
+ *
+ * The size is large enough for every optional algorithm, so that the layout of `CryptoContext`
+ * does not depend on the enabled cargo features.
+ */
+#define CRYPTO_CONTEXT_STATE_SIZE 244
+
+/**
+ * Byte length of a buffer that holds any blob written by `crypto_export_state`.
  */
-#define CRYPTO_CONTEXT_STATE_SIZE 45
+#define CRYPTO_STATE_MAX_SIZE 512
 
 /**
  * Crypto return type with Success or Failure error cases.
@@ -53,21 +218,208 @@ typedef enum {
   BadOrUnsupportedAlgorithm,
   UninitializedOrCorruptedContext,
   BadBufferOutputSize,
+  BadParameter,
+  VerificationFailed,
+  ZeroSharedSecret,
 } CryptoResult;
 
+/**
+ * Opaque heap-allocated Hash_DRBG generator, created with `crypto_drbg_instantiate` and released
+ * with `crypto_drbg_free`.
+ */
+typedef struct CryptoDrbg CryptoDrbg;
+
+/**
+ * Opaque heap-allocated context, created with `crypto_new` or `crypto_hmac_new` and released
+ * with `crypto_free`.
+ *
+ * Unlike `CryptoContext`, its size is not part of the ABI, so it keeps working unchanged when
+ * algorithms with larger states are added.
+ */
+typedef struct CryptoHandle CryptoHandle;
+
+/**
+ * Opaque heap-allocated Merkle tree, created with `crypto_merkle_new` and released with
+ * `crypto_merkle_free`.
+ */
+typedef struct CryptoMerkleTree CryptoMerkleTree;
+
 /**
  * Crypto Context containing memory for internal usage.
  * Memory is declared as array of 64bit integer to preserve memory alignment
  * and match the internal context type.
+ *
+ * The struct is 16-byte aligned, as required by the 128-bit counters of the SHA-512 family.
  */
-typedef struct {
+typedef struct CRYPTO_ALIGNED(16) {
   uint64_t state[CRYPTO_CONTEXT_STATE_SIZE];
 } CryptoContext;
 
+/**
+ * Input buffer for scatter/gather hashing with `crypto_hash_multi`.
+ */
+typedef struct {
+  const uint8_t *data;
+  size_t length;
+} CryptoBuffer;
+
+#if defined(ED25519)
+/**
+ * Ed25519 seed: the 32 byte private key of RFC 8032, which must come from a cryptographically
+ * secure random source and be kept secret.
+ */
+typedef struct {
+  uint8_t bytes[CRYPTO_ED25519_SEED_SIZE];
+} CryptoEd25519Seed;
+#endif
+
+#if defined(ED25519)
+/**
+ * Ed25519 public key, derived from a seed with `crypto_ed25519_public_key`.
+ */
+typedef struct {
+  uint8_t bytes[CRYPTO_ED25519_PUBLIC_KEY_SIZE];
+} CryptoEd25519PublicKey;
+#endif
+
+#if defined(ED25519)
+/**
+ * Ed25519 signature, made with `crypto_ed25519_sign`.
+ */
+typedef struct {
+  uint8_t bytes[CRYPTO_ED25519_SIGNATURE_SIZE];
+} CryptoEd25519Signature;
+#endif
+
+#if defined(X25519)
+/**
+ * X25519 secret key: 32 bytes from a cryptographically secure random source, to be kept
+ * secret.
+ */
+typedef struct {
+  uint8_t bytes[CRYPTO_X25519_KEY_SIZE];
+} CryptoX25519SecretKey;
+#endif
+
+#if defined(X25519)
+/**
+ * X25519 public key, derived from a secret key with `crypto_x25519_public_key`.
+ */
+typedef struct {
+  uint8_t bytes[CRYPTO_X25519_KEY_SIZE];
+} CryptoX25519PublicKey;
+#endif
+
+#if defined(X25519)
+/**
+ * X25519 shared secret, computed with `crypto_x25519_shared_secret`.
+ */
+typedef struct {
+  uint8_t bytes[CRYPTO_X25519_KEY_SIZE];
+} CryptoX25519SharedSecret;
+#endif
+
+#if defined(ARGON2)
+/**
+ * Argon2id cost parameters.
+ *
+ * RFC 9106 recommends 2 GiB of memory with 1 iteration and 4 lanes, or 64 MiB with 3 iterations
+ * and 4 lanes when memory is constrained. OWASP asks for at least 19 MiB with 2 iterations.
+ */
+typedef struct {
+  /**
+   * Memory size in KiB, at least 8 times the parallelism and at most
+   * `CRYPTO_ARGON2_MAX_MEMORY_KIB`.
+   */
+  uint32_t memory_kib;
+  /**
+   * Number of passes over the memory, between 1 and `CRYPTO_ARGON2_MAX_ITERATIONS`.
+   */
+  uint32_t iterations;
+  /**
+   * Number of lanes, between 1 and `CRYPTO_ARGON2_MAX_PARALLELISM`.
+   */
+  uint32_t parallelism;
+  /**
+   * Byte length of the hash, between 4 and 64.
+   */
+  size_t output_length;
+} CryptoArgon2Params;
+#endif
+
 #ifdef __cplusplus
 extern "C" {
 #endif // __cplusplus
 
+/**
+ * Crypto API version
+ * Returns the nul-terminated version of the library, e.g. "0.1.0". The string is static and
+ * must not be freed.
+ */
+const char *crypto_api_version(void);
+
+/**
+ * Crypto result str
+ * Returns a static, nul-terminated description of a result, e.g. "bad output buffer size".
+ * The string must not be freed.
+ */
+const char *crypto_result_str(CryptoResult result);
+
+/**
+ * Crypto last error detail
+ * Returns a nul-terminated description of why the last failing call on the calling thread
+ * failed, e.g. the expected and the given lengths for `BadBufferOutputSize`, or null if that
+ * error has no detail.
+ *
+ * Every function returning a `CryptoResult` clears the detail when called, so it must be read
+ * right after the error is returned. The string is owned by the library and stays valid until
+ * the next such call on the same thread.
+ */
+const char *crypto_last_error_detail(void);
+
+/**
+ * Crypto algorithm supported
+ * Returns whether the given algorithm ID is compiled into this library.
+ */
+bool crypto_algorithm_supported(uint32_t algorithm_id);
+
+/**
+ * Crypto output size
+ * Returns the digest size in bytes of the given algorithm (the default output size for XOFs),
+ * or 0 if the algorithm is not supported.
+ */
+size_t crypto_output_size(uint32_t algorithm_id);
+
+/**
+ * Crypto block size
+ * Returns the internal block size in bytes of the given algorithm (the rate for SHA-3 and
+ * SHAKE), or 0 if the algorithm is not supported.
+ */
+size_t crypto_block_size(uint32_t algorithm_id);
+
+/**
+ * Crypto algorithm name
+ * Returns the nul-terminated name of the given algorithm, e.g. "SHA-256", or null if the
+ * algorithm is not supported. The string is static and must not be freed.
+ */
+const char *crypto_algorithm_name(uint32_t algorithm_id);
+
+/**
+ * Crypto algorithm count
+ * Returns the number of algorithms compiled into this library.
+ */
+size_t crypto_algorithm_count(void);
+
+/**
+ * Crypto algorithm ID
+ * Writes the ID of the algorithm at `index`, in increasing ID order, to `algorithm_id`.
+ * Together with `crypto_algorithm_count` this enumerates every supported algorithm.
+ *
+ * # Safety
+ * Pointer must not be null.
+ */
+CRYPTO_MUST_USE CryptoResult crypto_algorithm_id(size_t index, uint32_t *algorithm_id);
+
 /**
  * Crypto init
  * Initialize a context with a given algorithm.
@@ -77,10 +429,68 @@ extern "C" {
  */
 CRYPTO_MUST_USE CryptoResult crypto_init(CryptoContext *ctx, uint32_t algorithm_id);
 
+/**
+ * Crypto HMAC init
+ * Initialize a context for HMAC with a given hash algorithm and key.
+ *
+ * The context is then used with `crypto_update` and `crypto_finalize` exactly as a plain hash
+ * context, and `crypto_finalize` outputs the MAC. After finalizing, the context is reset to its
+ * keyed initial state and can be used to authenticate another message with the same key.
+ *
+ * # Safety
+ * Pointers must not be null and key length must be correct.
+ */
+CRYPTO_MUST_USE
+CryptoResult crypto_hmac_init(CryptoContext *ctx,
+                              uint32_t algorithm_id,
+                              const uint8_t *key,
+                              size_t key_length);
+
+#if (defined(BLAKE2) || defined(BLAKE3))
+/**
+ * Crypto keyed init
+ * Initialize a context for the native keyed mode of a given algorithm.
+ *
+ * Supported for BLAKE2b (keys up to 64 bytes), BLAKE2s (keys up to 32 bytes) and BLAKE3 (keys
+ * of exactly 32 bytes). Other key lengths return `BadParameter`, and algorithms without a
+ * keyed mode return `BadOrUnsupportedAlgorithm`. The context is then used with `crypto_update`
+ * and `crypto_finalize` as a plain hash context.
+ *
+ * # Safety
+ * Pointers must not be null and key length must be correct.
+ */
+CRYPTO_MUST_USE
+CryptoResult crypto_keyed_init(CryptoContext *ctx,
+                               uint32_t algorithm_id,
+                               const uint8_t *key,
+                               size_t key_length);
+#endif
+
+#if defined(BLAKE3)
+/**
+ * Crypto derive key init
+ * Initialize a BLAKE3 context in derive key mode with the given context string.
+ *
+ * The context string must be UTF-8 encoded, otherwise `BadParameter` is returned. The key
+ * material is then input with `crypto_update`, and the derived key is read with
+ * `crypto_finalize` or `crypto_squeeze`.
+ *
+ * # Safety
+ * Pointers must not be null and context length must be correct.
+ */
+CRYPTO_MUST_USE
+CryptoResult crypto_derive_key_init(CryptoContext *ctx,
+                                    const uint8_t *context,
+                                    size_t context_length);
+#endif
+
 /**
  * Crypto update
  * Updates internal hash context with input buffer
  *
+ * Contexts that were not set up by one of the init functions, or were overwritten since, return
+ * `UninitializedOrCorruptedContext`.
+ *
  * # Safety
  * Pointers must not be null and input length must be correct.
  */
@@ -99,6 +509,9 @@ CryptoResult crypto_update(CryptoContext *ctx,
  * the user reads the doc and uses well-formed arrays of proper size, then the API could support
  * additional algorithms (smaller and larger outputs).
  *
+ * Contexts that were not set up by one of the init functions, or were overwritten since, return
+ * `UninitializedOrCorruptedContext`.
+ *
  * # Safety
  * Pointers must not be null.
  */
@@ -107,6 +520,766 @@ CryptoResult crypto_finalize(CryptoContext *ctx,
                              uint8_t *output,
                              size_t output_length);
 
+/**
+ * Crypto finalize verify
+ * Finalizes the digest (or MAC) computation and compares it with `expected` in constant time,
+ * without exposing the computed value.
+ *
+ * Returns `VerificationFailed` if they differ. The expected length must match the digest size
+ * of the algorithm, otherwise `BadBufferOutputSize` is returned and the context is untouched.
+ * As with `crypto_finalize`, the context is reset afterwards.
+ *
+ * # Safety
+ * Pointers must not be null and expected length must be correct.
+ */
+CRYPTO_MUST_USE
+CryptoResult crypto_finalize_verify(CryptoContext *ctx,
+                                    const uint8_t *expected,
+                                    size_t expected_length);
+
+/**
+ * Crypto verify
+ * Compares two buffers of `length` bytes, e.g. a computed and a received MAC, in constant time.
+ *
+ * Returns `Success` if they are equal and `VerificationFailed` otherwise. Unlike `memcmp`, the
+ * time taken does not depend on the position of the first difference.
+ *
+ * # Safety
+ * Pointers must not be null and length must be correct.
+ */
+CRYPTO_MUST_USE CryptoResult crypto_verify(const uint8_t *a, const uint8_t *b, size_t length);
+
+/**
+ * Crypto squeeze
+ * Reads the next `output_length` bytes of output from an extendable-output function context
+ * (SHAKE128, SHAKE256 or BLAKE3).
+ *
+ * The first call ends the absorb phase, and following calls continue the output stream where the
+ * previous one stopped, so the output can be read in pieces of any length. Once squeezing, the
+ * context no longer accepts `crypto_update` nor `crypto_finalize`, which return
+ * `UninitializedOrCorruptedContext`; call `crypto_init` to start over. Contexts of fixed-output
+ * algorithms return `BadOrUnsupportedAlgorithm`.
+ *
+ * # Safety
+ * Pointers must not be null and output length must be correct.
+ */
+CRYPTO_MUST_USE
+CryptoResult crypto_squeeze(CryptoContext *ctx,
+                            uint8_t *output,
+                            size_t output_length);
+
+/**
+ * Crypto destroy
+ * Wipes a context, including any key material and buffered input, with writes that the
+ * optimizer cannot elide.
+ *
+ * The context is left uninitialized, so using it returns `UninitializedOrCorruptedContext`
+ * until it is initialized again. Call this once a context is no longer needed, as finalizing
+ * only resets a context for reuse and keyed contexts keep their key.
+ *
+ * # Safety
+ * Pointer must not be null.
+ */
+CRYPTO_MUST_USE CryptoResult crypto_destroy(CryptoContext *ctx);
+
+/**
+ * Crypto clone
+ * Copies an initialized context, including any absorbed input, into another context.
+ *
+ * This allows hashing a common prefix once and then finishing several messages from it. Use
+ * `crypto_export_state` to resume the context in another process.
+ *
+ * # Safety
+ * Pointers must not be null.
+ */
+CRYPTO_MUST_USE CryptoResult crypto_clone(CryptoContext *dst, const CryptoContext *src);
+
+/**
+ * Crypto export state
+ * Serializes an initialized context, including any absorbed input, into a versioned blob tagged
+ * with the algorithm, and writes its length to `output_length`.
+ *
+ * The blob can be resumed with `crypto_import_state`, also in another process or by another build
+ * of the library that has the algorithm compiled in. Only contexts of the SHA-2 and SHA-3
+ * families can be exported, SHAKE before squeezing included: HMAC, BLAKE2 and BLAKE3 contexts
+ * return `BadOrUnsupportedAlgorithm`. If the blob is longer than `output_capacity`,
+ * `BadBufferOutputSize` is returned and `output_length` still receives the required length,
+ * which is never more than `CRYPTO_STATE_MAX_SIZE`.
+ *
+ * # Safety
+ * Pointers must not be null and output capacity must be correct.
+ */
+CRYPTO_MUST_USE
+CryptoResult crypto_export_state(const CryptoContext *ctx,
+                                 uint8_t *output,
+                                 size_t output_capacity,
+                                 size_t *output_length);
+
+/**
+ * Crypto import state
+ * Initializes a context from a blob written by `crypto_export_state`.
+ *
+ * Damaged blobs, or blobs of another format version, return `UninitializedOrCorruptedContext`
+ * and blobs of algorithms that are not compiled in return `BadOrUnsupportedAlgorithm`. The
+ * context is left untouched on failure.
+ *
+ * # Safety
+ * Pointers must not be null and input length must be correct.
+ */
+CRYPTO_MUST_USE
+CryptoResult crypto_import_state(CryptoContext *ctx,
+                                 const uint8_t *input,
+                                 size_t input_length);
+
+/**
+ * Crypto new
+ * Allocates a handle initialized with a given algorithm.
+ *
+ * Returns null if the algorithm is not supported, with the reason in `crypto_last_error_detail`.
+ * The handle must be released with `crypto_free`.
+ */
+CryptoHandle *crypto_new(uint32_t algorithm_id);
+
+/**
+ * Crypto HMAC new
+ * Allocates a handle initialized for HMAC with a given hash algorithm and key.
+ *
+ * Returns null if the algorithm is not supported or the key is null, with the reason in
+ * `crypto_last_error_detail`. The handle must be released with `crypto_free`.
+ *
+ * # Safety
+ * Key length must be correct.
+ */
+CryptoHandle *crypto_hmac_new(uint32_t algorithm_id, const uint8_t *key, size_t key_length);
+
+/**
+ * Crypto handle update
+ * Updates the context of a handle with input buffer, as `crypto_update`.
+ *
+ * # Safety
+ * Pointers must not be null and input length must be correct. The handle must come from
+ * `crypto_new` or `crypto_hmac_new` and not be freed.
+ */
+CRYPTO_MUST_USE
+CryptoResult crypto_handle_update(CryptoHandle *handle,
+                                  const uint8_t *input,
+                                  size_t input_length);
+
+/**
+ * Crypto handle finalize
+ * Outputs the digest (or MAC) of the context of a handle and resets it, as `crypto_finalize`.
+ *
+ * # Safety
+ * Pointers must not be null and output length must be correct. The handle must come from
+ * `crypto_new` or `crypto_hmac_new` and not be freed.
+ */
+CRYPTO_MUST_USE
+CryptoResult crypto_handle_finalize(CryptoHandle *handle,
+                                    uint8_t *output,
+                                    size_t output_length);
+
+/**
+ * Crypto handle finalize verify
+ * Finalizes the context of a handle and compares the result with `expected` in constant time,
+ * as `crypto_finalize_verify`.
+ *
+ * # Safety
+ * Pointers must not be null and expected length must be correct. The handle must come from
+ * `crypto_new` or `crypto_hmac_new` and not be freed.
+ */
+CRYPTO_MUST_USE
+CryptoResult crypto_handle_finalize_verify(CryptoHandle *handle,
+                                           const uint8_t *expected,
+                                           size_t expected_length);
+
+/**
+ * Crypto handle squeeze
+ * Reads the next `output_length` bytes of output from the extendable-output function context of
+ * a handle, as `crypto_squeeze`.
+ *
+ * # Safety
+ * Pointers must not be null and output length must be correct. The handle must come from
+ * `crypto_new` or `crypto_hmac_new` and not be freed.
+ */
+CRYPTO_MUST_USE
+CryptoResult crypto_handle_squeeze(CryptoHandle *handle,
+                                   uint8_t *output,
+                                   size_t output_length);
+
+/**
+ * Crypto free
+ * Wipes and releases a handle. Null handles are ignored.
+ *
+ * # Safety
+ * The handle must come from `crypto_new` or `crypto_hmac_new` and not be freed already.
+ */
+void crypto_free(CryptoHandle *handle);
+
+/**
+ * Crypto hash
+ * Computes the digest of a single input buffer in one call, without a caller-owned context.
+ *
+ * The output length must match the digest size of the given algorithm.
+ *
+ * # Safety
+ * Pointers must not be null and lengths must be correct.
+ */
+CRYPTO_MUST_USE
+CryptoResult crypto_hash(uint32_t algorithm_id,
+                         const uint8_t *input,
+                         size_t input_length,
+                         uint8_t *output,
+                         size_t output_length);
+
+/**
+ * Crypto hash multi
+ * Computes the digest of the concatenation of `buffer_count` input buffers in one call,
+ * without a caller-owned context.
+ *
+ * The output length must match the digest size of the given algorithm.
+ *
+ * # Safety
+ * Pointers must not be null, including the data pointer of every buffer, and lengths must be
+ * correct.
+ */
+CRYPTO_MUST_USE
+CryptoResult crypto_hash_multi(uint32_t algorithm_id,
+                               const CryptoBuffer *buffers,
+                               size_t buffer_count,
+                               uint8_t *output,
+                               size_t output_length);
+
+#if defined(PARALLEL)
+/**
+ * Crypto hash file
+ * Computes the digest of the file at `path`, which is memory-mapped rather than read. BLAKE3
+ * hashes it on all cores, other algorithms on the calling thread.
+ *
+ * The output length must match the digest size of the given algorithm.
+ *
+ * # Safety
+ * Pointers must not be null, `path` must be nul-terminated and output length must be correct.
+ * The file is memory-mapped, and must not be truncated or modified until the call returns.
+ */
+CRYPTO_MUST_USE
+CryptoResult crypto_hash_file(uint32_t algorithm_id,
+                              const char *path,
+                              uint8_t *output,
+                              size_t output_length);
+#endif
+
+#if defined(PARALLEL)
+/**
+ * Crypto hash file tree
+ * Computes the tree mode digest of the file at `path`: the RFC 6962 Merkle tree hash of its
+ * `chunk_size` byte chunks, which are hashed in parallel on all cores with any algorithm.
+ *
+ * The digest depends on the chunk size and differs from the one of `crypto_hash_file`. The
+ * output length must match the digest size of the given algorithm, and a zero chunk size
+ * returns `BadParameter`.
+ *
+ * # Safety
+ * Pointers must not be null, `path` must be nul-terminated and output length must be correct.
+ * The file is memory-mapped, and must not be truncated or modified until the call returns.
+ */
+CRYPTO_MUST_USE
+CryptoResult crypto_hash_file_tree(uint32_t algorithm_id,
+                                   const char *path,
+                                   size_t chunk_size,
+                                   uint8_t *output,
+                                   size_t output_length);
+#endif
+
+#if defined(AEAD)
+/**
+ * Crypto AEAD seal
+ * Encrypts `input` and authenticates it together with the associated data. The output is the
+ * ciphertext followed by the tag, so `output_length` must be `input_length +
+ * CRYPTO_AEAD_TAG_SIZE`.
+ *
+ * Keys are `CRYPTO_AEAD_KEY_SIZE` bytes and nonces `CRYPTO_AEAD_NONCE_SIZE` bytes long, other
+ * lengths return `BadParameter`. A nonce must never be used twice with the same key. `output`
+ * may be the same pointer as `input` to encrypt in place, with room for the tag after the data.
+ *
+ * Parameters are checked before anything is written to the output, which is zeroed if the
+ * encryption itself fails, so plaintext is never left in it on error.
+ *
+ * # Safety
+ * Pointers must not be null, even for empty buffers, and lengths must be correct.
+ */
+CRYPTO_MUST_USE
+CryptoResult crypto_aead_seal(uint32_t aead_id,
+                              const uint8_t *key,
+                              size_t key_length,
+                              const uint8_t *nonce,
+                              size_t nonce_length,
+                              const uint8_t *associated_data,
+                              size_t associated_data_length,
+                              const uint8_t *input,
+                              size_t input_length,
+                              uint8_t *output,
+                              size_t output_length);
+#endif
+
+#if defined(AEAD)
+/**
+ * Crypto AEAD open
+ * Checks and decrypts the output of `crypto_aead_seal`: `input` is the ciphertext followed by
+ * the tag, and `output_length` must be `input_length - CRYPTO_AEAD_TAG_SIZE`.
+ *
+ * Returns `VerificationFailed` if the ciphertext, the associated data, the key or the nonce
+ * differ from the sealed ones, in which case the output is zeroed. `output` may be the same
+ * pointer as `input` to decrypt in place.
+ *
+ * # Safety
+ * Pointers must not be null, even for empty buffers, and lengths must be correct.
+ */
+CRYPTO_MUST_USE
+CryptoResult crypto_aead_open(uint32_t aead_id,
+                              const uint8_t *key,
+                              size_t key_length,
+                              const uint8_t *nonce,
+                              size_t nonce_length,
+                              const uint8_t *associated_data,
+                              size_t associated_data_length,
+                              const uint8_t *input,
+                              size_t input_length,
+                              uint8_t *output,
+                              size_t output_length);
+#endif
+
+#if defined(ED25519)
+/**
+ * Crypto Ed25519 public key
+ * Derives the public key of an Ed25519 seed (RFC 8032 key generation).
+ *
+ * # Safety
+ * Pointers must not be null.
+ */
+CRYPTO_MUST_USE
+CryptoResult crypto_ed25519_public_key(const CryptoEd25519Seed *seed,
+                                       CryptoEd25519PublicKey *public_key);
+#endif
+
+#if defined(ED25519)
+/**
+ * Crypto Ed25519 sign
+ * Signs a message with the key derived from an Ed25519 seed. Signatures are deterministic, so
+ * no random source is needed.
+ *
+ * # Safety
+ * Pointers must not be null and message length must be correct.
+ */
+CRYPTO_MUST_USE
+CryptoResult crypto_ed25519_sign(const CryptoEd25519Seed *seed,
+                                 const uint8_t *message,
+                                 size_t message_length,
+                                 CryptoEd25519Signature *signature);
+#endif
+
+#if defined(ED25519)
+/**
+ * Crypto Ed25519 verify
+ * Verifies an Ed25519 signature of a message.
+ *
+ * Returns `VerificationFailed` if the signature does not hold, and `BadParameter` if the public
+ * key is not a valid curve point. Verification is strict: non-canonical signatures and
+ * small-order public keys are rejected.
+ *
+ * # Safety
+ * Pointers must not be null and message length must be correct.
+ */
+CRYPTO_MUST_USE
+CryptoResult crypto_ed25519_verify(const CryptoEd25519PublicKey *public_key,
+                                   const uint8_t *message,
+                                   size_t message_length,
+                                   const CryptoEd25519Signature *signature);
+#endif
+
+#if defined(X25519)
+/**
+ * Crypto X25519 public key
+ * Derives the public key of an X25519 secret key, to be sent to the peer.
+ *
+ * # Safety
+ * Pointers must not be null.
+ */
+CRYPTO_MUST_USE
+CryptoResult crypto_x25519_public_key(const CryptoX25519SecretKey *secret_key,
+                                      CryptoX25519PublicKey *public_key);
+#endif
+
+#if defined(X25519)
+/**
+ * Crypto X25519 shared secret
+ * Computes the shared secret of our secret key and the public key of the peer (RFC 7748 ECDH).
+ *
+ * Returns `ZeroSharedSecret` if the peer public key is a low-order point, which gives an
+ * all-zero secret that does not depend on our key; the output is then left zeroed. The secret
+ * should be passed through a KDF before use, see `crypto_x25519_hkdf`.
+ *
+ * # Safety
+ * Pointers must not be null.
+ */
+CRYPTO_MUST_USE
+CryptoResult crypto_x25519_shared_secret(const CryptoX25519SecretKey *secret_key,
+                                         const CryptoX25519PublicKey *peer_public_key,
+                                         CryptoX25519SharedSecret *shared_secret);
+#endif
+
+#if defined(X25519)
+/**
+ * Crypto X25519 HKDF
+ * Computes the X25519 shared secret as `crypto_x25519_shared_secret`, and derives
+ * `okm_length` bytes of key material from it with HKDF, as `crypto_hkdf_extract` followed by
+ * `crypto_hkdf_expand` with the same hash algorithm ID would. The shared secret itself is
+ * never exposed.
+ *
+ * The output can be at most 255 times the digest size long. Both parties get the same key
+ * material when they use the same salt and info.
+ *
+ * # Safety
+ * Pointers must not be null, even for an empty salt or info, and lengths must be correct.
+ */
+CRYPTO_MUST_USE
+CryptoResult crypto_x25519_hkdf(uint32_t algorithm_id,
+                                const CryptoX25519SecretKey *secret_key,
+                                const CryptoX25519PublicKey *peer_public_key,
+                                const uint8_t *salt,
+                                size_t salt_length,
+                                const uint8_t *info,
+                                size_t info_length,
+                                uint8_t *okm,
+                                size_t okm_length);
+#endif
+
+#if defined(RANDOM)
+/**
+ * Crypto random bytes
+ * Fills a buffer with random bytes from the operating system entropy source.
+ *
+ * Returns `Failure`, with the OS error in `crypto_last_error_detail`, if the entropy source
+ * cannot be read.
+ *
+ * # Safety
+ * Pointers must not be null and output length must be correct.
+ */
+CRYPTO_MUST_USE CryptoResult crypto_random_bytes(uint8_t *output, size_t output_length);
+#endif
+
+/**
+ * Crypto DRBG instantiate
+ * Allocates a Hash_DRBG generator (NIST SP 800-90A) with a SHA-2 or SHA-3 algorithm, seeded
+ * from an entropy input, a nonce and a personalization string.
+ *
+ * The output is fully determined by the seed and later inputs, so a fixed seed gives
+ * reproducible streams for tests. The entropy input must be at least 32 bytes long (24 bytes for
+ * SHA-224 and SHA-512/224); the nonce and personalization string may be empty.
+ *
+ * Returns null if a pointer is null, or if the algorithm is not supported or the entropy input
+ * is too short, with the reason in `crypto_last_error_detail`. The generator must be released
+ * with `crypto_drbg_free`.
+ *
+ * # Safety
+ * Pointers must not be null and lengths must be correct.
+ */
+CryptoDrbg *crypto_drbg_instantiate(uint32_t algorithm_id,
+                                    const uint8_t *entropy,
+                                    size_t entropy_length,
+                                    const uint8_t *nonce,
+                                    size_t nonce_length,
+                                    const uint8_t *personalization,
+                                    size_t personalization_length);
+
+/**
+ * Crypto DRBG reseed
+ * Reseeds a Hash_DRBG generator with fresh entropy and optional additional input, which may be
+ * empty.
+ *
+ * Returns `BadParameter` if the entropy input is shorter than for `crypto_drbg_instantiate`.
+ *
+ * # Safety
+ * Pointers must not be null and lengths must be correct. The generator must come from
+ * `crypto_drbg_instantiate` and not be freed.
+ */
+CRYPTO_MUST_USE
+CryptoResult crypto_drbg_reseed(CryptoDrbg *drbg,
+                                const uint8_t *entropy,
+                                size_t entropy_length,
+                                const uint8_t *additional_input,
+                                size_t additional_input_length);
+
+/**
+ * Crypto DRBG generate
+ * Fills a buffer with pseudorandom bytes from a Hash_DRBG generator, after mixing in optional
+ * additional input, which may be empty.
+ *
+ * Returns `BadBufferOutputSize` if more than `CRYPTO_DRBG_MAX_REQUEST_SIZE` bytes are requested.
+ *
+ * # Safety
+ * Pointers must not be null and lengths must be correct. The generator must come from
+ * `crypto_drbg_instantiate` and not be freed.
+ */
+CRYPTO_MUST_USE
+CryptoResult crypto_drbg_generate(CryptoDrbg *drbg,
+                                  uint8_t *output,
+                                  size_t output_length,
+                                  const uint8_t *additional_input,
+                                  size_t additional_input_length);
+
+/**
+ * Crypto DRBG free
+ * Releases a Hash_DRBG generator, zeroizing its state. Null generators are ignored.
+ *
+ * # Safety
+ * The generator must come from `crypto_drbg_instantiate` and not be freed already.
+ */
+void crypto_drbg_free(CryptoDrbg *drbg);
+
+/**
+ * Crypto Merkle new
+ * Allocates an empty Merkle tree (RFC 6962) with a given hash algorithm.
+ *
+ * Returns null if the algorithm is not supported, with the reason in `crypto_last_error_detail`.
+ * The tree must be released with `crypto_merkle_free`.
+ */
+CryptoMerkleTree *crypto_merkle_new(uint32_t algorithm_id);
+
+/**
+ * Crypto Merkle append
+ * Appends a leaf to a Merkle tree. The leaf is hashed as `H(0x00 || leaf)`.
+ *
+ * # Safety
+ * Pointers must not be null and leaf length must be correct. The tree must come from
+ * `crypto_merkle_new` and not be freed.
+ */
+CRYPTO_MUST_USE
+CryptoResult crypto_merkle_append(CryptoMerkleTree *tree,
+                                  const uint8_t *leaf,
+                                  size_t leaf_length);
+
+/**
+ * Crypto Merkle leaf count
+ * Returns the number of leaves of a Merkle tree, or 0 if the tree is null.
+ *
+ * # Safety
+ * The tree must come from `crypto_merkle_new` and not be freed.
+ */
+size_t crypto_merkle_leaf_count(const CryptoMerkleTree *tree);
+
+/**
+ * Crypto Merkle root
+ * Writes the root hash of a Merkle tree. The root of the empty tree is the hash of the empty
+ * string.
+ *
+ * The output length must match the digest size of the algorithm of the tree.
+ *
+ * # Safety
+ * Pointers must not be null and output length must be correct. The tree must come from
+ * `crypto_merkle_new` and not be freed.
+ */
+CRYPTO_MUST_USE
+CryptoResult crypto_merkle_root(const CryptoMerkleTree *tree,
+                                uint8_t *output,
+                                size_t output_length);
+
+/**
+ * Crypto Merkle inclusion proof
+ * Writes the audit path of the leaf at `index` to `proof`, as concatenated hashes, and its
+ * length in bytes to `proof_length`.
+ *
+ * Returns `BadParameter` if there is no leaf at `index`. If the proof is longer than
+ * `proof_capacity`, `BadBufferOutputSize` is returned and `proof_length` still receives the
+ * required length. A proof has at most 64 hashes.
+ *
+ * # Safety
+ * Pointers must not be null and proof capacity must be correct. The tree must come from
+ * `crypto_merkle_new` and not be freed.
+ */
+CRYPTO_MUST_USE
+CryptoResult crypto_merkle_inclusion_proof(const CryptoMerkleTree *tree,
+                                           size_t index,
+                                           uint8_t *proof,
+                                           size_t proof_capacity,
+                                           size_t *proof_length);
+
+/**
+ * Crypto Merkle consistency proof
+ * Writes the proof that the tree made of the first `old_size` leaves is a prefix of the current
+ * tree to `proof`, as concatenated hashes, and its length in bytes to `proof_length`.
+ *
+ * Returns `BadParameter` unless `old_size` is between 1 and the number of leaves. Buffers that
+ * are too small are handled as by `crypto_merkle_inclusion_proof`.
+ *
+ * # Safety
+ * Pointers must not be null and proof capacity must be correct. The tree must come from
+ * `crypto_merkle_new` and not be freed.
+ */
+CRYPTO_MUST_USE
+CryptoResult crypto_merkle_consistency_proof(const CryptoMerkleTree *tree,
+                                             size_t old_size,
+                                             uint8_t *proof,
+                                             size_t proof_capacity,
+                                             size_t *proof_length);
+
+/**
+ * Crypto Merkle verify inclusion
+ * Verifies that `leaf` is the leaf at `index` of the tree of `tree_size` leaves with the given
+ * root, using an audit path from `crypto_merkle_inclusion_proof`.
+ *
+ * Returns `VerificationFailed` if the proof does not hold, and `BadBufferOutputSize` if the
+ * root length is not the digest size of the algorithm.
+ *
+ * # Safety
+ * Pointers must not be null and lengths must be correct.
+ */
+CRYPTO_MUST_USE
+CryptoResult crypto_merkle_verify_inclusion(uint32_t algorithm_id,
+                                            const uint8_t *leaf,
+                                            size_t leaf_length,
+                                            size_t index,
+                                            size_t tree_size,
+                                            const uint8_t *proof,
+                                            size_t proof_length,
+                                            const uint8_t *root,
+                                            size_t root_length);
+
+/**
+ * Crypto Merkle verify consistency
+ * Verifies that the tree of `old_size` leaves with root `old_root` is a prefix of the tree of
+ * `new_size` leaves with root `new_root`, using a proof from `crypto_merkle_consistency_proof`.
+ *
+ * Both roots are `root_length` bytes long. Returns `VerificationFailed` if the proof does not
+ * hold, and `BadBufferOutputSize` if the root length is not the digest size of the algorithm.
+ *
+ * # Safety
+ * Pointers must not be null and lengths must be correct.
+ */
+CRYPTO_MUST_USE
+CryptoResult crypto_merkle_verify_consistency(uint32_t algorithm_id,
+                                              size_t old_size,
+                                              size_t new_size,
+                                              const uint8_t *proof,
+                                              size_t proof_length,
+                                              const uint8_t *old_root,
+                                              const uint8_t *new_root,
+                                              size_t root_length);
+
+/**
+ * Crypto Merkle free
+ * Releases a Merkle tree. Null trees are ignored.
+ *
+ * # Safety
+ * The tree must come from `crypto_merkle_new` and not be freed already.
+ */
+void crypto_merkle_free(CryptoMerkleTree *tree);
+
+/**
+ * Crypto HKDF extract
+ * Derives a pseudorandom key from input keying material and a salt (RFC 5869 HKDF-Extract).
+ *
+ * The output length must be the digest size of the given algorithm. An empty salt is equivalent
+ * to the RFC default salt of digest size zero bytes.
+ *
+ * # Safety
+ * Pointers must not be null and lengths must be correct.
+ */
+CRYPTO_MUST_USE
+CryptoResult crypto_hkdf_extract(uint32_t algorithm_id,
+                                 const uint8_t *salt,
+                                 size_t salt_length,
+                                 const uint8_t *ikm,
+                                 size_t ikm_length,
+                                 uint8_t *prk,
+                                 size_t prk_length);
+
+/**
+ * Crypto HKDF expand
+ * Expands a pseudorandom key into output keying material bound to `info` (RFC 5869
+ * HKDF-Expand).
+ *
+ * The output length can be at most 255 times the digest size of the given algorithm, larger
+ * outputs return `BadBufferOutputSize`. The pseudorandom key should be at least the digest size,
+ * e.g. the output of `crypto_hkdf_extract`.
+ *
+ * # Safety
+ * Pointers must not be null and lengths must be correct.
+ */
+CRYPTO_MUST_USE
+CryptoResult crypto_hkdf_expand(uint32_t algorithm_id,
+                                const uint8_t *prk,
+                                size_t prk_length,
+                                const uint8_t *info,
+                                size_t info_length,
+                                uint8_t *okm,
+                                size_t okm_length);
+
+#if defined(ARGON2)
+/**
+ * Crypto Argon2id hash
+ * Hashes a password with Argon2id (RFC 9106) and writes it to `phc` as a null-terminated PHC
+ * string, e.g. `$argon2id$v=19$m=19456,t=2,p=1$<salt>$<hash>`, to be stored and later checked
+ * with `crypto_argon2id_verify`.
+ *
+ * The salt must be unique per password, ideally 16 bytes from `crypto_random_bytes`, and between
+ * 8 and 48 bytes long. Returns `BadParameter` if the salt length or a cost parameter is out of
+ * range, and `BadBufferOutputSize` if the string does not fit `phc_capacity` bytes, which never
+ * happens with `CRYPTO_ARGON2_PHC_MAX_SIZE` bytes.
+ *
+ * # Safety
+ * Pointers must not be null and lengths must be correct.
+ */
+CRYPTO_MUST_USE
+CryptoResult crypto_argon2id_hash(const uint8_t *password,
+                                  size_t password_length,
+                                  const uint8_t *salt,
+                                  size_t salt_length,
+                                  const CryptoArgon2Params *params,
+                                  char *phc,
+                                  size_t phc_capacity);
+#endif
+
+#if defined(ARGON2)
+/**
+ * Crypto Argon2id verify
+ * Verifies a password against a null-terminated PHC string from `crypto_argon2id_hash`, using
+ * the cost parameters, salt and hash length of the string.
+ *
+ * Returns `VerificationFailed` if the password does not match, `BadParameter` if the string
+ * cannot be parsed or its parameters are out of range, and `BadOrUnsupportedAlgorithm` if it is
+ * not an Argon2id hash.
+ *
+ * # Safety
+ * Pointers must not be null, password length must be correct and the string must be null
+ * terminated.
+ */
+CRYPTO_MUST_USE
+CryptoResult crypto_argon2id_verify(const uint8_t *password,
+                                    size_t password_length,
+                                    const char *phc);
+#endif
+
+/**
+ * Crypto PBKDF2
+ * Derives a key from a password and a salt using PBKDF2 with HMAC of the given algorithm
+ * (RFC 8018).
+ *
+ * The iteration count must be between 1 and `CRYPTO_PBKDF2_MAX_ITERATIONS`, otherwise
+ * `BadParameter` is returned.
+ *
+ * # Safety
+ * Pointers must not be null and lengths must be correct.
+ */
+CRYPTO_MUST_USE
+CryptoResult crypto_pbkdf2(uint32_t algorithm_id,
+                           const uint8_t *password,
+                           size_t password_length,
+                           const uint8_t *salt,
+                           size_t salt_length,
+                           uint32_t iterations,
+                           uint8_t *output,
+                           size_t output_length);
+
 #ifdef __cplusplus
 } // extern "C"
 #endif // __cplusplus
@@ -5,19 +5,21 @@
 int main() {
 
     CryptoContext ctx = {0};
+    CryptoResult result;
     uint8_t digest[32] = {0};
     uint8_t input[5] = {0x68, 0x65, 0x6c, 0x6c, 0x6f};
 
-    if(crypto_init(&ctx, SHA3_256_ALG_ID) != Success) {
-        printf("Error while initializing\n");
+    if((result = crypto_init(&ctx, SHA3_256_ALG_ID)) != Success) {
+        printf("Error while initializing: %s\n", crypto_result_str(result));
         return 1;
     }
-    if (crypto_update(&ctx, input, 5) != Success) {
-        printf("Error while updating\n");
+    if ((result = crypto_update(&ctx, input, 5)) != Success) {
+        printf("Error while updating: %s\n", crypto_result_str(result));
         return 1;
     }
-    if (crypto_finalize(&ctx, digest, sizeof(digest)) != Success) {
-        printf("Error while finalizing\n");
+    if ((result = crypto_finalize(&ctx, digest, sizeof(digest))) != Success) {
+        const char *detail = crypto_last_error_detail();
+        printf("Error while finalizing: %s (%s)\n", crypto_result_str(result), detail ? detail : "no detail");
         return 1;
 
     }
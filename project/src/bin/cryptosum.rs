@@ -0,0 +1,570 @@
+//! `cryptosum`: prints or checks checksums of files with any algorithm of the library.
+//!
+//! The command line and the output formats follow GNU coreutils `sha256sum`, so existing
+//! checksum files and scripts keep working:
+//!
+//! ```text
+//! cryptosum [-a ALGORITHM] [-b | -t] [--tag] [-z] [FILE]...
+//! cryptosum [-a ALGORITHM] -c [--ignore-missing] [--quiet | --status] [--strict] [-z] [FILE]...
+//! ```
+
+use cryptoapi::{Algorithm, Hasher};
+use std::ffi::{OsStr, OsString};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Usage: cryptosum [OPTION]... [FILE]...
+Print or check checksums. With no FILE, or when FILE is -, read standard input.
+
+  -a, --algorithm NAME  digest algorithm (default SHA-256), one of:
+                        {algorithms}
+  -b, --binary          read in binary mode
+  -c, --check           read checksums from the FILEs and check them
+      --tag             print BSD-style checksums
+  -t, --text            read in text mode (default)
+  -z, --zero            end each output line with NUL, not newline, and disable file
+                        name escaping; with --check, read NUL-terminated lines
+  -h, --help            display this help and exit
+
+The following options are useful only when verifying checksums:
+      --ignore-missing  don't fail or report status for missing files
+      --quiet           don't print OK for each successfully verified file
+      --status          don't output anything, status code shows success
+      --strict          exit non-zero for improperly formatted checksum lines
+";
+
+/// Parsed command line.
+#[derive(Debug, PartialEq)]
+struct Options {
+    algorithm: Algorithm,
+    /// `-b` (true) or `-t` (false). Binary mode marks files with '*' in checksum lines, but
+    /// both modes read files the same way.
+    binary: Option<bool>,
+    tag: bool,
+    zero: bool,
+    check: bool,
+    ignore_missing: bool,
+    quiet: bool,
+    status: bool,
+    strict: bool,
+    files: Vec<OsString>,
+}
+
+/// Outcome of parsing the command line.
+#[derive(Debug, PartialEq)]
+enum Command {
+    Run(Options),
+    Help,
+}
+
+fn parse_args(args: impl IntoIterator<Item = OsString>) -> Result<Command, String> {
+    let mut options = Options {
+        algorithm: Algorithm::Sha256,
+        binary: None,
+        tag: false,
+        zero: false,
+        check: false,
+        ignore_missing: false,
+        quiet: false,
+        status: false,
+        strict: false,
+        files: Vec::new(),
+    };
+    let mut args = args.into_iter();
+    let mut only_files = false;
+    while let Some(arg) = args.next() {
+        let option = match arg.to_str() {
+            Some(option) if !only_files && option.starts_with('-') && option != "-" => option,
+            _ => {
+                options.files.push(arg);
+                continue;
+            }
+        };
+        // Accepts "-a NAME", "-aNAME", "--algorithm NAME" and "--algorithm=NAME".
+        let algorithm = if let Some(name) = option.strip_prefix("--algorithm=") {
+            Some(name.to_owned())
+        } else if option == "-a" || option == "--algorithm" {
+            let name = args
+                .next()
+                .ok_or_else(|| format!("option '{option}' requires an argument"))?;
+            Some(name.to_string_lossy().into_owned())
+        } else if option.starts_with("--") {
+            None
+        } else {
+            option.strip_prefix("-a").map(str::to_owned)
+        };
+        if let Some(name) = algorithm {
+            options.algorithm = name
+                .parse()
+                .map_err(|_| format!("unknown algorithm '{name}'"))?;
+            continue;
+        }
+        match option {
+            "--" => only_files = true,
+            "-b" | "--binary" => options.binary = Some(true),
+            "-t" | "--text" => options.binary = Some(false),
+            "--tag" => options.tag = true,
+            "-z" | "--zero" => options.zero = true,
+            "-c" | "--check" => options.check = true,
+            "--ignore-missing" => options.ignore_missing = true,
+            "--quiet" => options.quiet = true,
+            "--status" => options.status = true,
+            "--strict" => options.strict = true,
+            "-h" | "--help" => return Ok(Command::Help),
+            _ => return Err(format!("unrecognized option '{option}'")),
+        }
+    }
+    if options.tag && options.check {
+        return Err("the --tag option is meaningless when verifying checksums".to_owned());
+    }
+    if options.binary.is_some() && options.check {
+        return Err(
+            "the --binary and --text options are meaningless when verifying checksums".to_owned(),
+        );
+    }
+    if options.tag && options.binary == Some(false) {
+        return Err("--tag does not support --text mode".to_owned());
+    }
+    if (options.ignore_missing || options.quiet || options.status || options.strict)
+        && !options.check
+    {
+        return Err(
+            "the --ignore-missing, --quiet, --status and --strict options are only \
+                    meaningful when verifying checksums"
+                .to_owned(),
+        );
+    }
+    if options.files.is_empty() {
+        options.files.push("-".into());
+    }
+    Ok(Command::Run(options))
+}
+
+/// Opens a file, or standard input for "-".
+///
+/// Standard input is not locked, so that a checksum file read from it can list "-" too.
+fn open(file: impl AsRef<OsStr>) -> io::Result<Box<dyn Read>> {
+    let file = file.as_ref();
+    if file == "-" {
+        Ok(Box::new(io::stdin()))
+    } else {
+        Ok(Box::new(File::open(file)?))
+    }
+}
+
+fn hash_reader(algorithm: Algorithm, mut reader: impl Read) -> io::Result<Vec<u8>> {
+    let mut hasher = Hasher::new(algorithm);
+    io::copy(&mut reader, &mut hasher)?;
+    hasher.finalize_to_vec().map_err(io::Error::other)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) || !hex.bytes().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
+
+/// Name of the algorithm in BSD-style lines, e.g. "SHA256" or "SHA3-256" as coreutils prints.
+fn tag_name(algorithm: Algorithm) -> String {
+    match algorithm.name().strip_prefix("SHA-") {
+        Some(size) => format!("SHA{size}"),
+        None => algorithm.name().to_owned(),
+    }
+}
+
+/// Formats a checksum line. As coreutils does, names containing a backslash or a newline are
+/// escaped and the line is then prefixed with a backslash, unless lines end with NUL.
+fn format_line(options: &Options, digest: &[u8], file: &str) -> String {
+    let escaped = !options.zero && file.contains(['\\', '\n']);
+    let file = if escaped {
+        file.replace('\\', "\\\\").replace('\n', "\\n")
+    } else {
+        file.to_owned()
+    };
+    let prefix = if escaped { "\\" } else { "" };
+    if options.tag {
+        format!(
+            "{prefix}{} ({file}) = {}",
+            tag_name(options.algorithm),
+            to_hex(digest)
+        )
+    } else {
+        let mode = if options.binary == Some(true) {
+            '*'
+        } else {
+            ' '
+        };
+        format!("{prefix}{} {mode}{file}", to_hex(digest))
+    }
+}
+
+/// A line of a checksum file.
+#[derive(Debug, PartialEq)]
+struct Entry {
+    algorithm: Algorithm,
+    digest: Vec<u8>,
+    /// Whether the line marks the file with '*' as read in binary mode. BSD-style lines always
+    /// do.
+    binary: bool,
+    file: String,
+}
+
+fn unescape(file: &str) -> Option<String> {
+    let mut unescaped = String::with_capacity(file.len());
+    let mut chars = file.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next()? {
+                '\\' => unescaped.push('\\'),
+                'n' => unescaped.push('\n'),
+                _ => return None,
+            },
+            c => unescaped.push(c),
+        }
+    }
+    Some(unescaped)
+}
+
+/// Parses a GNU ("HEX  FILE" or "HEX *FILE") or a BSD-style ("NAME (FILE) = HEX") line. GNU
+/// lines do not name their algorithm, `algorithm` is used for them.
+fn parse_line(line: &str, algorithm: Algorithm) -> Option<Entry> {
+    let (escaped, line) = match line.strip_prefix('\\') {
+        Some(line) => (true, line),
+        None => (false, line),
+    };
+    let bsd = line.split_once(" (").and_then(|(name, rest)| {
+        let (file, hex) = rest.rsplit_once(") = ")?;
+        Some((name.parse().ok()?, file, hex))
+    });
+    let (algorithm, binary, file, hex) = match bsd {
+        Some((algorithm, file, hex)) => (algorithm, true, file, hex),
+        None => {
+            let (hex, rest) = line.split_once(' ')?;
+            let binary = rest.starts_with('*');
+            let file = rest.strip_prefix([' ', '*'])?;
+            (algorithm, binary, file, hex)
+        }
+    };
+    let digest = from_hex(hex).filter(|digest| digest.len() == algorithm.output_size())?;
+    let file = if escaped {
+        unescape(file)?
+    } else {
+        file.to_owned()
+    };
+    Some(Entry {
+        algorithm,
+        digest,
+        binary,
+        file,
+    })
+}
+
+/// Parses a line read from a checksum file, without its delimiter. Lines that are not valid
+/// UTF-8 are improperly formatted.
+fn parse_raw_line(mut line: Vec<u8>, options: &Options) -> Option<Entry> {
+    if !options.zero && line.ends_with(b"\r") {
+        line.pop();
+    }
+    let line = String::from_utf8(line).ok()?;
+    parse_line(&line, options.algorithm)
+}
+
+/// Prints the checksum of every file. Returns false if a file could not be read.
+fn compute(options: &Options) -> bool {
+    let mut stdout = io::stdout().lock();
+    let mut success = true;
+    for file in &options.files {
+        let name = file.to_string_lossy();
+        match open(file).and_then(|reader| hash_reader(options.algorithm, reader)) {
+            Ok(digest) => {
+                let line = format_line(options, &digest, &name);
+                let end = if options.zero { '\0' } else { '\n' };
+                if write!(stdout, "{line}{end}").is_err() {
+                    return false;
+                }
+            }
+            Err(error) => {
+                eprintln!("cryptosum: {name}: {error}");
+                success = false;
+            }
+        }
+    }
+    success
+}
+
+/// Verifies the checksums listed in every file. Returns false if a checksum did not match, a
+/// listed file could not be read, or a checksum file had no valid line.
+///
+/// Lines that are not valid UTF-8 count as improperly formatted, and only fail the check with
+/// `--strict`. With `--ignore-missing`, files that do not exist are skipped, but a checksum file
+/// must still verify at least one.
+fn check(options: &Options) -> bool {
+    let mut stdout = io::stdout().lock();
+    let mut success = true;
+    for checksum_file in &options.files {
+        let checksum_name = checksum_file.to_string_lossy();
+        let reader = match open(checksum_file) {
+            Ok(reader) => BufReader::new(reader),
+            Err(error) => {
+                eprintln!("cryptosum: {checksum_name}: {error}");
+                success = false;
+                continue;
+            }
+        };
+        let (mut checked, mut improperly_formatted, mut unreadable, mut mismatched) = (0, 0, 0, 0);
+        let mut missing = 0;
+        let delimiter = if options.zero { b'\0' } else { b'\n' };
+        for line in reader.split(delimiter) {
+            let line = match line {
+                Ok(line) => line,
+                Err(error) => {
+                    eprintln!("cryptosum: {checksum_name}: {error}");
+                    success = false;
+                    break;
+                }
+            };
+            let Some(entry) = parse_raw_line(line, options) else {
+                improperly_formatted += 1;
+                continue;
+            };
+            checked += 1;
+            // A listed "-" is standard input, as in GNU checksum files.
+            let result = match open(&entry.file).and_then(|f| hash_reader(entry.algorithm, f)) {
+                Ok(digest) if cryptoapi::verify(&digest, &entry.digest) => "OK",
+                Ok(_) => {
+                    mismatched += 1;
+                    "FAILED"
+                }
+                Err(error) if options.ignore_missing && error.kind() == io::ErrorKind::NotFound => {
+                    missing += 1;
+                    continue;
+                }
+                Err(error) => {
+                    if !options.status {
+                        eprintln!("cryptosum: {}: {error}", entry.file);
+                    }
+                    unreadable += 1;
+                    "FAILED open or read"
+                }
+            };
+            let print = !(options.status || options.quiet && result == "OK");
+            if print && writeln!(stdout, "{}: {result}", entry.file).is_err() {
+                return false;
+            }
+        }
+        if checked == 0 {
+            if !options.status {
+                eprintln!("cryptosum: {checksum_name}: no properly formatted checksum lines found");
+            }
+            success = false;
+            continue;
+        }
+        if checked == missing {
+            if !options.status {
+                eprintln!("cryptosum: {checksum_name}: no file was verified");
+            }
+            success = false;
+            continue;
+        }
+        if !options.status {
+            let plural = |count: usize, one: &'static str, many: &'static str| {
+                if count == 1 {
+                    one
+                } else {
+                    many
+                }
+            };
+            if improperly_formatted > 0 {
+                eprintln!(
+                    "cryptosum: WARNING: {improperly_formatted} {} improperly formatted",
+                    plural(improperly_formatted, "line is", "lines are")
+                );
+            }
+            if unreadable > 0 {
+                eprintln!(
+                    "cryptosum: WARNING: {unreadable} listed {} could not be read",
+                    plural(unreadable, "file", "files")
+                );
+            }
+            if mismatched > 0 {
+                eprintln!(
+                    "cryptosum: WARNING: {mismatched} computed {} did NOT match",
+                    plural(mismatched, "checksum", "checksums")
+                );
+            }
+        }
+        success &= unreadable == 0 && mismatched == 0;
+        success &= !options.strict || improperly_formatted == 0;
+    }
+    success && stdout.flush().is_ok()
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args(std::env::args_os().skip(1)) {
+        Ok(Command::Run(options)) => options,
+        Ok(Command::Help) => {
+            let algorithms = Algorithm::all().map(Algorithm::name).collect::<Vec<_>>();
+            print!("{}", USAGE.replace("{algorithms}", &algorithms.join(", ")));
+            return ExitCode::SUCCESS;
+        }
+        Err(message) => {
+            eprintln!("cryptosum: {message}\nTry 'cryptosum --help' for more information.");
+            return ExitCode::FAILURE;
+        }
+    };
+    let success = if options.check {
+        check(&options)
+    } else {
+        compute(&options)
+    };
+    if success {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ABC_SHA256: &str = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+
+    fn options(args: &[&str]) -> Options {
+        match parse_args(args.iter().map(OsString::from)) {
+            Ok(Command::Run(options)) => options,
+            result => panic!("unexpected {result:?}"),
+        }
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let defaults = options(&[]);
+        assert_eq!(defaults.algorithm, Algorithm::Sha256);
+        assert_eq!(defaults.files, vec![OsString::from("-")]);
+        assert!(!defaults.tag && !defaults.check);
+
+        for args in [
+            &["-a", "sha3-256"][..],
+            &["-asha3_256"],
+            &["--algorithm=SHA3-256"],
+        ] {
+            assert_eq!(options(args).algorithm, Algorithm::Sha3_256);
+        }
+        let check = options(&["-c", "--quiet", "a", "--", "-b"]);
+        assert!(check.check && check.quiet);
+        assert_eq!(check.files, vec![OsString::from("a"), OsString::from("-b")]);
+        let check = options(&["-c", "--strict", "--ignore-missing", "-z"]);
+        assert!(check.strict && check.ignore_missing && check.zero);
+        assert_eq!(options(&["-b"]).binary, Some(true));
+        assert_eq!(options(&["--binary", "--text"]).binary, Some(false));
+        assert_eq!(options(&["--tag", "-b"]).binary, Some(true));
+
+        assert_eq!(parse_args([OsString::from("--help")]), Ok(Command::Help));
+        for args in [
+            &["-a"][..],
+            &["-a", "md5"],
+            &["-x"],
+            &["--tag", "-c"],
+            &["--status"],
+            &["-c", "-b"],
+            &["--tag", "-t"],
+            &["--strict"],
+            &["--ignore-missing"],
+        ] {
+            assert!(parse_args(args.iter().map(OsString::from)).is_err());
+        }
+    }
+
+    #[test]
+    fn test_format_line() {
+        let digest = hash_reader(Algorithm::Sha256, &b"abc"[..]).unwrap();
+        assert_eq!(to_hex(&digest), ABC_SHA256);
+        assert_eq!(
+            format_line(&options(&[]), &digest, "abc.txt"),
+            format!("{ABC_SHA256}  abc.txt")
+        );
+        assert_eq!(
+            format_line(&options(&["-b"]), &digest, "abc.txt"),
+            format!("{ABC_SHA256} *abc.txt")
+        );
+        assert_eq!(
+            format_line(&options(&["--tag"]), &digest, "abc.txt"),
+            format!("SHA256 (abc.txt) = {ABC_SHA256}")
+        );
+        assert_eq!(
+            format_line(&options(&[]), &digest, "a\\b\nc"),
+            format!("\\{ABC_SHA256}  a\\\\b\\nc")
+        );
+        assert_eq!(
+            format_line(&options(&["-z"]), &digest, "a\\b\nc"),
+            format!("{ABC_SHA256}  a\\b\nc")
+        );
+        assert_eq!(tag_name(Algorithm::Sha3_256), "SHA3-256");
+    }
+
+    #[test]
+    fn test_parse_line() {
+        let digest = from_hex(ABC_SHA256).unwrap();
+        let entry = |binary: bool, file: &str| Entry {
+            algorithm: Algorithm::Sha256,
+            digest: digest.clone(),
+            binary,
+            file: file.to_owned(),
+        };
+
+        assert_eq!(
+            parse_line(&format!("{ABC_SHA256}  abc.txt"), Algorithm::Sha256),
+            Some(entry(false, "abc.txt"))
+        );
+        assert_eq!(
+            parse_line(&format!("{ABC_SHA256} *abc.txt"), Algorithm::Sha256),
+            Some(entry(true, "abc.txt"))
+        );
+        // BSD-style lines name their algorithm, whatever -a says.
+        let bsd = format!("SHA256 (a (1).txt) = {ABC_SHA256}");
+        assert_eq!(
+            parse_line(&bsd, Algorithm::Sha3_256),
+            Some(entry(true, "a (1).txt"))
+        );
+        let escaped = format!("\\{ABC_SHA256}  a\\\\b\\nc");
+        assert_eq!(
+            parse_line(&escaped, Algorithm::Sha256),
+            Some(entry(false, "a\\b\nc"))
+        );
+
+        // Lines read from checksum files, without their delimiter
+        let raw = |line: &[u8], args: &[&str]| parse_raw_line(line.to_vec(), &options(args));
+        assert_eq!(
+            raw(format!("{ABC_SHA256} *abc.txt\r").as_bytes(), &["-c"]),
+            Some(entry(true, "abc.txt"))
+        );
+        assert_eq!(
+            raw(format!("{ABC_SHA256}  a\nb").as_bytes(), &["-c", "-z"]),
+            Some(entry(false, "a\nb"))
+        );
+        let mut non_utf8 = format!("{ABC_SHA256}  ").into_bytes();
+        non_utf8.extend_from_slice(b"\xff.txt");
+        assert_eq!(raw(&non_utf8, &["-c"]), None);
+
+        for line in [
+            format!("{ABC_SHA256} abc.txt"),
+            format!("{}  abc.txt", &ABC_SHA256[1..]),
+            format!("MD5 (abc.txt) = {ABC_SHA256}"),
+            "SHA256 (abc.txt) = zz".to_owned(),
+            format!("\\{ABC_SHA256}  a\\b"),
+        ] {
+            assert_eq!(parse_line(&line, Algorithm::Sha256), None, "{line}");
+        }
+    }
+}
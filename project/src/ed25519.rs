@@ -0,0 +1,39 @@
+use crate::{
+    CryptoResult, CRYPTO_ED25519_PUBLIC_KEY_SIZE as PUBLIC_KEY_SIZE,
+    CRYPTO_ED25519_SEED_SIZE as SEED_SIZE, CRYPTO_ED25519_SIGNATURE_SIZE as SIGNATURE_SIZE,
+};
+use ed25519_dalek::{Signature, SigningKey, VerifyingKey};
+
+/// Derives the public key of an Ed25519 seed (RFC 8032, section 5.1.5).
+///
+/// The seed is the private key: 32 bytes from a cryptographically secure random source.
+pub fn public_key(seed: &[u8; SEED_SIZE]) -> [u8; PUBLIC_KEY_SIZE] {
+    SigningKey::from_bytes(seed).verifying_key().to_bytes()
+}
+
+/// Signs `message` with the key derived from `seed` (RFC 8032, section 5.1.6). Signatures are
+/// deterministic: the same seed and message always give the same signature.
+pub fn sign(seed: &[u8; SEED_SIZE], message: &[u8]) -> [u8; SIGNATURE_SIZE] {
+    use ed25519_dalek::Signer;
+
+    SigningKey::from_bytes(seed).sign(message).to_bytes()
+}
+
+/// Verifies an Ed25519 signature of `message` (RFC 8032, section 5.1.7).
+///
+/// Verification is strict: besides invalid signatures, it rejects non-canonical encodings and
+/// small-order public keys, for which several messages could share a signature. Fails with
+/// `VerificationFailed` if the signature does not hold, and with `BadParameter` if the public
+/// key is not a valid curve point.
+pub fn verify(
+    public_key: &[u8; PUBLIC_KEY_SIZE],
+    message: &[u8],
+    signature: &[u8; SIGNATURE_SIZE],
+) -> Result<(), CryptoResult> {
+    let public_key = VerifyingKey::from_bytes(public_key).map_err(|_| {
+        CryptoResult::BadParameter.with_detail("public key is not a valid Ed25519 point")
+    })?;
+    public_key
+        .verify_strict(message, &Signature::from_bytes(signature))
+        .map_err(|_| CryptoResult::VerificationFailed.with_detail("Ed25519 signature is invalid"))
+}
@@ -0,0 +1,117 @@
+use crate::internal_hash_context::cstr;
+use crate::CryptoResult;
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::fmt::{self, Display};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+thread_local! {
+    /// Detail of the error returned by the last failing call on this thread, if it has one.
+    static LAST_ERROR_DETAIL: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+#[cfg(test)]
+thread_local! {
+    /// Makes the next body run by `catch_panic` on this thread panic, to test panic handling
+    /// without corrupting a context.
+    pub(crate) static INJECT_PANIC: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+}
+
+impl CryptoResult {
+    /// Static, human-readable description of the result.
+    pub fn description(&self) -> &'static CStr {
+        match self {
+            CryptoResult::Success => cstr(b"success\0"),
+            CryptoResult::Failure => cstr(b"operation failed\0"),
+            CryptoResult::PointerCannotBeNull => cstr(b"a pointer argument is null\0"),
+            CryptoResult::BadOrUnsupportedAlgorithm => cstr(b"bad or unsupported algorithm\0"),
+            CryptoResult::UninitializedOrCorruptedContext => {
+                cstr(b"context is uninitialized or corrupted\0")
+            }
+            CryptoResult::BadBufferOutputSize => cstr(b"bad output buffer size\0"),
+            CryptoResult::BadParameter => cstr(b"bad parameter\0"),
+            CryptoResult::VerificationFailed => {
+                cstr(b"digest or MAC does not match the expected value\0")
+            }
+            CryptoResult::ZeroSharedSecret => {
+                cstr(b"key agreement gave an all-zero shared secret\0")
+            }
+        }
+    }
+
+    /// Records `detail` as the last error detail of the calling thread and returns the result.
+    pub fn with_detail(self, detail: impl Display) -> Self {
+        // Details are built from our own format strings, which never contain a nul byte.
+        let detail = CString::new(detail.to_string()).ok();
+        LAST_ERROR_DETAIL.with(|last| *last.borrow_mut() = detail);
+        self
+    }
+
+    /// `BadBufferOutputSize` with the expected and the given output lengths as detail.
+    pub fn bad_output_size(expected: impl Display, actual: usize) -> Self {
+        CryptoResult::BadBufferOutputSize.with_detail(format_args!(
+            "output buffer is {actual} bytes, expected {expected} bytes"
+        ))
+    }
+
+    /// `BadOrUnsupportedAlgorithm` with the rejected algorithm ID as detail.
+    pub fn unsupported_algorithm(algorithm_id: u32) -> Self {
+        CryptoResult::BadOrUnsupportedAlgorithm
+            .with_detail(format_args!("algorithm ID {algorithm_id} is not supported"))
+    }
+}
+
+impl Display for CryptoResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Descriptions are ASCII literals.
+        f.write_str(self.description().to_str().unwrap_or_default())
+    }
+}
+
+impl std::error::Error for CryptoResult {}
+
+/// Forgets the last error detail of the calling thread. Called when entering every fallible
+/// function of the C API, so that a detail always belongs to the last returned error.
+pub fn clear_detail() {
+    LAST_ERROR_DETAIL.with(|last| *last.borrow_mut() = None);
+}
+
+/// Runs the body of a C API function returning a `CryptoResult`.
+///
+/// The last error detail is cleared first, and a panic in the body is reported as `Failure`
+/// instead of unwinding across the FFI boundary, which is undefined behavior.
+pub fn guard(body: impl FnOnce() -> CryptoResult) -> CryptoResult {
+    clear_detail();
+    catch_panic(CryptoResult::Failure, body)
+}
+
+/// Runs the body of a C API function, returning `on_panic` if it panics. The panic message is
+/// kept as the last error detail.
+pub fn catch_panic<T>(on_panic: T, body: impl FnOnce() -> T) -> T {
+    // Contexts a panicking body was using are not touched again by this call, and callers are
+    // told with `Failure` to initialize them again.
+    panic::catch_unwind(AssertUnwindSafe(|| {
+        #[cfg(test)]
+        if INJECT_PANIC.with(|inject| inject.replace(false)) {
+            panic!("injected panic");
+        }
+        body()
+    }))
+    .unwrap_or_else(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+            .unwrap_or("unknown panic payload");
+        CryptoResult::Failure.with_detail(format_args!("internal panic: {message}"));
+        on_panic
+    })
+}
+
+/// Pointer to the last error detail of the calling thread, or null if there is none.
+///
+/// The string stays valid until the next fallible call on the same thread.
+pub fn detail() -> *const c_char {
+    LAST_ERROR_DETAIL.with(|last| last.borrow().as_ref().map_or(ptr::null(), |d| d.as_ptr()))
+}
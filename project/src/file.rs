@@ -0,0 +1,98 @@
+use crate::hasher::check_output_size;
+use crate::merkle::{leaf_hash, node_hash, split_point};
+use crate::{Algorithm, CryptoResult, Hasher};
+use memmap2::Mmap;
+use std::fs::File;
+use std::path::Path;
+
+/// Hashes the file at `path` into `output`, whose length must be the digest size of the
+/// algorithm.
+///
+/// The file is memory-mapped rather than read. BLAKE3 hashes it on the current rayon thread pool
+/// (the global one unless called from `ThreadPool::install`), other algorithms on the calling
+/// thread. The digest is the same as hashing the contents with `Hasher`.
+///
+/// # Safety
+/// The file must not be truncated or modified, by this process or another one, until the call
+/// returns: the mapping is read as a `&[u8]` that must not change, and reading past the end of
+/// a truncated file raises `SIGBUS`.
+pub unsafe fn hash_file(
+    algorithm: Algorithm,
+    path: impl AsRef<Path>,
+    output: &mut [u8],
+) -> Result<(), CryptoResult> {
+    check_output_size(algorithm.output_size(), output)?;
+    let map = map_file(path.as_ref())?;
+    let mut hasher = Hasher::new(algorithm);
+    hasher.update_parallel(&map)?;
+    hasher.finalize(output)
+}
+
+/// Hashes the file at `path` in tree mode, as `hash_tree` does, into `output`.
+///
+/// # Safety
+/// The file is memory-mapped, and must not be truncated or modified until the call returns, as
+/// for `hash_file`.
+pub unsafe fn hash_file_tree(
+    algorithm: Algorithm,
+    path: impl AsRef<Path>,
+    chunk_size: usize,
+    output: &mut [u8],
+) -> Result<(), CryptoResult> {
+    check_output_size(algorithm.output_size(), output)?;
+    check_chunk_size(chunk_size)?;
+    let map = map_file(path.as_ref())?;
+    hash_tree(algorithm, &map, chunk_size, output)
+}
+
+/// Hashes `input` in tree mode into `output`, whose length must be the digest size of the
+/// algorithm. Chunks are hashed in parallel on the current rayon thread pool, so any algorithm
+/// scales with the number of cores.
+///
+/// The input is split into chunks of `chunk_size` bytes (the last one may be shorter, and an
+/// empty input is one empty chunk), and the result is the root of the `MerkleTree` of the
+/// chunks, computed without keeping the leaf hashes.
+///
+/// The digest depends on the chunk size, and differs from the plain digest of the input.
+pub fn hash_tree(
+    algorithm: Algorithm,
+    input: &[u8],
+    chunk_size: usize,
+    output: &mut [u8],
+) -> Result<(), CryptoResult> {
+    check_output_size(algorithm.output_size(), output)?;
+    check_chunk_size(chunk_size)?;
+    let root = subtree_hash(algorithm, input, chunk_size);
+    output.copy_from_slice(&root);
+    Ok(())
+}
+
+fn subtree_hash(algorithm: Algorithm, input: &[u8], chunk_size: usize) -> Vec<u8> {
+    if input.len() <= chunk_size {
+        return leaf_hash(algorithm, input);
+    }
+    let left_chunks = split_point(input.len().div_ceil(chunk_size));
+    let (left, right) = input.split_at(left_chunks * chunk_size);
+    let (left, right) = rayon::join(
+        || subtree_hash(algorithm, left, chunk_size),
+        || subtree_hash(algorithm, right, chunk_size),
+    );
+    node_hash(algorithm, &left, &right)
+}
+
+fn check_chunk_size(chunk_size: usize) -> Result<(), CryptoResult> {
+    if chunk_size == 0 {
+        return Err(CryptoResult::BadParameter.with_detail("chunk size must not be zero"));
+    }
+    Ok(())
+}
+
+/// Maps the file at `path` read-only. Callers must uphold the safety contract of `hash_file`.
+unsafe fn map_file(path: &Path) -> Result<Mmap, CryptoResult> {
+    let io_error = |error| {
+        CryptoResult::Failure.with_detail(format_args!("cannot read {}: {error}", path.display()))
+    };
+    let file = File::open(path).map_err(io_error)?;
+    // SAFETY: the mapping is only read, and callers guarantee that the file is not modified.
+    Mmap::map(&file).map_err(io_error)
+}
@@ -0,0 +1,360 @@
+use crate::internal_hash_context::{AlgorithmInfo, InternalHashContext};
+use crate::state;
+use crate::zeroize::zeroize_temporary;
+#[cfg(feature = "blake3")]
+use crate::BLAKE3_ALG_ID;
+#[cfg(feature = "sha224")]
+use crate::SHA224_ALG_ID;
+#[cfg(feature = "sha384")]
+use crate::SHA384_ALG_ID;
+#[cfg(feature = "sha3_512")]
+use crate::SHA3_512_ALG_ID;
+#[cfg(feature = "sha512_224")]
+use crate::SHA512_224_ALG_ID;
+#[cfg(feature = "sha512_256")]
+use crate::SHA512_256_ALG_ID;
+#[cfg(feature = "sha512")]
+use crate::SHA512_ALG_ID;
+use crate::{CryptoResult, SHA256_ALG_ID, SHA3_256_ALG_ID, SHAKE128_ALG_ID, SHAKE256_ALG_ID};
+#[cfg(feature = "blake2")]
+use crate::{BLAKE2B_512_ALG_ID, BLAKE2S_256_ALG_ID};
+use std::str::FromStr;
+use std::{fmt, io};
+use subtle::ConstantTimeEq;
+
+/// Hash algorithms compiled into the library.
+///
+/// The discriminants are the algorithm IDs of the C API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+#[repr(u32)]
+pub enum Algorithm {
+    Sha256 = SHA256_ALG_ID,
+    Sha3_256 = SHA3_256_ALG_ID,
+    #[cfg(feature = "sha3_512")]
+    Sha3_512 = SHA3_512_ALG_ID,
+    Shake128 = SHAKE128_ALG_ID,
+    Shake256 = SHAKE256_ALG_ID,
+    #[cfg(feature = "blake2")]
+    Blake2b512 = BLAKE2B_512_ALG_ID,
+    #[cfg(feature = "blake2")]
+    Blake2s256 = BLAKE2S_256_ALG_ID,
+    #[cfg(feature = "blake3")]
+    Blake3 = BLAKE3_ALG_ID,
+    #[cfg(feature = "sha224")]
+    Sha224 = SHA224_ALG_ID,
+    #[cfg(feature = "sha384")]
+    Sha384 = SHA384_ALG_ID,
+    #[cfg(feature = "sha512")]
+    Sha512 = SHA512_ALG_ID,
+    #[cfg(feature = "sha512_224")]
+    Sha512_224 = SHA512_224_ALG_ID,
+    #[cfg(feature = "sha512_256")]
+    Sha512_256 = SHA512_256_ALG_ID,
+}
+
+impl Algorithm {
+    /// Every algorithm compiled in, in algorithm ID order.
+    pub fn all() -> impl Iterator<Item = Algorithm> {
+        AlgorithmInfo::all()
+            .iter()
+            .filter_map(|info| Algorithm::from_id(info.id))
+    }
+
+    /// Algorithm with the given C API algorithm ID, if it is compiled in.
+    pub fn from_id(algorithm_id: u32) -> Option<Algorithm> {
+        match algorithm_id {
+            SHA256_ALG_ID => Some(Algorithm::Sha256),
+            SHA3_256_ALG_ID => Some(Algorithm::Sha3_256),
+            #[cfg(feature = "sha3_512")]
+            SHA3_512_ALG_ID => Some(Algorithm::Sha3_512),
+            SHAKE128_ALG_ID => Some(Algorithm::Shake128),
+            SHAKE256_ALG_ID => Some(Algorithm::Shake256),
+            #[cfg(feature = "blake2")]
+            BLAKE2B_512_ALG_ID => Some(Algorithm::Blake2b512),
+            #[cfg(feature = "blake2")]
+            BLAKE2S_256_ALG_ID => Some(Algorithm::Blake2s256),
+            #[cfg(feature = "blake3")]
+            BLAKE3_ALG_ID => Some(Algorithm::Blake3),
+            #[cfg(feature = "sha224")]
+            SHA224_ALG_ID => Some(Algorithm::Sha224),
+            #[cfg(feature = "sha384")]
+            SHA384_ALG_ID => Some(Algorithm::Sha384),
+            #[cfg(feature = "sha512")]
+            SHA512_ALG_ID => Some(Algorithm::Sha512),
+            #[cfg(feature = "sha512_224")]
+            SHA512_224_ALG_ID => Some(Algorithm::Sha512_224),
+            #[cfg(feature = "sha512_256")]
+            SHA512_256_ALG_ID => Some(Algorithm::Sha512_256),
+            _ => None,
+        }
+    }
+
+    /// C API algorithm ID.
+    pub fn id(self) -> u32 {
+        self as u32
+    }
+
+    /// Name of the algorithm, e.g. "SHA-256".
+    pub fn name(self) -> &'static str {
+        // Names are ASCII literals of the algorithm table.
+        self.info().name.to_str().unwrap_or_default()
+    }
+
+    /// Digest size in bytes (the default output size for extendable-output functions).
+    pub fn output_size(self) -> usize {
+        self.info().output_size
+    }
+
+    /// Internal block size in bytes (the rate for SHA-3 and SHAKE).
+    pub fn block_size(self) -> usize {
+        self.info().block_size
+    }
+
+    /// Whether the algorithm is an extendable-output function, readable with `Hasher::squeeze`.
+    pub fn is_xof(self) -> bool {
+        match self {
+            Algorithm::Shake128 | Algorithm::Shake256 => true,
+            #[cfg(feature = "blake3")]
+            Algorithm::Blake3 => true,
+            _ => false,
+        }
+    }
+
+    pub(crate) fn info(self) -> &'static AlgorithmInfo {
+        AlgorithmInfo::find(self.id()).expect("every algorithm is in the algorithm table")
+    }
+}
+
+impl TryFrom<u32> for Algorithm {
+    type Error = CryptoResult;
+
+    /// Fails with `BadOrUnsupportedAlgorithm` for IDs of algorithms that are not compiled in.
+    fn try_from(algorithm_id: u32) -> Result<Self, Self::Error> {
+        Algorithm::from_id(algorithm_id)
+            .ok_or_else(|| CryptoResult::unsupported_algorithm(algorithm_id))
+    }
+}
+
+impl FromStr for Algorithm {
+    type Err = CryptoResult;
+
+    /// Parses an algorithm name, ignoring case and the `-`, `/` and `_` separators, so that both
+    /// "SHA3-256" and "sha3_256" are accepted. "BLAKE2b" and "BLAKE2s" stand for their only
+    /// output size.
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        let normalize = |name: &str| {
+            name.chars()
+                .filter(|c| !matches!(c, '-' | '/' | '_'))
+                .collect::<String>()
+                .to_ascii_lowercase()
+        };
+        let normalized = normalize(name);
+        Algorithm::all()
+            .find(|algorithm| {
+                let algorithm_name = normalize(algorithm.name());
+                algorithm_name == normalized
+                    || (algorithm_name.starts_with("blake2") && algorithm_name[..7] == normalized)
+            })
+            .ok_or_else(|| {
+                CryptoResult::BadOrUnsupportedAlgorithm
+                    .with_detail(format_args!("unknown algorithm name {name:?}"))
+            })
+    }
+}
+
+impl fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Incremental hash, MAC or extendable-output function computation.
+///
+/// This is the safe counterpart of `CryptoContext`: the C API functions are thin wrappers over
+/// it, and a `CryptoContext` holds a `Hasher` in its memory.
+#[derive(Clone)]
+#[repr(transparent)]
+pub struct Hasher {
+    context: InternalHashContext,
+}
+
+impl Hasher {
+    /// Creates a hasher for `algorithm`.
+    pub fn new(algorithm: Algorithm) -> Self {
+        Hasher {
+            context: InternalHashContext::new(algorithm.id())
+                .expect("every algorithm is in the algorithm table"),
+        }
+    }
+
+    /// Creates an HMAC hasher keyed with `key`, for a hash of the SHA-2 or SHA-3 families.
+    ///
+    /// Other algorithms fail with `BadOrUnsupportedAlgorithm`.
+    pub fn new_hmac(algorithm: Algorithm, key: &[u8]) -> Result<Self, CryptoResult> {
+        InternalHashContext::new_hmac(algorithm.id(), key)
+            .map(|context| Hasher { context })
+            .ok_or_else(|| {
+                CryptoResult::BadOrUnsupportedAlgorithm
+                    .with_detail(format_args!("HMAC is not supported with {algorithm}"))
+            })
+    }
+
+    /// Creates a hasher for the native keyed mode of BLAKE2b (keys up to 64 bytes), BLAKE2s (keys
+    /// up to 32 bytes) or BLAKE3 (keys of exactly 32 bytes).
+    ///
+    /// Other key lengths fail with `BadParameter`, and other algorithms with
+    /// `BadOrUnsupportedAlgorithm`.
+    #[cfg(any(feature = "blake2", feature = "blake3"))]
+    pub fn new_keyed(algorithm: Algorithm, key: &[u8]) -> Result<Self, CryptoResult> {
+        InternalHashContext::new_keyed(algorithm.id(), key).map(|context| Hasher { context })
+    }
+
+    /// Creates a BLAKE3 hasher in derive key mode for the given context string.
+    #[cfg(feature = "blake3")]
+    pub fn new_derive_key(context: &str) -> Self {
+        Hasher {
+            context: InternalHashContext::new_derive_key(context),
+        }
+    }
+
+    /// Hashes the concatenation of `inputs` into `output` in one call.
+    ///
+    /// The output length must match the digest size of the algorithm.
+    pub fn hash<'a>(
+        algorithm: Algorithm,
+        inputs: impl IntoIterator<Item = &'a [u8]>,
+        output: &mut [u8],
+    ) -> Result<(), CryptoResult> {
+        let mut hasher = Hasher::new(algorithm);
+        hasher.check_output_size(output)?;
+        for input in inputs {
+            hasher.update(input)?;
+        }
+        hasher.finalize(output)
+    }
+
+    /// Returns the digest of `input` in one call.
+    pub fn digest(algorithm: Algorithm, input: &[u8]) -> Vec<u8> {
+        Hasher::digest_concat(algorithm, [input])
+    }
+
+    /// Returns the digest of the concatenation of `inputs` in one call.
+    pub fn digest_concat<'a>(
+        algorithm: Algorithm,
+        inputs: impl IntoIterator<Item = &'a [u8]>,
+    ) -> Vec<u8> {
+        let mut output = vec![0u8; algorithm.output_size()];
+        // A new hasher always accepts input, and the output has the digest size.
+        let _ = Hasher::hash(algorithm, inputs, &mut output);
+        output
+    }
+
+    /// Size in bytes of the output of `finalize`.
+    pub fn output_size(&self) -> usize {
+        self.context.output_size()
+    }
+
+    /// Absorbs `input`. Fails with `UninitializedOrCorruptedContext` once squeezing.
+    pub fn update(&mut self, input: &[u8]) -> Result<(), CryptoResult> {
+        self.context.udpate(input)
+    }
+
+    /// Absorbs `input` as `update` does, splitting it across the current rayon thread pool for
+    /// algorithms with a tree mode (BLAKE3). Other algorithms hash it on the calling thread.
+    ///
+    /// Only worth it for inputs of at least a few hundred kilobytes.
+    #[cfg(feature = "parallel")]
+    pub fn update_parallel(&mut self, input: &[u8]) -> Result<(), CryptoResult> {
+        self.context.update_parallel(input)
+    }
+
+    /// Writes the digest (or MAC) into `output`, whose length must be `output_size`, and resets
+    /// the hasher to its initial state. Keyed hashers keep their key after the reset.
+    pub fn finalize(&mut self, output: &mut [u8]) -> Result<(), CryptoResult> {
+        self.check_output_size(output)?;
+        self.context.finalize(output)
+    }
+
+    /// Returns the digest (or MAC) and resets the hasher, as `finalize`.
+    pub fn finalize_to_vec(&mut self) -> Result<Vec<u8>, CryptoResult> {
+        let mut output = vec![0u8; self.output_size()];
+        self.finalize(&mut output)?;
+        Ok(output)
+    }
+
+    /// Finalizes as `finalize`, and compares the digest (or MAC) with `expected` in constant time.
+    ///
+    /// Fails with `VerificationFailed` if they differ, and with `BadBufferOutputSize` if the
+    /// expected value is not `output_size` bytes long.
+    pub fn finalize_verify(&mut self, expected: &[u8]) -> Result<(), CryptoResult> {
+        self.check_output_size(expected)?;
+        let mut output = vec![0u8; self.output_size()];
+        let result = self.finalize(&mut output).and_then(|()| {
+            if verify(&output, expected) {
+                Ok(())
+            } else {
+                Err(CryptoResult::VerificationFailed)
+            }
+        });
+        zeroize_temporary(&mut output);
+        result
+    }
+
+    /// Reads the next `output.len()` bytes of an extendable-output function.
+    ///
+    /// The first call ends the absorb phase, after which `update` and `finalize` fail. Other
+    /// algorithms fail with `BadOrUnsupportedAlgorithm`.
+    pub fn squeeze(&mut self, output: &mut [u8]) -> Result<(), CryptoResult> {
+        self.context.squeeze(output)
+    }
+
+    /// Serializes the state of the hasher, including any absorbed input, into a versioned blob
+    /// tagged with the algorithm, which `import_state` resumes, also in another process.
+    ///
+    /// Only hashes of the SHA-2 and SHA-3 families can be exported, SHAKE before squeezing
+    /// included. HMAC, BLAKE2 and BLAKE3 hashers fail with `BadOrUnsupportedAlgorithm`.
+    pub fn export_state(&self) -> Result<Vec<u8>, CryptoResult> {
+        state::export(&self.context)
+    }
+
+    /// Restores a hasher from a blob written by `export_state`.
+    ///
+    /// Damaged blobs fail with `UninitializedOrCorruptedContext`, and blobs of algorithms that are
+    /// not compiled in with `BadOrUnsupportedAlgorithm`.
+    pub fn import_state(blob: &[u8]) -> Result<Self, CryptoResult> {
+        state::import(blob).map(|context| Hasher { context })
+    }
+
+    fn check_output_size(&self, output: &[u8]) -> Result<(), CryptoResult> {
+        check_output_size(self.output_size(), output)
+    }
+}
+
+/// Checks that `output` is `output_size` bytes long.
+pub(crate) fn check_output_size(output_size: usize, output: &[u8]) -> Result<(), CryptoResult> {
+    if output.len() != output_size {
+        return Err(CryptoResult::bad_output_size(output_size, output.len()));
+    }
+    Ok(())
+}
+
+impl io::Write for Hasher {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.update(buf).map_err(io::Error::other)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Compares two digests or MACs in constant time.
+///
+/// The time taken only depends on the lengths, so that comparing a computed MAC with one provided
+/// by an attacker does not reveal how many leading bytes match. Values of different lengths are
+/// never equal.
+pub fn verify(a: &[u8], b: &[u8]) -> bool {
+    a.ct_eq(b).into()
+}
@@ -1,21 +1,286 @@
+#[cfg(feature = "zeroize")]
+use crate::zeroize::zeroize_raw;
+use crate::zeroize::zeroize_temporary;
+#[cfg(feature = "blake3")]
+use crate::BLAKE3_ALG_ID;
+#[cfg(feature = "sha224")]
+use crate::SHA224_ALG_ID;
+#[cfg(feature = "sha384")]
+use crate::SHA384_ALG_ID;
 #[cfg(feature = "sha3_512")]
 use crate::SHA3_512_ALG_ID;
-use crate::{SHA256_ALG_ID, SHA3_256_ALG_ID};
-use sha2::Digest;
+#[cfg(feature = "sha512_224")]
+use crate::SHA512_224_ALG_ID;
+#[cfg(feature = "sha512_256")]
+use crate::SHA512_256_ALG_ID;
+#[cfg(feature = "sha512")]
+use crate::SHA512_ALG_ID;
+use crate::{CryptoResult, SHA256_ALG_ID, SHA3_256_ALG_ID, SHAKE128_ALG_ID, SHAKE256_ALG_ID};
+#[cfg(feature = "blake2")]
+use crate::{BLAKE2B_512_ALG_ID, BLAKE2S_256_ALG_ID};
+use hmac::HmacReset as Hmac;
+use sha2::digest::array::{Array, ArraySize};
+use sha2::digest::block_api::BlockSizeUser;
+use sha2::digest::common::hazmat::SerializableState;
+use sha2::digest::typenum::Unsigned;
+use sha2::digest::{self, Digest, ExtendableOutputReset, KeyInit, Mac, Update, XofReader};
+use std::ffi::CStr;
 use InternalHashContext::*;
 
+/// Tag added to the algorithm ID of keyed (HMAC or native keyed mode) variants.
+///
+/// Keyed contexts are initialized from the same public algorithm IDs as plain hashes, so this tag
+/// is only used internally to keep the enum discriminants unique.
+const KEYED_TAG: u32 = 0x100;
+
+/// Tag added to the algorithm ID of extendable-output functions in their squeeze phase.
+const SQUEEZE_TAG: u32 = 0x200;
+
+/// Digest size of SHAKE128 when used with `finalize`, matching its 128-bit security level.
+const SHAKE128_OUTPUT_SIZE: usize = 32;
+
+/// Digest size of SHAKE256 when used with `finalize`, matching its 256-bit security level.
+const SHAKE256_OUTPUT_SIZE: usize = 64;
+
 /// This is an internal enum type that maps algorithm IDs into their correct type.
 /// The advantage of using this is that it will guarantee that algorithm IDs are unique
 ///
+/// Extendable-output functions have two variants: one absorbing input and one squeezing output.
+/// A context moves from the first to the second on its first `squeeze`, and cannot go back.
+///
+/// Every variant must also be listed in `DISCRIMINANTS`.
+///
 #[allow(clippy::large_enum_variant)]
+#[derive(Clone)]
 #[repr(u32)]
 pub enum InternalHashContext {
     Sha256(sha2::Sha256) = SHA256_ALG_ID,
     Sha3_256(sha3::Sha3_256) = SHA3_256_ALG_ID,
     #[cfg(feature = "sha3_512")]
     Sha3_512(sha3::Sha3_512) = SHA3_512_ALG_ID,
+    #[cfg(feature = "sha224")]
+    Sha224(sha2::Sha224) = SHA224_ALG_ID,
+    #[cfg(feature = "sha384")]
+    Sha384(sha2::Sha384) = SHA384_ALG_ID,
+    #[cfg(feature = "sha512")]
+    Sha512(sha2::Sha512) = SHA512_ALG_ID,
+    #[cfg(feature = "sha512_224")]
+    Sha512_224(sha2::Sha512_224) = SHA512_224_ALG_ID,
+    #[cfg(feature = "sha512_256")]
+    Sha512_256(sha2::Sha512_256) = SHA512_256_ALG_ID,
+    Shake128(sha3::Shake128) = SHAKE128_ALG_ID,
+    Shake256(sha3::Shake256) = SHAKE256_ALG_ID,
+    #[cfg(feature = "blake2")]
+    Blake2b512(blake2::Blake2b512) = BLAKE2B_512_ALG_ID,
+    #[cfg(feature = "blake2")]
+    Blake2s256(blake2::Blake2s256) = BLAKE2S_256_ALG_ID,
+    /// BLAKE3 in any of its modes (hash, keyed hash or derive key).
+    #[cfg(feature = "blake3")]
+    Blake3(blake3::Hasher) = BLAKE3_ALG_ID,
+    HmacSha256(Hmac<sha2::Sha256>) = KEYED_TAG | SHA256_ALG_ID,
+    HmacSha3_256(Hmac<sha3::Sha3_256>) = KEYED_TAG | SHA3_256_ALG_ID,
+    #[cfg(feature = "sha3_512")]
+    HmacSha3_512(Hmac<sha3::Sha3_512>) = KEYED_TAG | SHA3_512_ALG_ID,
+    #[cfg(feature = "sha224")]
+    HmacSha224(Hmac<sha2::Sha224>) = KEYED_TAG | SHA224_ALG_ID,
+    #[cfg(feature = "sha384")]
+    HmacSha384(Hmac<sha2::Sha384>) = KEYED_TAG | SHA384_ALG_ID,
+    #[cfg(feature = "sha512")]
+    HmacSha512(Hmac<sha2::Sha512>) = KEYED_TAG | SHA512_ALG_ID,
+    #[cfg(feature = "sha512_224")]
+    HmacSha512_224(Hmac<sha2::Sha512_224>) = KEYED_TAG | SHA512_224_ALG_ID,
+    #[cfg(feature = "sha512_256")]
+    HmacSha512_256(Hmac<sha2::Sha512_256>) = KEYED_TAG | SHA512_256_ALG_ID,
+    #[cfg(feature = "blake2")]
+    Blake2bMac512(blake2::Blake2bMac512) = KEYED_TAG | BLAKE2B_512_ALG_ID,
+    #[cfg(feature = "blake2")]
+    Blake2sMac256(blake2::Blake2sMac256) = KEYED_TAG | BLAKE2S_256_ALG_ID,
+    Shake128Squeeze(sha3::Shake128Reader) = SQUEEZE_TAG | SHAKE128_ALG_ID,
+    Shake256Squeeze(sha3::Shake256Reader) = SQUEEZE_TAG | SHAKE256_ALG_ID,
+    #[cfg(feature = "blake3")]
+    Blake3Squeeze(blake3::OutputReader) = SQUEEZE_TAG | BLAKE3_ALG_ID,
+}
+
+/// Discriminants of all the variants of `InternalHashContext`.
+///
+/// Used to validate the tag of a context read from caller memory before it is interpreted as an
+/// `InternalHashContext`, so it must list every variant of the enum.
+const DISCRIMINANTS: &[u32] = &[
+    SHA256_ALG_ID,
+    SHA3_256_ALG_ID,
+    #[cfg(feature = "sha3_512")]
+    SHA3_512_ALG_ID,
+    #[cfg(feature = "sha224")]
+    SHA224_ALG_ID,
+    #[cfg(feature = "sha384")]
+    SHA384_ALG_ID,
+    #[cfg(feature = "sha512")]
+    SHA512_ALG_ID,
+    #[cfg(feature = "sha512_224")]
+    SHA512_224_ALG_ID,
+    #[cfg(feature = "sha512_256")]
+    SHA512_256_ALG_ID,
+    SHAKE128_ALG_ID,
+    SHAKE256_ALG_ID,
+    #[cfg(feature = "blake2")]
+    BLAKE2B_512_ALG_ID,
+    #[cfg(feature = "blake2")]
+    BLAKE2S_256_ALG_ID,
+    #[cfg(feature = "blake3")]
+    BLAKE3_ALG_ID,
+    (KEYED_TAG | SHA256_ALG_ID),
+    (KEYED_TAG | SHA3_256_ALG_ID),
+    #[cfg(feature = "sha3_512")]
+    (KEYED_TAG | SHA3_512_ALG_ID),
+    #[cfg(feature = "sha224")]
+    (KEYED_TAG | SHA224_ALG_ID),
+    #[cfg(feature = "sha384")]
+    (KEYED_TAG | SHA384_ALG_ID),
+    #[cfg(feature = "sha512")]
+    (KEYED_TAG | SHA512_ALG_ID),
+    #[cfg(feature = "sha512_224")]
+    (KEYED_TAG | SHA512_224_ALG_ID),
+    #[cfg(feature = "sha512_256")]
+    (KEYED_TAG | SHA512_256_ALG_ID),
+    #[cfg(feature = "blake2")]
+    (KEYED_TAG | BLAKE2B_512_ALG_ID),
+    #[cfg(feature = "blake2")]
+    (KEYED_TAG | BLAKE2S_256_ALG_ID),
+    (SQUEEZE_TAG | SHAKE128_ALG_ID),
+    (SQUEEZE_TAG | SHAKE256_ALG_ID),
+    #[cfg(feature = "blake3")]
+    (SQUEEZE_TAG | BLAKE3_ALG_ID),
+];
+
+/// With the `zeroize` feature, the state of every context owned by the library (one-shot hashes,
+/// key derivation, replaced absorbing states) is wiped when it goes out of scope. Contexts in
+/// caller memory are never dropped, and are wiped with `crypto_destroy` instead.
+///
+/// The whole context is overwritten with zeros, which is a valid `Sha256` context. This relies on
+/// `Sha256` being the variant with discriminant 0, as the assertion below checks: the fields are
+/// dropped after `drop` through that variant. `sha2::Sha256` only holds integers, and its drop
+/// glue at most wipes them again.
+#[cfg(feature = "zeroize")]
+impl Drop for InternalHashContext {
+    fn drop(&mut self) {
+        // SAFETY: all-zero memory is the discriminant of `Sha256` followed by a zeroed SHA-256
+        // state, and dropping it only writes zeros over it.
+        unsafe { zeroize_raw(self) }
+    }
 }
 
+#[cfg(feature = "zeroize")]
+const _: () = assert!(SHA256_ALG_ID == 0);
+
+/// Static description of an algorithm that can be initialized with `InternalHashContext::new`.
+pub struct AlgorithmInfo {
+    pub id: u32,
+    pub name: &'static CStr,
+    pub output_size: usize,
+    pub block_size: usize,
+    pub new: fn() -> InternalHashContext,
+}
+
+impl AlgorithmInfo {
+    /// Describes an algorithm implemented by the RustCrypto type `D`.
+    const fn of<D: digest::OutputSizeUser + BlockSizeUser>(
+        id: u32,
+        name: &'static CStr,
+        new: fn() -> InternalHashContext,
+    ) -> Self {
+        AlgorithmInfo {
+            id,
+            name,
+            output_size: D::OutputSize::USIZE,
+            block_size: D::BlockSize::USIZE,
+            new,
+        }
+    }
+
+    /// All the algorithms compiled in, in algorithm ID order.
+    pub fn all() -> &'static [AlgorithmInfo] {
+        ALGORITHMS
+    }
+
+    pub fn find(algorithm_id: u32) -> Option<&'static AlgorithmInfo> {
+        ALGORITHMS.iter().find(|info| info.id == algorithm_id)
+    }
+}
+
+/// Builds a C string constant, such as an algorithm name or a result description, at compile
+/// time (cbindgen cannot parse `c"..."` literals).
+pub(crate) const fn cstr(bytes: &'static [u8]) -> &'static CStr {
+    match CStr::from_bytes_with_nul(bytes) {
+        Ok(string) => string,
+        Err(_) => panic!("C string constants must be nul-terminated"),
+    }
+}
+
+/// Table of the algorithms compiled in, which drives `InternalHashContext::new` and the
+/// capability queries of the C API.
+const ALGORITHMS: &[AlgorithmInfo] = &[
+    AlgorithmInfo::of::<sha2::Sha256>(SHA256_ALG_ID, cstr(b"SHA-256\0"), || {
+        Sha256(sha2::Sha256::new())
+    }),
+    AlgorithmInfo::of::<sha3::Sha3_256>(SHA3_256_ALG_ID, cstr(b"SHA3-256\0"), || {
+        Sha3_256(sha3::Sha3_256::new())
+    }),
+    #[cfg(feature = "sha3_512")]
+    AlgorithmInfo::of::<sha3::Sha3_512>(SHA3_512_ALG_ID, cstr(b"SHA3-512\0"), || {
+        Sha3_512(sha3::Sha3_512::new())
+    }),
+    AlgorithmInfo {
+        id: SHAKE128_ALG_ID,
+        name: cstr(b"SHAKE128\0"),
+        output_size: SHAKE128_OUTPUT_SIZE,
+        block_size: <sha3::Shake128 as BlockSizeUser>::BlockSize::USIZE,
+        new: || Shake128(sha3::Shake128::default()),
+    },
+    AlgorithmInfo {
+        id: SHAKE256_ALG_ID,
+        name: cstr(b"SHAKE256\0"),
+        output_size: SHAKE256_OUTPUT_SIZE,
+        block_size: <sha3::Shake256 as BlockSizeUser>::BlockSize::USIZE,
+        new: || Shake256(sha3::Shake256::default()),
+    },
+    #[cfg(feature = "blake2")]
+    AlgorithmInfo::of::<blake2::Blake2b512>(BLAKE2B_512_ALG_ID, cstr(b"BLAKE2b-512\0"), || {
+        Blake2b512(blake2::Blake2b512::new())
+    }),
+    #[cfg(feature = "blake2")]
+    AlgorithmInfo::of::<blake2::Blake2s256>(BLAKE2S_256_ALG_ID, cstr(b"BLAKE2s-256\0"), || {
+        Blake2s256(blake2::Blake2s256::new())
+    }),
+    #[cfg(feature = "blake3")]
+    AlgorithmInfo {
+        id: BLAKE3_ALG_ID,
+        name: cstr(b"BLAKE3\0"),
+        output_size: blake3::OUT_LEN,
+        block_size: blake3::BLOCK_LEN,
+        new: || Blake3(blake3::Hasher::new()),
+    },
+    #[cfg(feature = "sha224")]
+    AlgorithmInfo::of::<sha2::Sha224>(SHA224_ALG_ID, cstr(b"SHA-224\0"), || {
+        Sha224(sha2::Sha224::new())
+    }),
+    #[cfg(feature = "sha384")]
+    AlgorithmInfo::of::<sha2::Sha384>(SHA384_ALG_ID, cstr(b"SHA-384\0"), || {
+        Sha384(sha2::Sha384::new())
+    }),
+    #[cfg(feature = "sha512")]
+    AlgorithmInfo::of::<sha2::Sha512>(SHA512_ALG_ID, cstr(b"SHA-512\0"), || {
+        Sha512(sha2::Sha512::new())
+    }),
+    #[cfg(feature = "sha512_224")]
+    AlgorithmInfo::of::<sha2::Sha512_224>(SHA512_224_ALG_ID, cstr(b"SHA-512/224\0"), || {
+        Sha512_224(sha2::Sha512_224::new())
+    }),
+    #[cfg(feature = "sha512_256")]
+    AlgorithmInfo::of::<sha2::Sha512_256>(SHA512_256_ALG_ID, cstr(b"SHA-512/256\0"), || {
+        Sha512_256(sha2::Sha512_256::new())
+    }),
+];
+
 /// Implementation of hash API thought the enumerated types.
 ///
 /// Check that the entire implementation here is safe and the handling of unsafe code
@@ -23,37 +288,351 @@ pub enum InternalHashContext {
 ///
 impl InternalHashContext {
     pub fn new(algorithm_id: u32) -> Option<Self> {
+        AlgorithmInfo::find(algorithm_id).map(|info| (info.new)())
+    }
+
+    /// Checks that `tag` is the discriminant of one of the variants compiled in.
+    pub fn is_valid_tag(tag: u32) -> bool {
+        DISCRIMINANTS.contains(&tag)
+    }
+
+    /// Creates an HMAC context keyed with `key` for the hash given by `algorithm_id`.
+    ///
+    /// HMAC accepts keys of any length, so this only fails for unsupported algorithms.
+    pub fn new_hmac(algorithm_id: u32, key: &[u8]) -> Option<Self> {
         match algorithm_id {
-            SHA256_ALG_ID => Some(Sha256(sha2::Sha256::new())),
-            SHA3_256_ALG_ID => Some(Sha3_256(sha3::Sha3_256::new())),
+            SHA256_ALG_ID => Hmac::new_from_slice(key).ok().map(HmacSha256),
+            SHA3_256_ALG_ID => Hmac::new_from_slice(key).ok().map(HmacSha3_256),
             #[cfg(feature = "sha3_512")]
-            SHA3_512_ALG_ID => Some(Sha3_512(sha3::Sha3_512::new())),
+            SHA3_512_ALG_ID => Hmac::new_from_slice(key).ok().map(HmacSha3_512),
+            #[cfg(feature = "sha224")]
+            SHA224_ALG_ID => Hmac::new_from_slice(key).ok().map(HmacSha224),
+            #[cfg(feature = "sha384")]
+            SHA384_ALG_ID => Hmac::new_from_slice(key).ok().map(HmacSha384),
+            #[cfg(feature = "sha512")]
+            SHA512_ALG_ID => Hmac::new_from_slice(key).ok().map(HmacSha512),
+            #[cfg(feature = "sha512_224")]
+            SHA512_224_ALG_ID => Hmac::new_from_slice(key).ok().map(HmacSha512_224),
+            #[cfg(feature = "sha512_256")]
+            SHA512_256_ALG_ID => Hmac::new_from_slice(key).ok().map(HmacSha512_256),
             _ => None,
         }
     }
-    pub fn udpate(&mut self, input: &[u8]) {
+
+    /// Creates a context for the native keyed mode of `algorithm_id`.
+    ///
+    /// BLAKE2b accepts keys of up to 64 bytes, BLAKE2s of up to 32 bytes and BLAKE3 requires a
+    /// 32 byte key. Other key lengths fail with `BadParameter`.
+    #[cfg(any(feature = "blake2", feature = "blake3"))]
+    pub fn new_keyed(algorithm_id: u32, key: &[u8]) -> Result<Self, CryptoResult> {
+        match algorithm_id {
+            #[cfg(feature = "blake2")]
+            BLAKE2B_512_ALG_ID => blake2::Blake2bMac512::new_from_slice(key)
+                .map(Blake2bMac512)
+                .map_err(|_| bad_key_length(key, "at most 64")),
+            #[cfg(feature = "blake2")]
+            BLAKE2S_256_ALG_ID => blake2::Blake2sMac256::new_from_slice(key)
+                .map(Blake2sMac256)
+                .map_err(|_| bad_key_length(key, "at most 32")),
+            #[cfg(feature = "blake3")]
+            BLAKE3_ALG_ID => key
+                .try_into()
+                .map(|key| Blake3(blake3::Hasher::new_keyed(key)))
+                .map_err(|_| bad_key_length(key, "exactly 32")),
+            _ => Err(CryptoResult::unsupported_algorithm(algorithm_id)),
+        }
+    }
+
+    /// Creates a BLAKE3 context in derive key mode for the given context string.
+    #[cfg(feature = "blake3")]
+    pub fn new_derive_key(context: &str) -> Self {
+        Blake3(blake3::Hasher::new_derive_key(context))
+    }
+
+    /// Absorbs `input` into the context. Fails once an extendable-output function is squeezing.
+    pub fn udpate(&mut self, input: &[u8]) -> Result<(), CryptoResult> {
         match self {
-            Sha256(hasher) => hasher.update(input),
-            Sha3_256(hasher) => hasher.update(input),
+            Sha256(hasher) => Digest::update(hasher, input),
+            Sha3_256(hasher) => Digest::update(hasher, input),
+            #[cfg(feature = "sha3_512")]
+            Sha3_512(hasher) => Digest::update(hasher, input),
+            #[cfg(feature = "sha224")]
+            Sha224(hasher) => Digest::update(hasher, input),
+            #[cfg(feature = "sha384")]
+            Sha384(hasher) => Digest::update(hasher, input),
+            #[cfg(feature = "sha512")]
+            Sha512(hasher) => Digest::update(hasher, input),
+            #[cfg(feature = "sha512_224")]
+            Sha512_224(hasher) => Digest::update(hasher, input),
+            #[cfg(feature = "sha512_256")]
+            Sha512_256(hasher) => Digest::update(hasher, input),
+            Shake128(hasher) => hasher.update(input),
+            Shake256(hasher) => hasher.update(input),
+            #[cfg(feature = "blake2")]
+            Blake2b512(hasher) => Digest::update(hasher, input),
+            #[cfg(feature = "blake2")]
+            Blake2s256(hasher) => Digest::update(hasher, input),
+            #[cfg(feature = "blake3")]
+            Blake3(hasher) => {
+                hasher.update(input);
+            }
+            HmacSha256(mac) => Mac::update(mac, input),
+            HmacSha3_256(mac) => Mac::update(mac, input),
             #[cfg(feature = "sha3_512")]
-            Sha3_512(hasher) => hasher.update(input),
+            HmacSha3_512(mac) => Mac::update(mac, input),
+            #[cfg(feature = "sha224")]
+            HmacSha224(mac) => Mac::update(mac, input),
+            #[cfg(feature = "sha384")]
+            HmacSha384(mac) => Mac::update(mac, input),
+            #[cfg(feature = "sha512")]
+            HmacSha512(mac) => Mac::update(mac, input),
+            #[cfg(feature = "sha512_224")]
+            HmacSha512_224(mac) => Mac::update(mac, input),
+            #[cfg(feature = "sha512_256")]
+            HmacSha512_256(mac) => Mac::update(mac, input),
+            #[cfg(feature = "blake2")]
+            Blake2bMac512(mac) => Mac::update(mac, input),
+            #[cfg(feature = "blake2")]
+            Blake2sMac256(mac) => Mac::update(mac, input),
+            #[cfg(feature = "blake3")]
+            Blake3Squeeze(_) => return Err(squeezing()),
+            Shake128Squeeze(_) | Shake256Squeeze(_) => return Err(squeezing()),
         };
+        Ok(())
     }
-    pub fn finalize(&mut self, output: &mut [u8]) {
+
+    /// Absorbs `input` as `udpate` does, on the rayon thread pool for algorithms with a tree mode
+    /// (BLAKE3).
+    #[cfg(feature = "parallel")]
+    pub fn update_parallel(&mut self, input: &[u8]) -> Result<(), CryptoResult> {
+        match self {
+            #[cfg(feature = "blake3")]
+            Blake3(hasher) => {
+                hasher.update_rayon(input);
+                Ok(())
+            }
+            _ => self.udpate(input),
+        }
+    }
+
+    /// Writes the digest (or MAC) into `output` and resets the context to its initial state.
+    /// Keyed contexts keep their key after the reset.
+    ///
+    /// Extendable-output functions write `output_size` bytes, and can only be finalized before
+    /// squeezing.
+    pub fn finalize(&mut self, output: &mut [u8]) -> Result<(), CryptoResult> {
         match self {
-            Sha256(hasher) => hasher.finalize_into_reset(output.into()),
-            Sha3_256(hasher) => hasher.finalize_into_reset(output.into()),
+            Sha256(hasher) => hasher.finalize_into_reset(fixed(output)),
+            Sha3_256(hasher) => hasher.finalize_into_reset(fixed(output)),
+            #[cfg(feature = "sha3_512")]
+            Sha3_512(hasher) => hasher.finalize_into_reset(fixed(output)),
+            #[cfg(feature = "sha224")]
+            Sha224(hasher) => hasher.finalize_into_reset(fixed(output)),
+            #[cfg(feature = "sha384")]
+            Sha384(hasher) => hasher.finalize_into_reset(fixed(output)),
+            #[cfg(feature = "sha512")]
+            Sha512(hasher) => hasher.finalize_into_reset(fixed(output)),
+            #[cfg(feature = "sha512_224")]
+            Sha512_224(hasher) => hasher.finalize_into_reset(fixed(output)),
+            #[cfg(feature = "sha512_256")]
+            Sha512_256(hasher) => hasher.finalize_into_reset(fixed(output)),
+            Shake128(hasher) => hasher.finalize_xof_reset_into(output),
+            Shake256(hasher) => hasher.finalize_xof_reset_into(output),
+            #[cfg(feature = "blake2")]
+            Blake2b512(hasher) => hasher.finalize_into_reset(fixed(output)),
+            #[cfg(feature = "blake2")]
+            Blake2s256(hasher) => hasher.finalize_into_reset(fixed(output)),
+            #[cfg(feature = "blake3")]
+            Blake3(hasher) => {
+                let mut hash: [u8; blake3::OUT_LEN] = hasher.finalize().into();
+                output.copy_from_slice(&hash);
+                zeroize_temporary(&mut hash);
+                hasher.reset();
+            }
+            HmacSha256(mac) => digest::FixedOutputReset::finalize_into_reset(mac, fixed(output)),
+            HmacSha3_256(mac) => digest::FixedOutputReset::finalize_into_reset(mac, fixed(output)),
+            #[cfg(feature = "sha3_512")]
+            HmacSha3_512(mac) => digest::FixedOutputReset::finalize_into_reset(mac, fixed(output)),
+            #[cfg(feature = "sha224")]
+            HmacSha224(mac) => digest::FixedOutputReset::finalize_into_reset(mac, fixed(output)),
+            #[cfg(feature = "sha384")]
+            HmacSha384(mac) => digest::FixedOutputReset::finalize_into_reset(mac, fixed(output)),
+            #[cfg(feature = "sha512")]
+            HmacSha512(mac) => digest::FixedOutputReset::finalize_into_reset(mac, fixed(output)),
+            #[cfg(feature = "sha512_224")]
+            HmacSha512_224(mac) => {
+                digest::FixedOutputReset::finalize_into_reset(mac, fixed(output))
+            }
+            #[cfg(feature = "sha512_256")]
+            HmacSha512_256(mac) => {
+                digest::FixedOutputReset::finalize_into_reset(mac, fixed(output))
+            }
+            #[cfg(feature = "blake2")]
+            Blake2bMac512(mac) => digest::FixedOutputReset::finalize_into_reset(mac, fixed(output)),
+            #[cfg(feature = "blake2")]
+            Blake2sMac256(mac) => digest::FixedOutputReset::finalize_into_reset(mac, fixed(output)),
+            #[cfg(feature = "blake3")]
+            Blake3Squeeze(_) => return Err(squeezing()),
+            Shake128Squeeze(_) | Shake256Squeeze(_) => return Err(squeezing()),
+        };
+        Ok(())
+    }
+
+    /// Reads the next `output.len()` bytes of an extendable-output function.
+    ///
+    /// The first call finishes absorbing and moves the context into its squeeze phase. Following
+    /// calls continue the output stream where the previous one stopped.
+    pub fn squeeze(&mut self, output: &mut [u8]) -> Result<(), CryptoResult> {
+        match self {
+            Shake128(hasher) => *self = Shake128Squeeze(hasher.finalize_xof_reset()),
+            Shake256(hasher) => *self = Shake256Squeeze(hasher.finalize_xof_reset()),
+            #[cfg(feature = "blake3")]
+            Blake3(hasher) => *self = Blake3Squeeze(hasher.finalize_xof()),
+            #[cfg(feature = "blake3")]
+            Blake3Squeeze(_) => {}
+            Shake128Squeeze(_) | Shake256Squeeze(_) => {}
+            _ => {
+                return Err(CryptoResult::BadOrUnsupportedAlgorithm
+                    .with_detail("only extendable-output functions can be squeezed"))
+            }
+        };
+        match self {
+            Shake128Squeeze(reader) => reader.read(output),
+            Shake256Squeeze(reader) => reader.read(output),
+            #[cfg(feature = "blake3")]
+            Blake3Squeeze(reader) => reader.fill(output),
+            _ => unreachable!("context was moved to its squeeze phase above"),
+        };
+        Ok(())
+    }
+
+    /// Serializes the state of a hash in its absorb phase, and returns it with the algorithm ID.
+    ///
+    /// Only the SHA-2 and SHA-3 families, SHAKE included, can be serialized: the HMAC, BLAKE2 and
+    /// BLAKE3 implementations do not expose their state, and a squeezing SHAKE reader cannot be
+    /// resumed.
+    pub fn export_state(&self) -> Result<(u32, Vec<u8>), CryptoResult> {
+        let state = match self {
+            Sha256(hasher) => (SHA256_ALG_ID, serialize(hasher)),
+            Sha3_256(hasher) => (SHA3_256_ALG_ID, serialize(hasher)),
             #[cfg(feature = "sha3_512")]
-            Sha3_512(hasher) => hasher.finalize_into_reset(output.into()),
+            Sha3_512(hasher) => (SHA3_512_ALG_ID, serialize(hasher)),
+            #[cfg(feature = "sha224")]
+            Sha224(hasher) => (SHA224_ALG_ID, serialize(hasher)),
+            #[cfg(feature = "sha384")]
+            Sha384(hasher) => (SHA384_ALG_ID, serialize(hasher)),
+            #[cfg(feature = "sha512")]
+            Sha512(hasher) => (SHA512_ALG_ID, serialize(hasher)),
+            #[cfg(feature = "sha512_224")]
+            Sha512_224(hasher) => (SHA512_224_ALG_ID, serialize(hasher)),
+            #[cfg(feature = "sha512_256")]
+            Sha512_256(hasher) => (SHA512_256_ALG_ID, serialize(hasher)),
+            Shake128(hasher) => (SHAKE128_ALG_ID, serialize(hasher)),
+            Shake256(hasher) => (SHAKE256_ALG_ID, serialize(hasher)),
+            _ => return Err(not_exportable()),
         };
+        Ok(state)
+    }
+
+    /// Restores a context from the state of `algorithm_id` written by `export_state`.
+    ///
+    /// States that the hash implementation rejects fail with `UninitializedOrCorruptedContext`.
+    pub fn import_state(algorithm_id: u32, state: &[u8]) -> Result<Self, CryptoResult> {
+        match algorithm_id {
+            SHA256_ALG_ID => deserialize(state).map(Sha256),
+            SHA3_256_ALG_ID => deserialize(state).map(Sha3_256),
+            #[cfg(feature = "sha3_512")]
+            SHA3_512_ALG_ID => deserialize(state).map(Sha3_512),
+            #[cfg(feature = "sha224")]
+            SHA224_ALG_ID => deserialize(state).map(Sha224),
+            #[cfg(feature = "sha384")]
+            SHA384_ALG_ID => deserialize(state).map(Sha384),
+            #[cfg(feature = "sha512")]
+            SHA512_ALG_ID => deserialize(state).map(Sha512),
+            #[cfg(feature = "sha512_224")]
+            SHA512_224_ALG_ID => deserialize(state).map(Sha512_224),
+            #[cfg(feature = "sha512_256")]
+            SHA512_256_ALG_ID => deserialize(state).map(Sha512_256),
+            SHAKE128_ALG_ID => deserialize(state).map(Shake128),
+            SHAKE256_ALG_ID => deserialize(state).map(Shake256),
+            _ if AlgorithmInfo::find(algorithm_id).is_some() => Err(not_exportable()),
+            _ => Err(CryptoResult::unsupported_algorithm(algorithm_id)),
+        }
     }
 
     pub fn output_size(&self) -> usize {
         match self {
-            Sha256(_) => sha2::Sha256::output_size(),
-            Sha3_256(_) => sha3::Sha3_256::output_size(),
+            Sha256(_) | HmacSha256(_) => sha2::Sha256::output_size(),
+            Sha3_256(_) | HmacSha3_256(_) => sha3::Sha3_256::output_size(),
             #[cfg(feature = "sha3_512")]
-            Sha3_512(_) => sha3::Sha3_512::output_size(),
+            Sha3_512(_) | HmacSha3_512(_) => sha3::Sha3_512::output_size(),
+            #[cfg(feature = "sha224")]
+            Sha224(_) | HmacSha224(_) => sha2::Sha224::output_size(),
+            #[cfg(feature = "sha384")]
+            Sha384(_) | HmacSha384(_) => sha2::Sha384::output_size(),
+            #[cfg(feature = "sha512")]
+            Sha512(_) | HmacSha512(_) => sha2::Sha512::output_size(),
+            #[cfg(feature = "sha512_224")]
+            Sha512_224(_) | HmacSha512_224(_) => sha2::Sha512_224::output_size(),
+            #[cfg(feature = "sha512_256")]
+            Sha512_256(_) | HmacSha512_256(_) => sha2::Sha512_256::output_size(),
+            Shake128(_) | Shake128Squeeze(_) => SHAKE128_OUTPUT_SIZE,
+            Shake256(_) | Shake256Squeeze(_) => SHAKE256_OUTPUT_SIZE,
+            #[cfg(feature = "blake2")]
+            Blake2b512(_) | Blake2bMac512(_) => blake2::Blake2b512::output_size(),
+            #[cfg(feature = "blake2")]
+            Blake2s256(_) | Blake2sMac256(_) => blake2::Blake2s256::output_size(),
+            #[cfg(feature = "blake3")]
+            Blake3(_) | Blake3Squeeze(_) => blake3::OUT_LEN,
         }
     }
 }
+
+/// `BadParameter` with the rejected key length as detail.
+#[cfg(any(feature = "blake2", feature = "blake3"))]
+fn bad_key_length(key: &[u8], expected: &str) -> CryptoResult {
+    CryptoResult::BadParameter.with_detail(format_args!(
+        "key is {} bytes, expected {expected} bytes",
+        key.len()
+    ))
+}
+
+/// Returns the serialized state of `hasher`, wiping the temporary copy.
+fn serialize<T: SerializableState>(hasher: &T) -> Vec<u8> {
+    let mut state = hasher.serialize();
+    let bytes = state.to_vec();
+    zeroize_temporary(&mut state);
+    bytes
+}
+
+fn deserialize<T: SerializableState>(state: &[u8]) -> Result<T, CryptoResult> {
+    state
+        .try_into()
+        .ok()
+        .and_then(|state| T::deserialize(state).ok())
+        .ok_or_else(|| {
+            CryptoResult::UninitializedOrCorruptedContext
+                .with_detail("state blob holds an invalid hash state")
+        })
+}
+
+/// Error of a context whose state cannot be exported.
+fn not_exportable() -> CryptoResult {
+    CryptoResult::BadOrUnsupportedAlgorithm.with_detail(
+        "only the state of SHA-2 and SHA-3 hashes, SHAKE before squeezing, can be exported",
+    )
+}
+
+/// Views `output` as the fixed-size output array of a digest. Callers check the length of
+/// `output` against `output_size` first.
+fn fixed<N: ArraySize>(output: &mut [u8]) -> &mut Array<u8, N> {
+    output
+        .try_into()
+        .expect("output length is checked by the caller")
+}
+
+/// Error of a context that can no longer absorb input nor be finalized.
+fn squeezing() -> CryptoResult {
+    CryptoResult::UninitializedOrCorruptedContext
+        .with_detail("context is squeezing output, initialize it again to start over")
+}
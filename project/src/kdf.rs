@@ -0,0 +1,103 @@
+use crate::zeroize::zeroize_temporary;
+use crate::{Algorithm, CryptoResult, Hasher, CRYPTO_PBKDF2_MAX_ITERATIONS};
+
+/// Maximum number of hash-length blocks that HKDF-Expand can output (RFC 5869, section 2.3).
+const HKDF_MAX_BLOCKS: usize = 255;
+
+/// HKDF-Extract (RFC 5869, section 2.2).
+///
+/// Computes `PRK = HMAC-Hash(salt, IKM)`. An empty salt is equivalent to the default salt of
+/// `HashLen` zero bytes, since HMAC pads short keys with zeros.
+pub fn hkdf_extract(
+    algorithm: Algorithm,
+    salt: &[u8],
+    ikm: &[u8],
+    prk: &mut [u8],
+) -> Result<(), CryptoResult> {
+    let mut mac = Hasher::new_hmac(algorithm, salt)?;
+    if prk.len() != mac.output_size() {
+        return Err(CryptoResult::bad_output_size(mac.output_size(), prk.len()));
+    }
+    mac.update(ikm)?;
+    mac.finalize(prk)?;
+    Ok(())
+}
+
+/// HKDF-Expand (RFC 5869, section 2.3).
+///
+/// Fills `okm` with `T(1) | T(2) | ...` where `T(i) = HMAC-Hash(PRK, T(i-1) | info | i)`.
+/// The output can be at most `255 * HashLen` bytes long.
+pub fn hkdf_expand(
+    algorithm: Algorithm,
+    prk: &[u8],
+    info: &[u8],
+    okm: &mut [u8],
+) -> Result<(), CryptoResult> {
+    let mut mac = Hasher::new_hmac(algorithm, prk)?;
+    let hash_length = mac.output_size();
+    if okm.len() > HKDF_MAX_BLOCKS * hash_length {
+        return Err(CryptoResult::bad_output_size(
+            format_args!("at most {}", HKDF_MAX_BLOCKS * hash_length),
+            okm.len(),
+        ));
+    }
+    let mut block = vec![0u8; hash_length];
+    for (index, chunk) in okm.chunks_mut(hash_length).enumerate() {
+        if index > 0 {
+            mac.update(&block)?;
+        }
+        mac.update(info)?;
+        // Bounded by HKDF_MAX_BLOCKS, so the counter always fits in a byte.
+        mac.update(&[index as u8 + 1])?;
+        // Finalize resets the MAC to its keyed state, ready for the next block.
+        mac.finalize(&mut block)?;
+        chunk.copy_from_slice(&block[..chunk.len()]);
+    }
+    zeroize_temporary(&mut block);
+    Ok(())
+}
+
+/// PBKDF2 with HMAC as pseudorandom function (RFC 8018, section 5.2).
+///
+/// Each output block is `U_1 ^ U_2 ^ ... ^ U_c` where `U_1 = PRF(P, S | INT(i))` and
+/// `U_j = PRF(P, U_{j-1})`. The iteration count must be between 1 and
+/// `CRYPTO_PBKDF2_MAX_ITERATIONS`.
+pub fn pbkdf2(
+    algorithm: Algorithm,
+    password: &[u8],
+    salt: &[u8],
+    iterations: u32,
+    output: &mut [u8],
+) -> Result<(), CryptoResult> {
+    let mut prf = Hasher::new_hmac(algorithm, password)?;
+    if iterations == 0 || iterations > CRYPTO_PBKDF2_MAX_ITERATIONS {
+        return Err(CryptoResult::BadParameter.with_detail(format_args!(
+            "iteration count {iterations} is not between 1 and {CRYPTO_PBKDF2_MAX_ITERATIONS}"
+        )));
+    }
+    let hash_length = prf.output_size();
+    let max_length = (u32::MAX as usize).saturating_mul(hash_length);
+    if output.len() > max_length {
+        return Err(CryptoResult::bad_output_size(
+            format_args!("at most {max_length}"),
+            output.len(),
+        ));
+    }
+    let mut u = vec![0u8; hash_length];
+    let mut block = vec![0u8; hash_length];
+    for (index, chunk) in output.chunks_mut(hash_length).enumerate() {
+        prf.update(salt)?;
+        prf.update(&(index as u32 + 1).to_be_bytes())?;
+        prf.finalize(&mut u)?;
+        block.copy_from_slice(&u);
+        for _ in 1..iterations {
+            prf.update(&u)?;
+            prf.finalize(&mut u)?;
+            block.iter_mut().zip(&u).for_each(|(b, u)| *b ^= u);
+        }
+        chunk.copy_from_slice(&block[..chunk.len()]);
+    }
+    zeroize_temporary(&mut u);
+    zeroize_temporary(&mut block);
+    Ok(())
+}
@@ -1,8 +1,26 @@
+#[cfg(feature = "aead")]
+pub mod aead;
+#[cfg(feature = "ed25519")]
+pub mod ed25519;
+mod error;
+#[cfg(feature = "parallel")]
+pub mod file;
+mod hasher;
 mod internal_hash_context;
+pub mod kdf;
+pub mod merkle;
+#[cfg(feature = "argon2")]
+pub mod password;
+pub mod random;
+mod state;
+#[cfg(feature = "x25519")]
+pub mod x25519;
+mod zeroize;
 
-use internal_hash_context::InternalHashContext;
+pub use hasher::{verify, Algorithm, Hasher};
 use static_assertions as sa;
-use std::ptr;
+use std::ffi::c_char;
+use std::{mem, ptr};
 
 /// Crypto return type with Success or Failure error cases.
 ///
@@ -18,7 +36,7 @@ use std::ptr;
 /// See the case for the SHA256_ALG_ID, for additional information.
 ///
 #[repr(C)]
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum CryptoResult {
     Success,
     Failure,
@@ -26,6 +44,9 @@ pub enum CryptoResult {
     BadOrUnsupportedAlgorithm,
     UninitializedOrCorruptedContext,
     BadBufferOutputSize,
+    BadParameter,
+    VerificationFailed,
+    ZeroSharedSecret,
 }
 
 /// Crypto algorithm types
@@ -41,21 +62,527 @@ pub const SHA256_ALG_ID: u32 = 0;
 pub const SHA3_256_ALG_ID: u32 = 1;
 #[cfg(feature = "sha3_512")]
 pub const SHA3_512_ALG_ID: u32 = 2;
+pub const SHAKE128_ALG_ID: u32 = 3;
+pub const SHAKE256_ALG_ID: u32 = 4;
+#[cfg(feature = "blake2")]
+pub const BLAKE2B_512_ALG_ID: u32 = 5;
+#[cfg(feature = "blake2")]
+pub const BLAKE2S_256_ALG_ID: u32 = 6;
+#[cfg(feature = "blake3")]
+pub const BLAKE3_ALG_ID: u32 = 7;
+#[cfg(feature = "sha224")]
+pub const SHA224_ALG_ID: u32 = 8;
+#[cfg(feature = "sha384")]
+pub const SHA384_ALG_ID: u32 = 9;
+#[cfg(feature = "sha512")]
+pub const SHA512_ALG_ID: u32 = 10;
+#[cfg(feature = "sha512_224")]
+pub const SHA512_224_ALG_ID: u32 = 11;
+#[cfg(feature = "sha512_256")]
+pub const SHA512_256_ALG_ID: u32 = 12;
+
+/// Authenticated encryption algorithms
+///
+/// IDs of the AEAD algorithms of `crypto_aead_seal` and `crypto_aead_open`, which have their own
+/// ID space, separate from the hash algorithms.
+#[cfg(feature = "aead")]
+pub const CHACHA20_POLY1305_AEAD_ID: u32 = 0;
+#[cfg(feature = "aead")]
+pub const AES_256_GCM_AEAD_ID: u32 = 1;
+
+/// Byte length of the keys of every AEAD algorithm.
+#[cfg(feature = "aead")]
+pub const CRYPTO_AEAD_KEY_SIZE: usize = 32;
+
+/// Byte length of the nonces of every AEAD algorithm.
+#[cfg(feature = "aead")]
+pub const CRYPTO_AEAD_NONCE_SIZE: usize = 12;
+
+/// Byte length of the authentication tags of every AEAD algorithm.
+#[cfg(feature = "aead")]
+pub const CRYPTO_AEAD_TAG_SIZE: usize = 16;
+
+/// Byte length of Ed25519 seeds, the private keys of RFC 8032.
+#[cfg(feature = "ed25519")]
+pub const CRYPTO_ED25519_SEED_SIZE: usize = 32;
+
+/// Byte length of Ed25519 public keys.
+#[cfg(feature = "ed25519")]
+pub const CRYPTO_ED25519_PUBLIC_KEY_SIZE: usize = 32;
+
+/// Byte length of Ed25519 signatures.
+#[cfg(feature = "ed25519")]
+pub const CRYPTO_ED25519_SIGNATURE_SIZE: usize = 64;
+
+/// Byte length of X25519 secret keys, public keys and shared secrets.
+#[cfg(feature = "x25519")]
+pub const CRYPTO_X25519_KEY_SIZE: usize = 32;
+
+/// Maximum number of bytes returned by a single `crypto_drbg_generate` call (2^19 bits, SP
+/// 800-90A table 2).
+pub const CRYPTO_DRBG_MAX_REQUEST_SIZE: usize = 65536;
+
+/// Maximum Argon2id memory size accepted by `crypto_argon2id_hash` and `crypto_argon2id_verify`,
+/// in KiB.
+///
+/// The cap (2 GiB, the first configuration recommended by RFC 9106) bounds the memory a single
+/// call can allocate, including when verifying a tampered PHC string.
+#[cfg(feature = "argon2")]
+pub const CRYPTO_ARGON2_MAX_MEMORY_KIB: u32 = 2097152;
+
+/// Maximum Argon2id iteration count accepted by `crypto_argon2id_hash` and
+/// `crypto_argon2id_verify`.
+///
+/// Argon2id runs in time linear to the iteration count, so the count is capped, as the memory
+/// size is, to bound the time a tampered PHC string can make verification take. The cap (2^6)
+/// is well above the 1 to 3 iterations recommended by RFC 9106.
+#[cfg(feature = "argon2")]
+pub const CRYPTO_ARGON2_MAX_ITERATIONS: u32 = 64;
+
+/// Maximum Argon2id parallelism (number of lanes) accepted by `crypto_argon2id_hash` and
+/// `crypto_argon2id_verify`.
+///
+/// Lanes are computed one after the other, so the cap bounds the work per call together with
+/// the memory size and iteration count. RFC 9106 recommends 4 lanes.
+#[cfg(feature = "argon2")]
+pub const CRYPTO_ARGON2_MAX_PARALLELISM: u32 = 64;
+
+/// Byte length of a buffer that holds any PHC string written by `crypto_argon2id_hash`,
+/// including the terminating null character.
+#[cfg(feature = "argon2")]
+pub const CRYPTO_ARGON2_PHC_MAX_SIZE: usize = 256;
+
+/// Maximum iteration count accepted by `crypto_pbkdf2`.
+///
+/// PBKDF2 runs in time linear to the iteration count, so the count is capped to bound the time a
+/// single call can take on constrained devices. The cap (2^24) still covers every RFC 6070 test
+/// case.
+pub const CRYPTO_PBKDF2_MAX_ITERATIONS: u32 = 16777216;
 
 /// Byte length of Context internal state.
-pub const CRYPTO_CONTEXT_STATE_SIZE: usize = 45;
+///
+/// The size is large enough for every optional algorithm, so that the layout of `CryptoContext`
+/// does not depend on the enabled cargo features.
+pub const CRYPTO_CONTEXT_STATE_SIZE: usize = 244;
+
+/// Byte length of a buffer that holds any blob written by `crypto_export_state`.
+pub const CRYPTO_STATE_MAX_SIZE: usize = 512;
 
 /// Crypto Context containing memory for internal usage.
 /// Memory is declared as array of 64bit integer to preserve memory alignment
 /// and match the internal context type.
-#[repr(C)]
+///
+/// The struct is 16-byte aligned, as required by the 128-bit counters of the SHA-512 family.
+#[repr(C, align(16))]
 #[derive(Debug, Copy, Clone)]
 pub struct CryptoContext {
     pub state: [u64; CRYPTO_CONTEXT_STATE_SIZE],
 }
 
-sa::assert_eq_size!(CryptoContext, InternalHashContext);
-sa::assert_eq_align!(CryptoContext, InternalHashContext);
+/// Marker written in front of every initialized internal context.
+const CONTEXT_MAGIC: u64 = 0x6372_7970_746f_6378;
+
+/// Layout of the memory of an initialized `CryptoContext`.
+///
+/// The magic word and the enum discriminant are checked before the memory is used as an
+/// `Hasher`, so that zeroed or overwritten contexts are reported as
+/// `UninitializedOrCorruptedContext` instead of being reinterpreted as a hash state.
+#[repr(C)]
+struct CheckedContext {
+    magic: u64,
+    hasher: Hasher,
+}
+
+sa::const_assert!(mem::size_of::<CheckedContext>() <= mem::size_of::<CryptoContext>());
+sa::const_assert!(mem::align_of::<CheckedContext>() <= mem::align_of::<CryptoContext>());
+
+/// Writes a new hasher into the caller memory, together with its magic word, or returns the error
+/// that prevented creating it.
+///
+/// # Safety
+/// Pointer must not be null.
+unsafe fn write_context(
+    ctx: *mut CryptoContext,
+    hasher: Result<Hasher, CryptoResult>,
+) -> CryptoResult {
+    match hasher {
+        Ok(hasher) => {
+            ptr::write(
+                ctx.cast(),
+                CheckedContext {
+                    magic: CONTEXT_MAGIC,
+                    hasher,
+                },
+            );
+            CryptoResult::Success
+        }
+        Err(error) => error,
+    }
+}
+
+/// Validates the caller memory and returns the discriminant of the internal context in it.
+///
+/// Only the magic word and the `repr(u32)` discriminant are read before the memory is checked,
+/// so no invalid `Hasher` is ever created.
+///
+/// # Safety
+/// Pointer must not be null.
+unsafe fn read_tag(checked: *const CheckedContext) -> Result<u32, CryptoResult> {
+    let magic = ptr::addr_of!((*checked).magic).read();
+    let tag = ptr::addr_of!((*checked).hasher).cast::<u32>().read();
+    if magic != CONTEXT_MAGIC {
+        return Err(CryptoResult::UninitializedOrCorruptedContext
+            .with_detail("context was not initialized by an init function"));
+    }
+    if !internal_hash_context::InternalHashContext::is_valid_tag(tag) {
+        return Err(CryptoResult::UninitializedOrCorruptedContext
+            .with_detail(format_args!("context holds unknown tag {tag:#x}")));
+    }
+    Ok(tag)
+}
+
+/// Validates the caller memory and returns the hasher stored in it.
+///
+/// # Safety
+/// Pointer must not be null.
+unsafe fn read_context<'a>(checked: *mut CheckedContext) -> Result<&'a mut Hasher, CryptoResult> {
+    read_tag(checked)?;
+    Ok(&mut (*checked).hasher)
+}
+
+/// Shared implementation of `crypto_update` and `crypto_handle_update`.
+///
+/// # Safety
+/// Pointers must not be null and input length must be correct.
+unsafe fn update(
+    checked: *mut CheckedContext,
+    input: *const u8,
+    input_length: usize,
+) -> CryptoResult {
+    if input.is_null() {
+        return CryptoResult::PointerCannotBeNull;
+    }
+    let hasher = match read_context(checked) {
+        Ok(hasher) => hasher,
+        Err(error) => return error,
+    };
+    // This must be checked as `from_raw_parts_mut` has many undefined behavior conditions that
+    // must be guaranteed by the caller. In this case, some of them must be guaranteed by the user.
+    let input_slice = std::slice::from_raw_parts(input, input_length);
+    hasher
+        .update(input_slice)
+        .err()
+        .unwrap_or(CryptoResult::Success)
+}
+
+/// Shared implementation of `crypto_finalize` and `crypto_handle_finalize`.
+///
+/// # Safety
+/// Pointers must not be null and output length must be correct.
+unsafe fn finalize(
+    checked: *mut CheckedContext,
+    output: *mut u8,
+    output_length: usize,
+) -> CryptoResult {
+    if output.is_null() {
+        return CryptoResult::PointerCannotBeNull;
+    }
+    let hasher = match read_context(checked) {
+        Ok(hasher) => hasher,
+        Err(error) => return error,
+    };
+    // This must be checked as `from_raw_parts_mut` has many undefined behavior conditions that
+    // must be guaranteed by the caller. In this case, some of them must be guaranteed by the user.
+    let output_slice = std::slice::from_raw_parts_mut(output, output_length);
+    hasher
+        .finalize(output_slice)
+        .err()
+        .unwrap_or(CryptoResult::Success)
+}
+
+/// Shared implementation of `crypto_squeeze` and `crypto_handle_squeeze`.
+///
+/// # Safety
+/// Pointers must not be null and output length must be correct.
+unsafe fn squeeze(
+    checked: *mut CheckedContext,
+    output: *mut u8,
+    output_length: usize,
+) -> CryptoResult {
+    if output.is_null() {
+        return CryptoResult::PointerCannotBeNull;
+    }
+    let hasher = match read_context(checked) {
+        Ok(hasher) => hasher,
+        Err(error) => return error,
+    };
+    let output_slice = std::slice::from_raw_parts_mut(output, output_length);
+    hasher
+        .squeeze(output_slice)
+        .err()
+        .unwrap_or(CryptoResult::Success)
+}
+
+/// Shared implementation of `crypto_finalize_verify` and `crypto_handle_finalize_verify`.
+///
+/// # Safety
+/// Pointers must not be null and expected length must be correct.
+unsafe fn finalize_verify(
+    checked: *mut CheckedContext,
+    expected: *const u8,
+    expected_length: usize,
+) -> CryptoResult {
+    if expected.is_null() {
+        return CryptoResult::PointerCannotBeNull;
+    }
+    let hasher = match read_context(checked) {
+        Ok(hasher) => hasher,
+        Err(error) => return error,
+    };
+    let expected_slice = std::slice::from_raw_parts(expected, expected_length);
+    hasher
+        .finalize_verify(expected_slice)
+        .err()
+        .unwrap_or(CryptoResult::Success)
+}
+
+/// Argon2id cost parameters.
+///
+/// RFC 9106 recommends 2 GiB of memory with 1 iteration and 4 lanes, or 64 MiB with 3 iterations
+/// and 4 lanes when memory is constrained. OWASP asks for at least 19 MiB with 2 iterations.
+#[cfg(feature = "argon2")]
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CryptoArgon2Params {
+    /// Memory size in KiB, at least 8 times the parallelism and at most
+    /// `CRYPTO_ARGON2_MAX_MEMORY_KIB`.
+    pub memory_kib: u32,
+    /// Number of passes over the memory, between 1 and `CRYPTO_ARGON2_MAX_ITERATIONS`.
+    pub iterations: u32,
+    /// Number of lanes, between 1 and `CRYPTO_ARGON2_MAX_PARALLELISM`.
+    pub parallelism: u32,
+    /// Byte length of the hash, between 4 and 64.
+    pub output_length: usize,
+}
+
+/// Opaque heap-allocated context, created with `crypto_new` or `crypto_hmac_new` and released
+/// with `crypto_free`.
+///
+/// Unlike `CryptoContext`, its size is not part of the ABI, so it keeps working unchanged when
+/// algorithms with larger states are added.
+pub struct CryptoHandle {
+    checked: CheckedContext,
+}
+
+/// Moves a new hasher to the heap and returns it as a handle, or null if it could not be created.
+fn new_handle(hasher: Result<Hasher, CryptoResult>) -> *mut CryptoHandle {
+    match hasher {
+        Ok(hasher) => Box::into_raw(Box::new(CryptoHandle {
+            checked: CheckedContext {
+                magic: CONTEXT_MAGIC,
+                hasher,
+            },
+        })),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Opaque heap-allocated Merkle tree, created with `crypto_merkle_new` and released with
+/// `crypto_merkle_free`.
+pub struct CryptoMerkleTree {
+    tree: merkle::MerkleTree,
+}
+
+/// Opaque heap-allocated Hash_DRBG generator, created with `crypto_drbg_instantiate` and released
+/// with `crypto_drbg_free`.
+pub struct CryptoDrbg {
+    drbg: random::HashDrbg,
+}
+
+/// Copies a proof to a caller buffer of `capacity` bytes and writes its length to `length`.
+///
+/// The length is written even when the buffer is too small, so callers can retry with a larger
+/// buffer.
+///
+/// # Safety
+/// Pointers must not be null and capacity must be correct.
+unsafe fn write_proof(
+    proof: Result<Vec<u8>, CryptoResult>,
+    output: *mut u8,
+    capacity: usize,
+    length: *mut usize,
+) -> CryptoResult {
+    let proof = match proof {
+        Ok(proof) => proof,
+        Err(error) => return error,
+    };
+    *length = proof.len();
+    if proof.len() > capacity {
+        return CryptoResult::bad_output_size(format_args!("at least {}", proof.len()), capacity);
+    }
+    ptr::copy_nonoverlapping(proof.as_ptr(), output, proof.len());
+    CryptoResult::Success
+}
+
+/// Ed25519 seed: the 32 byte private key of RFC 8032, which must come from a cryptographically
+/// secure random source and be kept secret.
+#[cfg(feature = "ed25519")]
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct CryptoEd25519Seed {
+    pub bytes: [u8; CRYPTO_ED25519_SEED_SIZE],
+}
+
+/// Ed25519 public key, derived from a seed with `crypto_ed25519_public_key`.
+#[cfg(feature = "ed25519")]
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CryptoEd25519PublicKey {
+    pub bytes: [u8; CRYPTO_ED25519_PUBLIC_KEY_SIZE],
+}
+
+/// Ed25519 signature, made with `crypto_ed25519_sign`.
+#[cfg(feature = "ed25519")]
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CryptoEd25519Signature {
+    pub bytes: [u8; CRYPTO_ED25519_SIGNATURE_SIZE],
+}
+
+/// X25519 secret key: 32 bytes from a cryptographically secure random source, to be kept
+/// secret.
+#[cfg(feature = "x25519")]
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct CryptoX25519SecretKey {
+    pub bytes: [u8; CRYPTO_X25519_KEY_SIZE],
+}
+
+/// X25519 public key, derived from a secret key with `crypto_x25519_public_key`.
+#[cfg(feature = "x25519")]
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CryptoX25519PublicKey {
+    pub bytes: [u8; CRYPTO_X25519_KEY_SIZE],
+}
+
+/// X25519 shared secret, computed with `crypto_x25519_shared_secret`.
+#[cfg(feature = "x25519")]
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct CryptoX25519SharedSecret {
+    pub bytes: [u8; CRYPTO_X25519_KEY_SIZE],
+}
+
+/// Input buffer for scatter/gather hashing with `crypto_hash_multi`.
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct CryptoBuffer {
+    pub data: *const u8,
+    pub length: usize,
+}
+
+/// Crypto API version
+/// Returns the nul-terminated version of the library, e.g. "0.1.0". The string is static and
+/// must not be freed.
+#[no_mangle]
+pub extern "C" fn crypto_api_version() -> *const c_char {
+    error::catch_panic(ptr::null(), || {
+        concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr().cast()
+    })
+}
+
+/// Crypto result str
+/// Returns a static, nul-terminated description of a result, e.g. "bad output buffer size".
+/// The string must not be freed.
+#[no_mangle]
+pub extern "C" fn crypto_result_str(result: CryptoResult) -> *const c_char {
+    error::catch_panic(ptr::null(), || result.description().as_ptr())
+}
+
+/// Crypto last error detail
+/// Returns a nul-terminated description of why the last failing call on the calling thread
+/// failed, e.g. the expected and the given lengths for `BadBufferOutputSize`, or null if that
+/// error has no detail.
+///
+/// Every function returning a `CryptoResult` clears the detail when called, so it must be read
+/// right after the error is returned. The string is owned by the library and stays valid until
+/// the next such call on the same thread.
+#[no_mangle]
+pub extern "C" fn crypto_last_error_detail() -> *const c_char {
+    error::catch_panic(ptr::null(), error::detail)
+}
+
+/// Crypto algorithm supported
+/// Returns whether the given algorithm ID is compiled into this library.
+#[no_mangle]
+pub extern "C" fn crypto_algorithm_supported(algorithm_id: u32) -> bool {
+    error::catch_panic(false, || Algorithm::from_id(algorithm_id).is_some())
+}
+
+/// Crypto output size
+/// Returns the digest size in bytes of the given algorithm (the default output size for XOFs),
+/// or 0 if the algorithm is not supported.
+#[no_mangle]
+pub extern "C" fn crypto_output_size(algorithm_id: u32) -> usize {
+    error::catch_panic(0, || {
+        Algorithm::from_id(algorithm_id).map_or(0, Algorithm::output_size)
+    })
+}
+
+/// Crypto block size
+/// Returns the internal block size in bytes of the given algorithm (the rate for SHA-3 and
+/// SHAKE), or 0 if the algorithm is not supported.
+#[no_mangle]
+pub extern "C" fn crypto_block_size(algorithm_id: u32) -> usize {
+    error::catch_panic(0, || {
+        Algorithm::from_id(algorithm_id).map_or(0, Algorithm::block_size)
+    })
+}
+
+/// Crypto algorithm name
+/// Returns the nul-terminated name of the given algorithm, e.g. "SHA-256", or null if the
+/// algorithm is not supported. The string is static and must not be freed.
+#[no_mangle]
+pub extern "C" fn crypto_algorithm_name(algorithm_id: u32) -> *const c_char {
+    error::catch_panic(ptr::null(), || {
+        Algorithm::from_id(algorithm_id)
+            .map_or(ptr::null(), |algorithm| algorithm.info().name.as_ptr())
+    })
+}
+
+/// Crypto algorithm count
+/// Returns the number of algorithms compiled into this library.
+#[no_mangle]
+pub extern "C" fn crypto_algorithm_count() -> usize {
+    error::catch_panic(0, || Algorithm::all().count())
+}
+
+/// Crypto algorithm ID
+/// Writes the ID of the algorithm at `index`, in increasing ID order, to `algorithm_id`.
+/// Together with `crypto_algorithm_count` this enumerates every supported algorithm.
+///
+/// # Safety
+/// Pointer must not be null.
+#[must_use]
+#[no_mangle]
+pub unsafe extern "C" fn crypto_algorithm_id(index: usize, algorithm_id: *mut u32) -> CryptoResult {
+    error::guard(|| {
+        if algorithm_id.is_null() {
+            return CryptoResult::PointerCannotBeNull;
+        }
+        match Algorithm::all().nth(index) {
+            Some(algorithm) => {
+                *algorithm_id = algorithm.id();
+                CryptoResult::Success
+            }
+            None => CryptoResult::BadParameter.with_detail(format_args!(
+                "index {index} is out of range, there are {} algorithms",
+                Algorithm::all().count()
+            )),
+        }
+    })
+}
 
 /// Crypto init
 /// Initialize a context with a given algorithm.
@@ -65,20 +592,113 @@ sa::assert_eq_align!(CryptoContext, InternalHashContext);
 #[must_use]
 #[no_mangle]
 pub unsafe extern "C" fn crypto_init(ctx: *mut CryptoContext, algorithm_id: u32) -> CryptoResult {
-    if ctx.is_null() {
-        return CryptoResult::PointerCannotBeNull;
-    }
-    InternalHashContext::new(algorithm_id)
-        .map(|new_ctx| {
-            ptr::write(ctx.cast(), new_ctx);
-            CryptoResult::Success
-        })
-        .unwrap_or(CryptoResult::BadOrUnsupportedAlgorithm)
+    error::guard(|| {
+        if ctx.is_null() {
+            return CryptoResult::PointerCannotBeNull;
+        }
+        write_context(ctx, Algorithm::try_from(algorithm_id).map(Hasher::new))
+    })
+}
+
+/// Crypto HMAC init
+/// Initialize a context for HMAC with a given hash algorithm and key.
+///
+/// The context is then used with `crypto_update` and `crypto_finalize` exactly as a plain hash
+/// context, and `crypto_finalize` outputs the MAC. After finalizing, the context is reset to its
+/// keyed initial state and can be used to authenticate another message with the same key.
+///
+/// # Safety
+/// Pointers must not be null and key length must be correct.
+#[must_use]
+#[no_mangle]
+pub unsafe extern "C" fn crypto_hmac_init(
+    ctx: *mut CryptoContext,
+    algorithm_id: u32,
+    key: *const u8,
+    key_length: usize,
+) -> CryptoResult {
+    error::guard(|| {
+        if ctx.is_null() || key.is_null() {
+            return CryptoResult::PointerCannotBeNull;
+        }
+        let key_slice = std::slice::from_raw_parts(key, key_length);
+        write_context(
+            ctx,
+            Algorithm::try_from(algorithm_id)
+                .and_then(|algorithm| Hasher::new_hmac(algorithm, key_slice)),
+        )
+    })
+}
+
+/// Crypto keyed init
+/// Initialize a context for the native keyed mode of a given algorithm.
+///
+/// Supported for BLAKE2b (keys up to 64 bytes), BLAKE2s (keys up to 32 bytes) and BLAKE3 (keys
+/// of exactly 32 bytes). Other key lengths return `BadParameter`, and algorithms without a
+/// keyed mode return `BadOrUnsupportedAlgorithm`. The context is then used with `crypto_update`
+/// and `crypto_finalize` as a plain hash context.
+///
+/// # Safety
+/// Pointers must not be null and key length must be correct.
+#[cfg(any(feature = "blake2", feature = "blake3"))]
+#[must_use]
+#[no_mangle]
+pub unsafe extern "C" fn crypto_keyed_init(
+    ctx: *mut CryptoContext,
+    algorithm_id: u32,
+    key: *const u8,
+    key_length: usize,
+) -> CryptoResult {
+    error::guard(|| {
+        if ctx.is_null() || key.is_null() {
+            return CryptoResult::PointerCannotBeNull;
+        }
+        let key_slice = std::slice::from_raw_parts(key, key_length);
+        write_context(
+            ctx,
+            Algorithm::try_from(algorithm_id)
+                .and_then(|algorithm| Hasher::new_keyed(algorithm, key_slice)),
+        )
+    })
+}
+
+/// Crypto derive key init
+/// Initialize a BLAKE3 context in derive key mode with the given context string.
+///
+/// The context string must be UTF-8 encoded, otherwise `BadParameter` is returned. The key
+/// material is then input with `crypto_update`, and the derived key is read with
+/// `crypto_finalize` or `crypto_squeeze`.
+///
+/// # Safety
+/// Pointers must not be null and context length must be correct.
+#[cfg(feature = "blake3")]
+#[must_use]
+#[no_mangle]
+pub unsafe extern "C" fn crypto_derive_key_init(
+    ctx: *mut CryptoContext,
+    context: *const u8,
+    context_length: usize,
+) -> CryptoResult {
+    error::guard(|| {
+        if ctx.is_null() || context.is_null() {
+            return CryptoResult::PointerCannotBeNull;
+        }
+        let context_slice = std::slice::from_raw_parts(context, context_length);
+        let hasher = std::str::from_utf8(context_slice)
+            .map(Hasher::new_derive_key)
+            .map_err(|_| {
+                CryptoResult::BadParameter.with_detail("context string is not valid UTF-8")
+            });
+        write_context(ctx, hasher)
+    })
 }
 
 /// Crypto update
 /// Updates internal hash context with input buffer
 ///
+/// Contexts that were not set up by one of the init functions, or were overwritten since, return
+/// `UninitializedOrCorruptedContext`.
+///
 /// # Safety
 /// Pointers must not be null and input length must be correct.
 #[must_use]
@@ -88,15 +708,12 @@ pub unsafe extern "C" fn crypto_update(
     input: *const u8,
     input_length: usize,
 ) -> CryptoResult {
-    if ctx.is_null() || input.is_null() {
-        return CryptoResult::PointerCannotBeNull;
-    }
-    let internal_context: &mut InternalHashContext = &mut *(ctx.cast());
-    // This must be checked as `from_raw_parts_mut` has many undefined behavior conditions that
-    // must be guaranteed by the caller. In this case, some of them must be guaranteed by the user.
-    let input_slice = std::slice::from_raw_parts(input, input_length);
-    internal_context.udpate(input_slice);
-    CryptoResult::Success
+    error::guard(|| {
+        if ctx.is_null() {
+            return CryptoResult::PointerCannotBeNull;
+        }
+        update(ctx.cast(), input, input_length)
+    })
 }
 
 /// Crypto Finalize
@@ -108,6 +725,9 @@ pub unsafe extern "C" fn crypto_update(
 /// the user reads the doc and uses well-formed arrays of proper size, then the API could support
 /// additional algorithms (smaller and larger outputs).
 ///
+/// Contexts that were not set up by one of the init functions, or were overwritten since, return
+/// `UninitializedOrCorruptedContext`.
+///
 /// # Safety
 /// Pointers must not be null.
 #[must_use]
@@ -117,119 +737,4688 @@ pub unsafe extern "C" fn crypto_finalize(
     output: *mut u8,
     output_length: usize,
 ) -> CryptoResult {
-    if ctx.is_null() || output.is_null() {
-        return CryptoResult::PointerCannotBeNull;
-    }
-    let internal_context: &mut InternalHashContext = &mut *(ctx.cast());
-    if output_length != internal_context.output_size() {
-        return CryptoResult::BadBufferOutputSize;
-    }
-    // This must be checked as `from_raw_parts_mut` has many undefined behavior conditions that
-    // must be guaranteed by the caller. In this case, some of them must be guaranteed by the user.
-    let output_slice = std::slice::from_raw_parts_mut(output, output_length);
-    internal_context.finalize(output_slice);
-
-    CryptoResult::Success
+    error::guard(|| {
+        if ctx.is_null() {
+            return CryptoResult::PointerCannotBeNull;
+        }
+        finalize(ctx.cast(), output, output_length)
+    })
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Crypto finalize verify
+/// Finalizes the digest (or MAC) computation and compares it with `expected` in constant time,
+/// without exposing the computed value.
+///
+/// Returns `VerificationFailed` if they differ. The expected length must match the digest size
+/// of the algorithm, otherwise `BadBufferOutputSize` is returned and the context is untouched.
+/// As with `crypto_finalize`, the context is reset afterwards.
+///
+/// # Safety
+/// Pointers must not be null and expected length must be correct.
+#[must_use]
+#[no_mangle]
+pub unsafe extern "C" fn crypto_finalize_verify(
+    ctx: *mut CryptoContext,
+    expected: *const u8,
+    expected_length: usize,
+) -> CryptoResult {
+    error::guard(|| {
+        if ctx.is_null() {
+            return CryptoResult::PointerCannotBeNull;
+        }
+        finalize_verify(ctx.cast(), expected, expected_length)
+    })
+}
 
-    #[test]
-    fn test_sha3_256() {
-        let mut state = CryptoContext {
-            state: [0u64; CRYPTO_CONTEXT_STATE_SIZE],
-        };
-        unsafe {
-            assert_eq!(
-                crypto_init(&mut state as *mut CryptoContext, SHA3_256_ALG_ID),
-                CryptoResult::Success
-            );
-        };
+/// Crypto verify
+/// Compares two buffers of `length` bytes, e.g. a computed and a received MAC, in constant time.
+///
+/// Returns `Success` if they are equal and `VerificationFailed` otherwise. Unlike `memcmp`, the
+/// time taken does not depend on the position of the first difference.
+///
+/// # Safety
+/// Pointers must not be null and length must be correct.
+#[must_use]
+#[no_mangle]
+pub unsafe extern "C" fn crypto_verify(a: *const u8, b: *const u8, length: usize) -> CryptoResult {
+    error::guard(|| {
+        if a.is_null() || b.is_null() {
+            return CryptoResult::PointerCannotBeNull;
+        }
+        let a_slice = std::slice::from_raw_parts(a, length);
+        let b_slice = std::slice::from_raw_parts(b, length);
+        if verify(a_slice, b_slice) {
+            CryptoResult::Success
+        } else {
+            CryptoResult::VerificationFailed
+        }
+    })
+}
 
-        // Update with the string "hello"
-        let input = b"hello";
+/// Crypto squeeze
+/// Reads the next `output_length` bytes of output from an extendable-output function context
+/// (SHAKE128, SHAKE256 or BLAKE3).
+///
+/// The first call ends the absorb phase, and following calls continue the output stream where the
+/// previous one stopped, so the output can be read in pieces of any length. Once squeezing, the
+/// context no longer accepts `crypto_update` nor `crypto_finalize`, which return
+/// `UninitializedOrCorruptedContext`; call `crypto_init` to start over. Contexts of fixed-output
+/// algorithms return `BadOrUnsupportedAlgorithm`.
+///
+/// # Safety
+/// Pointers must not be null and output length must be correct.
+#[must_use]
+#[no_mangle]
+pub unsafe extern "C" fn crypto_squeeze(
+    ctx: *mut CryptoContext,
+    output: *mut u8,
+    output_length: usize,
+) -> CryptoResult {
+    error::guard(|| {
+        if ctx.is_null() {
+            return CryptoResult::PointerCannotBeNull;
+        }
+        squeeze(ctx.cast(), output, output_length)
+    })
+}
+
+/// Crypto destroy
+/// Wipes a context, including any key material and buffered input, with writes that the
+/// optimizer cannot elide.
+///
+/// The context is left uninitialized, so using it returns `UninitializedOrCorruptedContext`
+/// until it is initialized again. Call this once a context is no longer needed, as finalizing
+/// only resets a context for reuse and keyed contexts keep their key.
+///
+/// # Safety
+/// Pointer must not be null.
+#[must_use]
+#[no_mangle]
+pub unsafe extern "C" fn crypto_destroy(ctx: *mut CryptoContext) -> CryptoResult {
+    error::guard(|| {
+        if ctx.is_null() {
+            return CryptoResult::PointerCannotBeNull;
+        }
+        zeroize::zeroize_raw(ctx);
+        CryptoResult::Success
+    })
+}
+
+/// Crypto clone
+/// Copies an initialized context, including any absorbed input, into another context.
+///
+/// This allows hashing a common prefix once and then finishing several messages from it. Use
+/// `crypto_export_state` to resume the context in another process.
+///
+/// # Safety
+/// Pointers must not be null.
+#[must_use]
+#[no_mangle]
+pub unsafe extern "C" fn crypto_clone(
+    dst: *mut CryptoContext,
+    src: *const CryptoContext,
+) -> CryptoResult {
+    error::guard(|| {
+        if dst.is_null() || src.is_null() {
+            return CryptoResult::PointerCannotBeNull;
+        }
+        if let Err(error) = read_tag(src.cast()) {
+            return error;
+        }
+        write_context(dst, Ok((*src.cast::<CheckedContext>()).hasher.clone()))
+    })
+}
+
+/// Crypto export state
+/// Serializes an initialized context, including any absorbed input, into a versioned blob tagged
+/// with the algorithm, and writes its length to `output_length`.
+///
+/// The blob can be resumed with `crypto_import_state`, also in another process or by another build
+/// of the library that has the algorithm compiled in. Only contexts of the SHA-2 and SHA-3
+/// families can be exported, SHAKE before squeezing included: HMAC, BLAKE2 and BLAKE3 contexts
+/// return `BadOrUnsupportedAlgorithm`. If the blob is longer than `output_capacity`,
+/// `BadBufferOutputSize` is returned and `output_length` still receives the required length,
+/// which is never more than `CRYPTO_STATE_MAX_SIZE`.
+///
+/// # Safety
+/// Pointers must not be null and output capacity must be correct.
+#[must_use]
+#[no_mangle]
+pub unsafe extern "C" fn crypto_export_state(
+    ctx: *const CryptoContext,
+    output: *mut u8,
+    output_capacity: usize,
+    output_length: *mut usize,
+) -> CryptoResult {
+    error::guard(|| {
+        if ctx.is_null() || output.is_null() || output_length.is_null() {
+            return CryptoResult::PointerCannotBeNull;
+        }
+        if let Err(error) = read_tag(ctx.cast()) {
+            return error;
+        }
+        let mut blob = match (*ctx.cast::<CheckedContext>()).hasher.export_state() {
+            Ok(blob) => blob,
+            Err(error) => return error,
+        };
+        *output_length = blob.len();
+        let result = if blob.len() > output_capacity {
+            CryptoResult::bad_output_size(format_args!("at least {}", blob.len()), output_capacity)
+        } else {
+            ptr::copy_nonoverlapping(blob.as_ptr(), output, blob.len());
+            CryptoResult::Success
+        };
+        zeroize::zeroize_temporary(&mut blob);
+        result
+    })
+}
+
+/// Crypto import state
+/// Initializes a context from a blob written by `crypto_export_state`.
+///
+/// Damaged blobs, or blobs of another format version, return `UninitializedOrCorruptedContext`
+/// and blobs of algorithms that are not compiled in return `BadOrUnsupportedAlgorithm`. The
+/// context is left untouched on failure.
+///
+/// # Safety
+/// Pointers must not be null and input length must be correct.
+#[must_use]
+#[no_mangle]
+pub unsafe extern "C" fn crypto_import_state(
+    ctx: *mut CryptoContext,
+    input: *const u8,
+    input_length: usize,
+) -> CryptoResult {
+    error::guard(|| {
+        if ctx.is_null() || input.is_null() {
+            return CryptoResult::PointerCannotBeNull;
+        }
+        let input_slice = std::slice::from_raw_parts(input, input_length);
+        write_context(ctx, Hasher::import_state(input_slice))
+    })
+}
+
+/// Crypto new
+/// Allocates a handle initialized with a given algorithm.
+///
+/// Returns null if the algorithm is not supported, with the reason in `crypto_last_error_detail`.
+/// The handle must be released with `crypto_free`.
+#[no_mangle]
+pub extern "C" fn crypto_new(algorithm_id: u32) -> *mut CryptoHandle {
+    error::clear_detail();
+    error::catch_panic(ptr::null_mut(), || {
+        new_handle(Algorithm::try_from(algorithm_id).map(Hasher::new))
+    })
+}
+
+/// Crypto HMAC new
+/// Allocates a handle initialized for HMAC with a given hash algorithm and key.
+///
+/// Returns null if the algorithm is not supported or the key is null, with the reason in
+/// `crypto_last_error_detail`. The handle must be released with `crypto_free`.
+///
+/// # Safety
+/// Key length must be correct.
+#[no_mangle]
+pub unsafe extern "C" fn crypto_hmac_new(
+    algorithm_id: u32,
+    key: *const u8,
+    key_length: usize,
+) -> *mut CryptoHandle {
+    error::clear_detail();
+    error::catch_panic(ptr::null_mut(), || {
+        if key.is_null() {
+            return new_handle(Err(
+                CryptoResult::PointerCannotBeNull.with_detail("key pointer is null")
+            ));
+        }
+        let key_slice = std::slice::from_raw_parts(key, key_length);
+        new_handle(
+            Algorithm::try_from(algorithm_id)
+                .and_then(|algorithm| Hasher::new_hmac(algorithm, key_slice)),
+        )
+    })
+}
+
+/// Crypto handle update
+/// Updates the context of a handle with input buffer, as `crypto_update`.
+///
+/// # Safety
+/// Pointers must not be null and input length must be correct. The handle must come from
+/// `crypto_new` or `crypto_hmac_new` and not be freed.
+#[must_use]
+#[no_mangle]
+pub unsafe extern "C" fn crypto_handle_update(
+    handle: *mut CryptoHandle,
+    input: *const u8,
+    input_length: usize,
+) -> CryptoResult {
+    error::guard(|| {
+        if handle.is_null() {
+            return CryptoResult::PointerCannotBeNull;
+        }
+        update(ptr::addr_of_mut!((*handle).checked), input, input_length)
+    })
+}
+
+/// Crypto handle finalize
+/// Outputs the digest (or MAC) of the context of a handle and resets it, as `crypto_finalize`.
+///
+/// # Safety
+/// Pointers must not be null and output length must be correct. The handle must come from
+/// `crypto_new` or `crypto_hmac_new` and not be freed.
+#[must_use]
+#[no_mangle]
+pub unsafe extern "C" fn crypto_handle_finalize(
+    handle: *mut CryptoHandle,
+    output: *mut u8,
+    output_length: usize,
+) -> CryptoResult {
+    error::guard(|| {
+        if handle.is_null() {
+            return CryptoResult::PointerCannotBeNull;
+        }
+        finalize(ptr::addr_of_mut!((*handle).checked), output, output_length)
+    })
+}
+
+/// Crypto handle finalize verify
+/// Finalizes the context of a handle and compares the result with `expected` in constant time,
+/// as `crypto_finalize_verify`.
+///
+/// # Safety
+/// Pointers must not be null and expected length must be correct. The handle must come from
+/// `crypto_new` or `crypto_hmac_new` and not be freed.
+#[must_use]
+#[no_mangle]
+pub unsafe extern "C" fn crypto_handle_finalize_verify(
+    handle: *mut CryptoHandle,
+    expected: *const u8,
+    expected_length: usize,
+) -> CryptoResult {
+    error::guard(|| {
+        if handle.is_null() {
+            return CryptoResult::PointerCannotBeNull;
+        }
+        finalize_verify(
+            ptr::addr_of_mut!((*handle).checked),
+            expected,
+            expected_length,
+        )
+    })
+}
+
+/// Crypto handle squeeze
+/// Reads the next `output_length` bytes of output from the extendable-output function context of
+/// a handle, as `crypto_squeeze`.
+///
+/// # Safety
+/// Pointers must not be null and output length must be correct. The handle must come from
+/// `crypto_new` or `crypto_hmac_new` and not be freed.
+#[must_use]
+#[no_mangle]
+pub unsafe extern "C" fn crypto_handle_squeeze(
+    handle: *mut CryptoHandle,
+    output: *mut u8,
+    output_length: usize,
+) -> CryptoResult {
+    error::guard(|| {
+        if handle.is_null() {
+            return CryptoResult::PointerCannotBeNull;
+        }
+        squeeze(ptr::addr_of_mut!((*handle).checked), output, output_length)
+    })
+}
+
+/// Crypto free
+/// Wipes and releases a handle. Null handles are ignored.
+///
+/// # Safety
+/// The handle must come from `crypto_new` or `crypto_hmac_new` and not be freed already.
+#[no_mangle]
+pub unsafe extern "C" fn crypto_free(handle: *mut CryptoHandle) {
+    error::catch_panic((), || {
+        if handle.is_null() {
+            return;
+        }
+        // SAFETY: all-zero memory is a valid `CryptoHandle` holding a zeroed SHA-256 state, which has
+        // no drop glue, so the box can still be dropped after the wipe.
+        zeroize::zeroize_raw(handle);
+        drop(Box::from_raw(handle));
+    })
+}
+
+/// Crypto hash
+/// Computes the digest of a single input buffer in one call, without a caller-owned context.
+///
+/// The output length must match the digest size of the given algorithm.
+///
+/// # Safety
+/// Pointers must not be null and lengths must be correct.
+#[must_use]
+#[no_mangle]
+pub unsafe extern "C" fn crypto_hash(
+    algorithm_id: u32,
+    input: *const u8,
+    input_length: usize,
+    output: *mut u8,
+    output_length: usize,
+) -> CryptoResult {
+    error::guard(|| {
+        if input.is_null() || output.is_null() {
+            return CryptoResult::PointerCannotBeNull;
+        }
+        let input_slice = std::slice::from_raw_parts(input, input_length);
+        let output_slice = std::slice::from_raw_parts_mut(output, output_length);
+        Algorithm::try_from(algorithm_id)
+            .and_then(|algorithm| Hasher::hash(algorithm, [input_slice], output_slice))
+            .err()
+            .unwrap_or(CryptoResult::Success)
+    })
+}
+
+/// Crypto hash multi
+/// Computes the digest of the concatenation of `buffer_count` input buffers in one call,
+/// without a caller-owned context.
+///
+/// The output length must match the digest size of the given algorithm.
+///
+/// # Safety
+/// Pointers must not be null, including the data pointer of every buffer, and lengths must be
+/// correct.
+#[must_use]
+#[no_mangle]
+pub unsafe extern "C" fn crypto_hash_multi(
+    algorithm_id: u32,
+    buffers: *const CryptoBuffer,
+    buffer_count: usize,
+    output: *mut u8,
+    output_length: usize,
+) -> CryptoResult {
+    error::guard(|| {
+        if buffers.is_null() || output.is_null() {
+            return CryptoResult::PointerCannotBeNull;
+        }
+        let buffer_slice = std::slice::from_raw_parts(buffers, buffer_count);
+        if buffer_slice.iter().any(|buffer| buffer.data.is_null()) {
+            return CryptoResult::PointerCannotBeNull;
+        }
+        let inputs = buffer_slice
+            .iter()
+            .map(|buffer| std::slice::from_raw_parts(buffer.data, buffer.length));
+        let output_slice = std::slice::from_raw_parts_mut(output, output_length);
+        Algorithm::try_from(algorithm_id)
+            .and_then(|algorithm| Hasher::hash(algorithm, inputs, output_slice))
+            .err()
+            .unwrap_or(CryptoResult::Success)
+    })
+}
+
+/// Crypto hash file
+/// Computes the digest of the file at `path`, which is memory-mapped rather than read. BLAKE3
+/// hashes it on all cores, other algorithms on the calling thread.
+///
+/// The output length must match the digest size of the given algorithm.
+///
+/// # Safety
+/// Pointers must not be null, `path` must be nul-terminated and output length must be correct.
+/// The file is memory-mapped, and must not be truncated or modified until the call returns.
+#[cfg(feature = "parallel")]
+#[must_use]
+#[no_mangle]
+pub unsafe extern "C" fn crypto_hash_file(
+    algorithm_id: u32,
+    path: *const c_char,
+    output: *mut u8,
+    output_length: usize,
+) -> CryptoResult {
+    error::guard(|| {
+        if path.is_null() || output.is_null() {
+            return CryptoResult::PointerCannotBeNull;
+        }
+        let output_slice = std::slice::from_raw_parts_mut(output, output_length);
+        Algorithm::try_from(algorithm_id)
+            .and_then(|algorithm| file::hash_file(algorithm, read_path(path)?, output_slice))
+            .err()
+            .unwrap_or(CryptoResult::Success)
+    })
+}
+
+/// Crypto hash file tree
+/// Computes the tree mode digest of the file at `path`: the RFC 6962 Merkle tree hash of its
+/// `chunk_size` byte chunks, which are hashed in parallel on all cores with any algorithm.
+///
+/// The digest depends on the chunk size and differs from the one of `crypto_hash_file`. The
+/// output length must match the digest size of the given algorithm, and a zero chunk size
+/// returns `BadParameter`.
+///
+/// # Safety
+/// Pointers must not be null, `path` must be nul-terminated and output length must be correct.
+/// The file is memory-mapped, and must not be truncated or modified until the call returns.
+#[cfg(feature = "parallel")]
+#[must_use]
+#[no_mangle]
+pub unsafe extern "C" fn crypto_hash_file_tree(
+    algorithm_id: u32,
+    path: *const c_char,
+    chunk_size: usize,
+    output: *mut u8,
+    output_length: usize,
+) -> CryptoResult {
+    error::guard(|| {
+        if path.is_null() || output.is_null() {
+            return CryptoResult::PointerCannotBeNull;
+        }
+        let output_slice = std::slice::from_raw_parts_mut(output, output_length);
+        Algorithm::try_from(algorithm_id)
+            .and_then(|algorithm| {
+                file::hash_file_tree(algorithm, read_path(path)?, chunk_size, output_slice)
+            })
+            .err()
+            .unwrap_or(CryptoResult::Success)
+    })
+}
+
+/// Borrows a nul-terminated path from C. Paths are bytes on Unix, and must be UTF-8 elsewhere.
+#[cfg(feature = "parallel")]
+unsafe fn read_path<'a>(path: *const c_char) -> Result<&'a std::path::Path, CryptoResult> {
+    let path = std::ffi::CStr::from_ptr(path);
+    #[cfg(unix)]
+    let path = <std::ffi::OsStr as std::os::unix::ffi::OsStrExt>::from_bytes(path.to_bytes());
+    #[cfg(not(unix))]
+    let path = path
+        .to_str()
+        .map_err(|_| CryptoResult::BadParameter.with_detail("path is not valid UTF-8"))?;
+    Ok(std::path::Path::new(path))
+}
+
+/// Crypto AEAD seal
+/// Encrypts `input` and authenticates it together with the associated data. The output is the
+/// ciphertext followed by the tag, so `output_length` must be `input_length +
+/// CRYPTO_AEAD_TAG_SIZE`.
+///
+/// Keys are `CRYPTO_AEAD_KEY_SIZE` bytes and nonces `CRYPTO_AEAD_NONCE_SIZE` bytes long, other
+/// lengths return `BadParameter`. A nonce must never be used twice with the same key. `output`
+/// may be the same pointer as `input` to encrypt in place, with room for the tag after the data.
+///
+/// Parameters are checked before anything is written to the output, which is zeroed if the
+/// encryption itself fails, so plaintext is never left in it on error.
+///
+/// # Safety
+/// Pointers must not be null, even for empty buffers, and lengths must be correct.
+#[cfg(feature = "aead")]
+#[must_use]
+#[no_mangle]
+pub unsafe extern "C" fn crypto_aead_seal(
+    aead_id: u32,
+    key: *const u8,
+    key_length: usize,
+    nonce: *const u8,
+    nonce_length: usize,
+    associated_data: *const u8,
+    associated_data_length: usize,
+    input: *const u8,
+    input_length: usize,
+    output: *mut u8,
+    output_length: usize,
+) -> CryptoResult {
+    error::guard(|| {
+        if key.is_null()
+            || nonce.is_null()
+            || associated_data.is_null()
+            || input.is_null()
+            || output.is_null()
+        {
+            return CryptoResult::PointerCannotBeNull;
+        }
+        let algorithm = match aead::AeadAlgorithm::try_from(aead_id) {
+            Ok(algorithm) => algorithm,
+            Err(error) => return error,
+        };
+        let Some(sealed_length) = input_length.checked_add(CRYPTO_AEAD_TAG_SIZE) else {
+            return CryptoResult::BadParameter.with_detail(format_args!(
+                "input is {input_length} bytes, too long to add the tag"
+            ));
+        };
+        if output_length != sealed_length {
+            return CryptoResult::bad_output_size(sealed_length, output_length);
+        }
+        let key = std::slice::from_raw_parts(key, key_length);
+        let nonce = std::slice::from_raw_parts(nonce, nonce_length);
+        if let Err(error) = aead::check_key_and_nonce(key, nonce) {
+            return error;
+        }
+        // The input is moved to the output before encrypting there, which also covers in-place
+        // calls, where input and output slices would alias.
+        ptr::copy(input, output, input_length);
+        let output = std::slice::from_raw_parts_mut(output, output_length);
+        let (buffer, tag) = output.split_at_mut(input_length);
+        aead::seal_in_place(
+            algorithm,
+            key,
+            nonce,
+            std::slice::from_raw_parts(associated_data, associated_data_length),
+            buffer,
+            tag,
+        )
+        .inspect_err(|_| output.fill(0))
+        .err()
+        .unwrap_or(CryptoResult::Success)
+    })
+}
+
+/// Crypto AEAD open
+/// Checks and decrypts the output of `crypto_aead_seal`: `input` is the ciphertext followed by
+/// the tag, and `output_length` must be `input_length - CRYPTO_AEAD_TAG_SIZE`.
+///
+/// Returns `VerificationFailed` if the ciphertext, the associated data, the key or the nonce
+/// differ from the sealed ones, in which case the output is zeroed. `output` may be the same
+/// pointer as `input` to decrypt in place.
+///
+/// # Safety
+/// Pointers must not be null, even for empty buffers, and lengths must be correct.
+#[cfg(feature = "aead")]
+#[must_use]
+#[no_mangle]
+pub unsafe extern "C" fn crypto_aead_open(
+    aead_id: u32,
+    key: *const u8,
+    key_length: usize,
+    nonce: *const u8,
+    nonce_length: usize,
+    associated_data: *const u8,
+    associated_data_length: usize,
+    input: *const u8,
+    input_length: usize,
+    output: *mut u8,
+    output_length: usize,
+) -> CryptoResult {
+    error::guard(|| {
+        if key.is_null()
+            || nonce.is_null()
+            || associated_data.is_null()
+            || input.is_null()
+            || output.is_null()
+        {
+            return CryptoResult::PointerCannotBeNull;
+        }
+        let algorithm = match aead::AeadAlgorithm::try_from(aead_id) {
+            Ok(algorithm) => algorithm,
+            Err(error) => return error,
+        };
+        let Some(data_length) = input_length.checked_sub(CRYPTO_AEAD_TAG_SIZE) else {
+            return CryptoResult::BadParameter.with_detail(format_args!(
+                "input is {input_length} bytes, shorter than the tag"
+            ));
+        };
+        if output_length != data_length {
+            return CryptoResult::bad_output_size(data_length, output_length);
+        }
+        // The tag is read before the data is moved to the output, which may overlap it.
+        let mut tag = [0u8; CRYPTO_AEAD_TAG_SIZE];
+        ptr::copy_nonoverlapping(input.add(data_length), tag.as_mut_ptr(), tag.len());
+        ptr::copy(input, output, data_length);
+        let buffer = std::slice::from_raw_parts_mut(output, output_length);
+        aead::open_in_place(
+            algorithm,
+            std::slice::from_raw_parts(key, key_length),
+            std::slice::from_raw_parts(nonce, nonce_length),
+            std::slice::from_raw_parts(associated_data, associated_data_length),
+            buffer,
+            &tag,
+        )
+        .inspect_err(|_| buffer.fill(0))
+        .err()
+        .unwrap_or(CryptoResult::Success)
+    })
+}
+
+/// Crypto Ed25519 public key
+/// Derives the public key of an Ed25519 seed (RFC 8032 key generation).
+///
+/// # Safety
+/// Pointers must not be null.
+#[cfg(feature = "ed25519")]
+#[must_use]
+#[no_mangle]
+pub unsafe extern "C" fn crypto_ed25519_public_key(
+    seed: *const CryptoEd25519Seed,
+    public_key: *mut CryptoEd25519PublicKey,
+) -> CryptoResult {
+    error::guard(|| {
+        if seed.is_null() || public_key.is_null() {
+            return CryptoResult::PointerCannotBeNull;
+        }
+        (*public_key).bytes = ed25519::public_key(&(*seed).bytes);
+        CryptoResult::Success
+    })
+}
+
+/// Crypto Ed25519 sign
+/// Signs a message with the key derived from an Ed25519 seed. Signatures are deterministic, so
+/// no random source is needed.
+///
+/// # Safety
+/// Pointers must not be null and message length must be correct.
+#[cfg(feature = "ed25519")]
+#[must_use]
+#[no_mangle]
+pub unsafe extern "C" fn crypto_ed25519_sign(
+    seed: *const CryptoEd25519Seed,
+    message: *const u8,
+    message_length: usize,
+    signature: *mut CryptoEd25519Signature,
+) -> CryptoResult {
+    error::guard(|| {
+        if seed.is_null() || message.is_null() || signature.is_null() {
+            return CryptoResult::PointerCannotBeNull;
+        }
+        let message_slice = std::slice::from_raw_parts(message, message_length);
+        (*signature).bytes = ed25519::sign(&(*seed).bytes, message_slice);
+        CryptoResult::Success
+    })
+}
+
+/// Crypto Ed25519 verify
+/// Verifies an Ed25519 signature of a message.
+///
+/// Returns `VerificationFailed` if the signature does not hold, and `BadParameter` if the public
+/// key is not a valid curve point. Verification is strict: non-canonical signatures and
+/// small-order public keys are rejected.
+///
+/// # Safety
+/// Pointers must not be null and message length must be correct.
+#[cfg(feature = "ed25519")]
+#[must_use]
+#[no_mangle]
+pub unsafe extern "C" fn crypto_ed25519_verify(
+    public_key: *const CryptoEd25519PublicKey,
+    message: *const u8,
+    message_length: usize,
+    signature: *const CryptoEd25519Signature,
+) -> CryptoResult {
+    error::guard(|| {
+        if public_key.is_null() || message.is_null() || signature.is_null() {
+            return CryptoResult::PointerCannotBeNull;
+        }
+        let message_slice = std::slice::from_raw_parts(message, message_length);
+        ed25519::verify(&(*public_key).bytes, message_slice, &(*signature).bytes)
+            .err()
+            .unwrap_or(CryptoResult::Success)
+    })
+}
+
+/// Crypto X25519 public key
+/// Derives the public key of an X25519 secret key, to be sent to the peer.
+///
+/// # Safety
+/// Pointers must not be null.
+#[cfg(feature = "x25519")]
+#[must_use]
+#[no_mangle]
+pub unsafe extern "C" fn crypto_x25519_public_key(
+    secret_key: *const CryptoX25519SecretKey,
+    public_key: *mut CryptoX25519PublicKey,
+) -> CryptoResult {
+    error::guard(|| {
+        if secret_key.is_null() || public_key.is_null() {
+            return CryptoResult::PointerCannotBeNull;
+        }
+        (*public_key).bytes = x25519::public_key(&(*secret_key).bytes);
+        CryptoResult::Success
+    })
+}
+
+/// Crypto X25519 shared secret
+/// Computes the shared secret of our secret key and the public key of the peer (RFC 7748 ECDH).
+///
+/// Returns `ZeroSharedSecret` if the peer public key is a low-order point, which gives an
+/// all-zero secret that does not depend on our key; the output is then left zeroed. The secret
+/// should be passed through a KDF before use, see `crypto_x25519_hkdf`.
+///
+/// # Safety
+/// Pointers must not be null.
+#[cfg(feature = "x25519")]
+#[must_use]
+#[no_mangle]
+pub unsafe extern "C" fn crypto_x25519_shared_secret(
+    secret_key: *const CryptoX25519SecretKey,
+    peer_public_key: *const CryptoX25519PublicKey,
+    shared_secret: *mut CryptoX25519SharedSecret,
+) -> CryptoResult {
+    error::guard(|| {
+        if secret_key.is_null() || peer_public_key.is_null() || shared_secret.is_null() {
+            return CryptoResult::PointerCannotBeNull;
+        }
+        match x25519::shared_secret(&(*secret_key).bytes, &(*peer_public_key).bytes) {
+            Ok(bytes) => {
+                (*shared_secret).bytes = bytes;
+                CryptoResult::Success
+            }
+            Err(error) => {
+                (*shared_secret).bytes = [0u8; CRYPTO_X25519_KEY_SIZE];
+                error
+            }
+        }
+    })
+}
+
+/// Crypto X25519 HKDF
+/// Computes the X25519 shared secret as `crypto_x25519_shared_secret`, and derives
+/// `okm_length` bytes of key material from it with HKDF, as `crypto_hkdf_extract` followed by
+/// `crypto_hkdf_expand` with the same hash algorithm ID would. The shared secret itself is
+/// never exposed.
+///
+/// The output can be at most 255 times the digest size long. Both parties get the same key
+/// material when they use the same salt and info.
+///
+/// # Safety
+/// Pointers must not be null, even for an empty salt or info, and lengths must be correct.
+#[cfg(feature = "x25519")]
+#[must_use]
+#[no_mangle]
+pub unsafe extern "C" fn crypto_x25519_hkdf(
+    algorithm_id: u32,
+    secret_key: *const CryptoX25519SecretKey,
+    peer_public_key: *const CryptoX25519PublicKey,
+    salt: *const u8,
+    salt_length: usize,
+    info: *const u8,
+    info_length: usize,
+    okm: *mut u8,
+    okm_length: usize,
+) -> CryptoResult {
+    error::guard(|| {
+        if secret_key.is_null()
+            || peer_public_key.is_null()
+            || salt.is_null()
+            || info.is_null()
+            || okm.is_null()
+        {
+            return CryptoResult::PointerCannotBeNull;
+        }
+        let salt_slice = std::slice::from_raw_parts(salt, salt_length);
+        let info_slice = std::slice::from_raw_parts(info, info_length);
+        let okm_slice = std::slice::from_raw_parts_mut(okm, okm_length);
+        Algorithm::try_from(algorithm_id)
+            .and_then(|algorithm| {
+                x25519::derive_key(
+                    algorithm,
+                    &(*secret_key).bytes,
+                    &(*peer_public_key).bytes,
+                    salt_slice,
+                    info_slice,
+                    okm_slice,
+                )
+            })
+            .err()
+            .unwrap_or(CryptoResult::Success)
+    })
+}
+
+/// Crypto random bytes
+/// Fills a buffer with random bytes from the operating system entropy source.
+///
+/// Returns `Failure`, with the OS error in `crypto_last_error_detail`, if the entropy source
+/// cannot be read.
+///
+/// # Safety
+/// Pointers must not be null and output length must be correct.
+#[cfg(feature = "random")]
+#[must_use]
+#[no_mangle]
+pub unsafe extern "C" fn crypto_random_bytes(
+    output: *mut u8,
+    output_length: usize,
+) -> CryptoResult {
+    error::guard(|| {
+        if output.is_null() {
+            return CryptoResult::PointerCannotBeNull;
+        }
+        let output_slice = std::slice::from_raw_parts_mut(output, output_length);
+        random::fill_random(output_slice)
+            .err()
+            .unwrap_or(CryptoResult::Success)
+    })
+}
+
+/// Crypto DRBG instantiate
+/// Allocates a Hash_DRBG generator (NIST SP 800-90A) with a SHA-2 or SHA-3 algorithm, seeded
+/// from an entropy input, a nonce and a personalization string.
+///
+/// The output is fully determined by the seed and later inputs, so a fixed seed gives
+/// reproducible streams for tests. The entropy input must be at least 32 bytes long (24 bytes for
+/// SHA-224 and SHA-512/224); the nonce and personalization string may be empty.
+///
+/// Returns null if a pointer is null, or if the algorithm is not supported or the entropy input
+/// is too short, with the reason in `crypto_last_error_detail`. The generator must be released
+/// with `crypto_drbg_free`.
+///
+/// # Safety
+/// Pointers must not be null and lengths must be correct.
+#[no_mangle]
+pub unsafe extern "C" fn crypto_drbg_instantiate(
+    algorithm_id: u32,
+    entropy: *const u8,
+    entropy_length: usize,
+    nonce: *const u8,
+    nonce_length: usize,
+    personalization: *const u8,
+    personalization_length: usize,
+) -> *mut CryptoDrbg {
+    error::clear_detail();
+    error::catch_panic(ptr::null_mut(), || {
+        if entropy.is_null() || nonce.is_null() || personalization.is_null() {
+            CryptoResult::PointerCannotBeNull
+                .with_detail("entropy, nonce or personalization pointer is null");
+            return ptr::null_mut();
+        }
+        let entropy_slice = std::slice::from_raw_parts(entropy, entropy_length);
+        let nonce_slice = std::slice::from_raw_parts(nonce, nonce_length);
+        let personalization_slice =
+            std::slice::from_raw_parts(personalization, personalization_length);
+        match Algorithm::try_from(algorithm_id).and_then(|algorithm| {
+            random::HashDrbg::new(algorithm, entropy_slice, nonce_slice, personalization_slice)
+        }) {
+            Ok(drbg) => Box::into_raw(Box::new(CryptoDrbg { drbg })),
+            Err(_) => ptr::null_mut(),
+        }
+    })
+}
+
+/// Crypto DRBG reseed
+/// Reseeds a Hash_DRBG generator with fresh entropy and optional additional input, which may be
+/// empty.
+///
+/// Returns `BadParameter` if the entropy input is shorter than for `crypto_drbg_instantiate`.
+///
+/// # Safety
+/// Pointers must not be null and lengths must be correct. The generator must come from
+/// `crypto_drbg_instantiate` and not be freed.
+#[must_use]
+#[no_mangle]
+pub unsafe extern "C" fn crypto_drbg_reseed(
+    drbg: *mut CryptoDrbg,
+    entropy: *const u8,
+    entropy_length: usize,
+    additional_input: *const u8,
+    additional_input_length: usize,
+) -> CryptoResult {
+    error::guard(|| {
+        if drbg.is_null() || entropy.is_null() || additional_input.is_null() {
+            return CryptoResult::PointerCannotBeNull;
+        }
+        let entropy_slice = std::slice::from_raw_parts(entropy, entropy_length);
+        let additional_slice =
+            std::slice::from_raw_parts(additional_input, additional_input_length);
+        (*drbg)
+            .drbg
+            .reseed(entropy_slice, additional_slice)
+            .err()
+            .unwrap_or(CryptoResult::Success)
+    })
+}
+
+/// Crypto DRBG generate
+/// Fills a buffer with pseudorandom bytes from a Hash_DRBG generator, after mixing in optional
+/// additional input, which may be empty.
+///
+/// Returns `BadBufferOutputSize` if more than `CRYPTO_DRBG_MAX_REQUEST_SIZE` bytes are requested.
+///
+/// # Safety
+/// Pointers must not be null and lengths must be correct. The generator must come from
+/// `crypto_drbg_instantiate` and not be freed.
+#[must_use]
+#[no_mangle]
+pub unsafe extern "C" fn crypto_drbg_generate(
+    drbg: *mut CryptoDrbg,
+    output: *mut u8,
+    output_length: usize,
+    additional_input: *const u8,
+    additional_input_length: usize,
+) -> CryptoResult {
+    error::guard(|| {
+        if drbg.is_null() || output.is_null() || additional_input.is_null() {
+            return CryptoResult::PointerCannotBeNull;
+        }
+        let output_slice = std::slice::from_raw_parts_mut(output, output_length);
+        let additional_slice =
+            std::slice::from_raw_parts(additional_input, additional_input_length);
+        (*drbg)
+            .drbg
+            .generate(output_slice, additional_slice)
+            .err()
+            .unwrap_or(CryptoResult::Success)
+    })
+}
+
+/// Crypto DRBG free
+/// Releases a Hash_DRBG generator, zeroizing its state. Null generators are ignored.
+///
+/// # Safety
+/// The generator must come from `crypto_drbg_instantiate` and not be freed already.
+#[no_mangle]
+pub unsafe extern "C" fn crypto_drbg_free(drbg: *mut CryptoDrbg) {
+    error::catch_panic((), || {
+        if !drbg.is_null() {
+            drop(Box::from_raw(drbg));
+        }
+    })
+}
+
+/// Crypto Merkle new
+/// Allocates an empty Merkle tree (RFC 6962) with a given hash algorithm.
+///
+/// Returns null if the algorithm is not supported, with the reason in `crypto_last_error_detail`.
+/// The tree must be released with `crypto_merkle_free`.
+#[no_mangle]
+pub extern "C" fn crypto_merkle_new(algorithm_id: u32) -> *mut CryptoMerkleTree {
+    error::clear_detail();
+    error::catch_panic(ptr::null_mut(), || {
+        match Algorithm::try_from(algorithm_id) {
+            Ok(algorithm) => Box::into_raw(Box::new(CryptoMerkleTree {
+                tree: merkle::MerkleTree::new(algorithm),
+            })),
+            Err(_) => ptr::null_mut(),
+        }
+    })
+}
+
+/// Crypto Merkle append
+/// Appends a leaf to a Merkle tree. The leaf is hashed as `H(0x00 || leaf)`.
+///
+/// # Safety
+/// Pointers must not be null and leaf length must be correct. The tree must come from
+/// `crypto_merkle_new` and not be freed.
+#[must_use]
+#[no_mangle]
+pub unsafe extern "C" fn crypto_merkle_append(
+    tree: *mut CryptoMerkleTree,
+    leaf: *const u8,
+    leaf_length: usize,
+) -> CryptoResult {
+    error::guard(|| {
+        if tree.is_null() || leaf.is_null() {
+            return CryptoResult::PointerCannotBeNull;
+        }
+        (*tree)
+            .tree
+            .push(std::slice::from_raw_parts(leaf, leaf_length));
+        CryptoResult::Success
+    })
+}
+
+/// Crypto Merkle leaf count
+/// Returns the number of leaves of a Merkle tree, or 0 if the tree is null.
+///
+/// # Safety
+/// The tree must come from `crypto_merkle_new` and not be freed.
+#[no_mangle]
+pub unsafe extern "C" fn crypto_merkle_leaf_count(tree: *const CryptoMerkleTree) -> usize {
+    error::catch_panic(0, || {
+        if tree.is_null() {
+            0
+        } else {
+            (*tree).tree.len()
+        }
+    })
+}
+
+/// Crypto Merkle root
+/// Writes the root hash of a Merkle tree. The root of the empty tree is the hash of the empty
+/// string.
+///
+/// The output length must match the digest size of the algorithm of the tree.
+///
+/// # Safety
+/// Pointers must not be null and output length must be correct. The tree must come from
+/// `crypto_merkle_new` and not be freed.
+#[must_use]
+#[no_mangle]
+pub unsafe extern "C" fn crypto_merkle_root(
+    tree: *const CryptoMerkleTree,
+    output: *mut u8,
+    output_length: usize,
+) -> CryptoResult {
+    error::guard(|| {
+        if tree.is_null() || output.is_null() {
+            return CryptoResult::PointerCannotBeNull;
+        }
+        let root = (*tree).tree.root();
+        if output_length != root.len() {
+            return CryptoResult::bad_output_size(root.len(), output_length);
+        }
+        ptr::copy_nonoverlapping(root.as_ptr(), output, root.len());
+        CryptoResult::Success
+    })
+}
+
+/// Crypto Merkle inclusion proof
+/// Writes the audit path of the leaf at `index` to `proof`, as concatenated hashes, and its
+/// length in bytes to `proof_length`.
+///
+/// Returns `BadParameter` if there is no leaf at `index`. If the proof is longer than
+/// `proof_capacity`, `BadBufferOutputSize` is returned and `proof_length` still receives the
+/// required length. A proof has at most 64 hashes.
+///
+/// # Safety
+/// Pointers must not be null and proof capacity must be correct. The tree must come from
+/// `crypto_merkle_new` and not be freed.
+#[must_use]
+#[no_mangle]
+pub unsafe extern "C" fn crypto_merkle_inclusion_proof(
+    tree: *const CryptoMerkleTree,
+    index: usize,
+    proof: *mut u8,
+    proof_capacity: usize,
+    proof_length: *mut usize,
+) -> CryptoResult {
+    error::guard(|| {
+        if tree.is_null() || proof.is_null() || proof_length.is_null() {
+            return CryptoResult::PointerCannotBeNull;
+        }
+        write_proof(
+            (*tree).tree.inclusion_proof(index),
+            proof,
+            proof_capacity,
+            proof_length,
+        )
+    })
+}
+
+/// Crypto Merkle consistency proof
+/// Writes the proof that the tree made of the first `old_size` leaves is a prefix of the current
+/// tree to `proof`, as concatenated hashes, and its length in bytes to `proof_length`.
+///
+/// Returns `BadParameter` unless `old_size` is between 1 and the number of leaves. Buffers that
+/// are too small are handled as by `crypto_merkle_inclusion_proof`.
+///
+/// # Safety
+/// Pointers must not be null and proof capacity must be correct. The tree must come from
+/// `crypto_merkle_new` and not be freed.
+#[must_use]
+#[no_mangle]
+pub unsafe extern "C" fn crypto_merkle_consistency_proof(
+    tree: *const CryptoMerkleTree,
+    old_size: usize,
+    proof: *mut u8,
+    proof_capacity: usize,
+    proof_length: *mut usize,
+) -> CryptoResult {
+    error::guard(|| {
+        if tree.is_null() || proof.is_null() || proof_length.is_null() {
+            return CryptoResult::PointerCannotBeNull;
+        }
+        write_proof(
+            (*tree).tree.consistency_proof(old_size),
+            proof,
+            proof_capacity,
+            proof_length,
+        )
+    })
+}
+
+/// Crypto Merkle verify inclusion
+/// Verifies that `leaf` is the leaf at `index` of the tree of `tree_size` leaves with the given
+/// root, using an audit path from `crypto_merkle_inclusion_proof`.
+///
+/// Returns `VerificationFailed` if the proof does not hold, and `BadBufferOutputSize` if the
+/// root length is not the digest size of the algorithm.
+///
+/// # Safety
+/// Pointers must not be null and lengths must be correct.
+#[must_use]
+#[no_mangle]
+pub unsafe extern "C" fn crypto_merkle_verify_inclusion(
+    algorithm_id: u32,
+    leaf: *const u8,
+    leaf_length: usize,
+    index: usize,
+    tree_size: usize,
+    proof: *const u8,
+    proof_length: usize,
+    root: *const u8,
+    root_length: usize,
+) -> CryptoResult {
+    error::guard(|| {
+        if leaf.is_null() || proof.is_null() || root.is_null() {
+            return CryptoResult::PointerCannotBeNull;
+        }
+        let leaf_slice = std::slice::from_raw_parts(leaf, leaf_length);
+        let proof_slice = std::slice::from_raw_parts(proof, proof_length);
+        let root_slice = std::slice::from_raw_parts(root, root_length);
+        Algorithm::try_from(algorithm_id)
+            .and_then(|algorithm| {
+                merkle::verify_inclusion(
+                    algorithm,
+                    leaf_slice,
+                    index,
+                    tree_size,
+                    proof_slice,
+                    root_slice,
+                )
+            })
+            .err()
+            .unwrap_or(CryptoResult::Success)
+    })
+}
+
+/// Crypto Merkle verify consistency
+/// Verifies that the tree of `old_size` leaves with root `old_root` is a prefix of the tree of
+/// `new_size` leaves with root `new_root`, using a proof from `crypto_merkle_consistency_proof`.
+///
+/// Both roots are `root_length` bytes long. Returns `VerificationFailed` if the proof does not
+/// hold, and `BadBufferOutputSize` if the root length is not the digest size of the algorithm.
+///
+/// # Safety
+/// Pointers must not be null and lengths must be correct.
+#[must_use]
+#[no_mangle]
+pub unsafe extern "C" fn crypto_merkle_verify_consistency(
+    algorithm_id: u32,
+    old_size: usize,
+    new_size: usize,
+    proof: *const u8,
+    proof_length: usize,
+    old_root: *const u8,
+    new_root: *const u8,
+    root_length: usize,
+) -> CryptoResult {
+    error::guard(|| {
+        if proof.is_null() || old_root.is_null() || new_root.is_null() {
+            return CryptoResult::PointerCannotBeNull;
+        }
+        let proof_slice = std::slice::from_raw_parts(proof, proof_length);
+        let old_root_slice = std::slice::from_raw_parts(old_root, root_length);
+        let new_root_slice = std::slice::from_raw_parts(new_root, root_length);
+        Algorithm::try_from(algorithm_id)
+            .and_then(|algorithm| {
+                merkle::verify_consistency(
+                    algorithm,
+                    old_size,
+                    new_size,
+                    proof_slice,
+                    old_root_slice,
+                    new_root_slice,
+                )
+            })
+            .err()
+            .unwrap_or(CryptoResult::Success)
+    })
+}
+
+/// Crypto Merkle free
+/// Releases a Merkle tree. Null trees are ignored.
+///
+/// # Safety
+/// The tree must come from `crypto_merkle_new` and not be freed already.
+#[no_mangle]
+pub unsafe extern "C" fn crypto_merkle_free(tree: *mut CryptoMerkleTree) {
+    error::catch_panic((), || {
+        if !tree.is_null() {
+            drop(Box::from_raw(tree));
+        }
+    })
+}
+
+/// Crypto HKDF extract
+/// Derives a pseudorandom key from input keying material and a salt (RFC 5869 HKDF-Extract).
+///
+/// The output length must be the digest size of the given algorithm. An empty salt is equivalent
+/// to the RFC default salt of digest size zero bytes.
+///
+/// # Safety
+/// Pointers must not be null and lengths must be correct.
+#[must_use]
+#[no_mangle]
+pub unsafe extern "C" fn crypto_hkdf_extract(
+    algorithm_id: u32,
+    salt: *const u8,
+    salt_length: usize,
+    ikm: *const u8,
+    ikm_length: usize,
+    prk: *mut u8,
+    prk_length: usize,
+) -> CryptoResult {
+    error::guard(|| {
+        if salt.is_null() || ikm.is_null() || prk.is_null() {
+            return CryptoResult::PointerCannotBeNull;
+        }
+        let salt_slice = std::slice::from_raw_parts(salt, salt_length);
+        let ikm_slice = std::slice::from_raw_parts(ikm, ikm_length);
+        let prk_slice = std::slice::from_raw_parts_mut(prk, prk_length);
+        Algorithm::try_from(algorithm_id)
+            .and_then(|algorithm| kdf::hkdf_extract(algorithm, salt_slice, ikm_slice, prk_slice))
+            .err()
+            .unwrap_or(CryptoResult::Success)
+    })
+}
+
+/// Crypto HKDF expand
+/// Expands a pseudorandom key into output keying material bound to `info` (RFC 5869
+/// HKDF-Expand).
+///
+/// The output length can be at most 255 times the digest size of the given algorithm, larger
+/// outputs return `BadBufferOutputSize`. The pseudorandom key should be at least the digest size,
+/// e.g. the output of `crypto_hkdf_extract`.
+///
+/// # Safety
+/// Pointers must not be null and lengths must be correct.
+#[must_use]
+#[no_mangle]
+pub unsafe extern "C" fn crypto_hkdf_expand(
+    algorithm_id: u32,
+    prk: *const u8,
+    prk_length: usize,
+    info: *const u8,
+    info_length: usize,
+    okm: *mut u8,
+    okm_length: usize,
+) -> CryptoResult {
+    error::guard(|| {
+        if prk.is_null() || info.is_null() || okm.is_null() {
+            return CryptoResult::PointerCannotBeNull;
+        }
+        let prk_slice = std::slice::from_raw_parts(prk, prk_length);
+        let info_slice = std::slice::from_raw_parts(info, info_length);
+        let okm_slice = std::slice::from_raw_parts_mut(okm, okm_length);
+        Algorithm::try_from(algorithm_id)
+            .and_then(|algorithm| kdf::hkdf_expand(algorithm, prk_slice, info_slice, okm_slice))
+            .err()
+            .unwrap_or(CryptoResult::Success)
+    })
+}
+
+/// Crypto Argon2id hash
+/// Hashes a password with Argon2id (RFC 9106) and writes it to `phc` as a null-terminated PHC
+/// string, e.g. `$argon2id$v=19$m=19456,t=2,p=1$<salt>$<hash>`, to be stored and later checked
+/// with `crypto_argon2id_verify`.
+///
+/// The salt must be unique per password, ideally 16 bytes from `crypto_random_bytes`, and between
+/// 8 and 48 bytes long. Returns `BadParameter` if the salt length or a cost parameter is out of
+/// range, and `BadBufferOutputSize` if the string does not fit `phc_capacity` bytes, which never
+/// happens with `CRYPTO_ARGON2_PHC_MAX_SIZE` bytes.
+///
+/// # Safety
+/// Pointers must not be null and lengths must be correct.
+#[cfg(feature = "argon2")]
+#[must_use]
+#[no_mangle]
+pub unsafe extern "C" fn crypto_argon2id_hash(
+    password: *const u8,
+    password_length: usize,
+    salt: *const u8,
+    salt_length: usize,
+    params: *const CryptoArgon2Params,
+    phc: *mut c_char,
+    phc_capacity: usize,
+) -> CryptoResult {
+    error::guard(|| {
+        if password.is_null() || salt.is_null() || params.is_null() || phc.is_null() {
+            return CryptoResult::PointerCannotBeNull;
+        }
+        let password_slice = std::slice::from_raw_parts(password, password_length);
+        let salt_slice = std::slice::from_raw_parts(salt, salt_length);
+        let hash = match password::hash_password(password_slice, salt_slice, &*params) {
+            Ok(hash) => hash,
+            Err(error) => return error,
+        };
+        if hash.len() >= phc_capacity {
+            return CryptoResult::bad_output_size(
+                format_args!("at least {}", hash.len() + 1),
+                phc_capacity,
+            );
+        }
+        ptr::copy_nonoverlapping(hash.as_ptr(), phc.cast(), hash.len());
+        *phc.add(hash.len()) = 0;
+        CryptoResult::Success
+    })
+}
+
+/// Crypto Argon2id verify
+/// Verifies a password against a null-terminated PHC string from `crypto_argon2id_hash`, using
+/// the cost parameters, salt and hash length of the string.
+///
+/// Returns `VerificationFailed` if the password does not match, `BadParameter` if the string
+/// cannot be parsed or its parameters are out of range, and `BadOrUnsupportedAlgorithm` if it is
+/// not an Argon2id hash.
+///
+/// # Safety
+/// Pointers must not be null, password length must be correct and the string must be null
+/// terminated.
+#[cfg(feature = "argon2")]
+#[must_use]
+#[no_mangle]
+pub unsafe extern "C" fn crypto_argon2id_verify(
+    password: *const u8,
+    password_length: usize,
+    phc: *const c_char,
+) -> CryptoResult {
+    error::guard(|| {
+        if password.is_null() || phc.is_null() {
+            return CryptoResult::PointerCannotBeNull;
+        }
+        let password_slice = std::slice::from_raw_parts(password, password_length);
+        let phc = match std::ffi::CStr::from_ptr(phc).to_str() {
+            Ok(phc) => phc,
+            Err(_) => {
+                return CryptoResult::BadParameter.with_detail("PHC string is not valid UTF-8")
+            }
+        };
+        password::verify_password(password_slice, phc)
+            .err()
+            .unwrap_or(CryptoResult::Success)
+    })
+}
+
+/// Crypto PBKDF2
+/// Derives a key from a password and a salt using PBKDF2 with HMAC of the given algorithm
+/// (RFC 8018).
+///
+/// The iteration count must be between 1 and `CRYPTO_PBKDF2_MAX_ITERATIONS`, otherwise
+/// `BadParameter` is returned.
+///
+/// # Safety
+/// Pointers must not be null and lengths must be correct.
+#[must_use]
+#[no_mangle]
+pub unsafe extern "C" fn crypto_pbkdf2(
+    algorithm_id: u32,
+    password: *const u8,
+    password_length: usize,
+    salt: *const u8,
+    salt_length: usize,
+    iterations: u32,
+    output: *mut u8,
+    output_length: usize,
+) -> CryptoResult {
+    error::guard(|| {
+        if password.is_null() || salt.is_null() || output.is_null() {
+            return CryptoResult::PointerCannotBeNull;
+        }
+        let password_slice = std::slice::from_raw_parts(password, password_length);
+        let salt_slice = std::slice::from_raw_parts(salt, salt_length);
+        let output_slice = std::slice::from_raw_parts_mut(output, output_length);
+        Algorithm::try_from(algorithm_id)
+            .and_then(|algorithm| {
+                kdf::pbkdf2(
+                    algorithm,
+                    password_slice,
+                    salt_slice,
+                    iterations,
+                    output_slice,
+                )
+            })
+            .err()
+            .unwrap_or(CryptoResult::Success)
+    })
+}
+
+/// Binds the C API functions to the CRYPTOAPI_0 symbol version of libcryptoapi.so, with the
+/// `symbol_versions` feature. rustc's own version script lists every export in an anonymous
+/// version, which wins over `cryptoapi.map`, so the symbols are renamed with `.symver` directives.
+/// "@@@" drops the plain name, which executables linking the static library still resolve to the
+/// versioned one.
+///
+/// The directives only apply to functions of the same object file: the C API functions must be
+/// defined in this module, and listed below, as `test_symbol_versions` checks.
+macro_rules! symbol_versions {
+    ($($name:ident)*) => {
+        #[cfg(all(feature = "symbol_versions", target_os = "linux"))]
+        std::arch::global_asm!($(
+            concat!(".symver ", stringify!($name), ", ", stringify!($name), "@@@CRYPTOAPI_0")
+        ),*);
+        #[cfg(test)]
+        const VERSIONED_FUNCTIONS: &[&str] = &[$(stringify!($name)),*];
+    };
+}
+
+symbol_versions! {
+    crypto_api_version crypto_result_str crypto_last_error_detail crypto_algorithm_supported
+    crypto_output_size crypto_block_size crypto_algorithm_name crypto_algorithm_count
+    crypto_algorithm_id crypto_init crypto_hmac_init crypto_keyed_init crypto_derive_key_init
+    crypto_update crypto_finalize crypto_finalize_verify crypto_verify crypto_squeeze
+    crypto_destroy crypto_clone crypto_export_state crypto_import_state crypto_new crypto_hmac_new crypto_handle_update
+    crypto_handle_finalize crypto_handle_finalize_verify crypto_handle_squeeze crypto_free
+    crypto_hash crypto_hash_multi crypto_hash_file crypto_hash_file_tree crypto_aead_seal
+    crypto_aead_open crypto_ed25519_public_key crypto_ed25519_sign crypto_ed25519_verify
+    crypto_x25519_public_key crypto_x25519_shared_secret crypto_x25519_hkdf crypto_random_bytes
+    crypto_drbg_instantiate crypto_drbg_reseed crypto_drbg_generate crypto_drbg_free
+    crypto_merkle_new crypto_merkle_append crypto_merkle_leaf_count crypto_merkle_root
+    crypto_merkle_inclusion_proof crypto_merkle_consistency_proof crypto_merkle_verify_inclusion
+    crypto_merkle_verify_consistency crypto_merkle_free crypto_hkdf_extract crypto_hkdf_expand
+    crypto_argon2id_hash crypto_argon2id_verify crypto_pbkdf2
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CStr;
+
+    #[test]
+    fn test_symbol_versions() {
+        let header = include_str!("api/crypto_api.h");
+        // Names followed by an opening parenthesis: the function declarations.
+        let mut functions: Vec<_> = header
+            .split('(')
+            .filter_map(|text| {
+                text.rsplit(|c: char| !(c.is_alphanumeric() || c == '_'))
+                    .next()
+            })
+            .filter(|name| name.starts_with("crypto_"))
+            .collect();
+        functions.sort_unstable();
+        functions.dedup();
+        let mut versioned = VERSIONED_FUNCTIONS.to_vec();
+        versioned.sort_unstable();
+        assert_eq!(functions, versioned);
+        let source = include_str!("lib.rs");
+        for function in VERSIONED_FUNCTIONS {
+            assert!(
+                source.contains(&format!("extern \"C\" fn {function}(")),
+                "{function} must be defined in lib.rs to get a symbol version"
+            );
+        }
+    }
+
+    #[test]
+    fn test_sha3_256() {
+        let mut state = CryptoContext {
+            state: [0u64; CRYPTO_CONTEXT_STATE_SIZE],
+        };
+        unsafe {
+            assert_eq!(
+                crypto_init(&mut state as *mut CryptoContext, SHA3_256_ALG_ID),
+                CryptoResult::Success
+            );
+        };
+
+        // Update with the string "hello"
+        let input = b"hello";
+        unsafe {
+            assert_eq!(
+                crypto_update(
+                    &mut state as *mut CryptoContext,
+                    input.as_ptr(),
+                    input.len(),
+                ),
+                CryptoResult::Success
+            )
+        }
+
+        // Finalize and get the digest
+        let mut digest = [0u8; 32];
+        unsafe {
+            assert_eq!(
+                crypto_finalize(
+                    &mut state as *mut CryptoContext,
+                    digest.as_mut_ptr(),
+                    digest.len()
+                ),
+                CryptoResult::Success
+            )
+        };
+
+        // Expected output of SHA3_256 of hello string (utf8)
+        let expected: [u8; 32] = [
+            0x33, 0x38, 0xbe, 0x69, 0x4f, 0x50, 0xc5, 0xf3, 0x38, 0x81, 0x49, 0x86, 0xcd, 0xf0,
+            0x68, 0x64, 0x53, 0xa8, 0x88, 0xb8, 0x4f, 0x42, 0x4d, 0x79, 0x2a, 0xf4, 0xb9, 0x20,
+            0x23, 0x98, 0xf3, 0x92,
+        ];
+
+        assert_eq!(digest, expected);
+    }
+
+    #[test]
+    fn test_sha256() {
+        // Initialize the state
+        let mut state = CryptoContext {
+            state: [0u64; CRYPTO_CONTEXT_STATE_SIZE],
+        };
+        unsafe {
+            assert_eq!(
+                crypto_init(&mut state as *mut CryptoContext, SHA256_ALG_ID),
+                CryptoResult::Success
+            );
+        };
+
+        // Update with the string "hello"
+        let input = b"hello";
+        unsafe {
+            assert_eq!(
+                crypto_update(
+                    &mut state as *mut CryptoContext,
+                    input.as_ptr(),
+                    input.len(),
+                ),
+                CryptoResult::Success
+            )
+        }
+
+        // Finalize and get the digest
+        let mut digest = [0u8; 32];
+        unsafe {
+            assert_eq!(
+                crypto_finalize(
+                    &mut state as *mut CryptoContext,
+                    digest.as_mut_ptr(),
+                    digest.len()
+                ),
+                CryptoResult::Success
+            )
+        };
+
+        // Expected output of SHA256 of hello string (utf8)
+        let expected: [u8; 32] = [
+            0x2c, 0xf2, 0x4d, 0xba, 0x5f, 0xb0, 0xa3, 0x0e, 0x26, 0xe8, 0x3b, 0x2a, 0xc5, 0xb9,
+            0xe2, 0x9e, 0x1b, 0x16, 0x1e, 0x5c, 0x1f, 0xa7, 0x42, 0x5e, 0x73, 0x04, 0x33, 0x62,
+            0x93, 0x8b, 0x98, 0x24,
+        ];
+
+        assert_eq!(digest, expected);
+    }
+
+    /// Runs a full HMAC computation through the C API and writes the MAC into `mac`.
+    fn hmac(algorithm_id: u32, key: &[u8], message: &[u8], mac: &mut [u8]) {
+        let mut state = CryptoContext {
+            state: [0u64; CRYPTO_CONTEXT_STATE_SIZE],
+        };
+        unsafe {
+            assert_eq!(
+                crypto_hmac_init(
+                    &mut state as *mut CryptoContext,
+                    algorithm_id,
+                    key.as_ptr(),
+                    key.len()
+                ),
+                CryptoResult::Success
+            );
+            assert_eq!(
+                crypto_update(
+                    &mut state as *mut CryptoContext,
+                    message.as_ptr(),
+                    message.len()
+                ),
+                CryptoResult::Success
+            );
+            assert_eq!(
+                crypto_finalize(
+                    &mut state as *mut CryptoContext,
+                    mac.as_mut_ptr(),
+                    mac.len()
+                ),
+                CryptoResult::Success
+            );
+        }
+    }
+
+    #[test]
+    fn test_hmac_sha256_rfc4231() {
+        // RFC 4231 test cases 1, 2, 3, 4, 6 and 7 (case 5 uses a truncated output)
+        let key_25: Vec<u8> = (0x01..=0x19).collect();
+        let long_message = b"This is a test using a larger than block-size key and a larger \
+            than block-size data. The key needs to be hashed before being used by the HMAC \
+            algorithm.";
+        let cases: [(&[u8], &[u8], [u8; 32]); 6] = [
+            (
+                &[0x0b; 20],
+                b"Hi There",
+                [
+                    0xb0, 0x34, 0x4c, 0x61, 0xd8, 0xdb, 0x38, 0x53, 0x5c, 0xa8, 0xaf, 0xce, 0xaf,
+                    0x0b, 0xf1, 0x2b, 0x88, 0x1d, 0xc2, 0x00, 0xc9, 0x83, 0x3d, 0xa7, 0x26, 0xe9,
+                    0x37, 0x6c, 0x2e, 0x32, 0xcf, 0xf7,
+                ],
+            ),
+            (
+                b"Jefe",
+                b"what do ya want for nothing?",
+                [
+                    0x5b, 0xdc, 0xc1, 0x46, 0xbf, 0x60, 0x75, 0x4e, 0x6a, 0x04, 0x24, 0x26, 0x08,
+                    0x95, 0x75, 0xc7, 0x5a, 0x00, 0x3f, 0x08, 0x9d, 0x27, 0x39, 0x83, 0x9d, 0xec,
+                    0x58, 0xb9, 0x64, 0xec, 0x38, 0x43,
+                ],
+            ),
+            (
+                &[0xaa; 20],
+                &[0xdd; 50],
+                [
+                    0x77, 0x3e, 0xa9, 0x1e, 0x36, 0x80, 0x0e, 0x46, 0x85, 0x4d, 0xb8, 0xeb, 0xd0,
+                    0x91, 0x81, 0xa7, 0x29, 0x59, 0x09, 0x8b, 0x3e, 0xf8, 0xc1, 0x22, 0xd9, 0x63,
+                    0x55, 0x14, 0xce, 0xd5, 0x65, 0xfe,
+                ],
+            ),
+            (
+                &key_25,
+                &[0xcd; 50],
+                [
+                    0x82, 0x55, 0x8a, 0x38, 0x9a, 0x44, 0x3c, 0x0e, 0xa4, 0xcc, 0x81, 0x98, 0x99,
+                    0xf2, 0x08, 0x3a, 0x85, 0xf0, 0xfa, 0xa3, 0xe5, 0x78, 0xf8, 0x07, 0x7a, 0x2e,
+                    0x3f, 0xf4, 0x67, 0x29, 0x66, 0x5b,
+                ],
+            ),
+            (
+                &[0xaa; 131],
+                b"Test Using Larger Than Block-Size Key - Hash Key First",
+                [
+                    0x60, 0xe4, 0x31, 0x59, 0x1e, 0xe0, 0xb6, 0x7f, 0x0d, 0x8a, 0x26, 0xaa, 0xcb,
+                    0xf5, 0xb7, 0x7f, 0x8e, 0x0b, 0xc6, 0x21, 0x37, 0x28, 0xc5, 0x14, 0x05, 0x46,
+                    0x04, 0x0f, 0x0e, 0xe3, 0x7f, 0x54,
+                ],
+            ),
+            (
+                &[0xaa; 131],
+                long_message,
+                [
+                    0x9b, 0x09, 0xff, 0xa7, 0x1b, 0x94, 0x2f, 0xcb, 0x27, 0x63, 0x5f, 0xbc, 0xd5,
+                    0xb0, 0xe9, 0x44, 0xbf, 0xdc, 0x63, 0x64, 0x4f, 0x07, 0x13, 0x93, 0x8a, 0x7f,
+                    0x51, 0x53, 0x5c, 0x3a, 0x35, 0xe2,
+                ],
+            ),
+        ];
+
+        for (key, message, expected) in cases {
+            let mut mac = [0u8; 32];
+            hmac(SHA256_ALG_ID, key, message, &mut mac);
+            assert_eq!(mac, expected);
+        }
+    }
+
+    #[test]
+    fn test_hmac_sha3_256_nist() {
+        // NIST HMAC_SHA3-256 example values, samples #1 to #3
+        let key: Vec<u8> = (0..=0xa7).collect();
+        let cases: [(usize, &[u8], [u8; 32]); 3] = [
+            (
+                32,
+                b"Sample message for keylen<blocklen",
+                [
+                    0x4f, 0xe8, 0xe2, 0x02, 0xc4, 0xf0, 0x58, 0xe8, 0xdd, 0xdc, 0x23, 0xd8, 0xc3,
+                    0x4e, 0x46, 0x73, 0x43, 0xe2, 0x35, 0x55, 0xe2, 0x4f, 0xc2, 0xf0, 0x25, 0xd5,
+                    0x98, 0xf5, 0x58, 0xf6, 0x72, 0x05,
+                ],
+            ),
+            (
+                136,
+                b"Sample message for keylen=blocklen",
+                [
+                    0x68, 0xb9, 0x4e, 0x2e, 0x53, 0x8a, 0x9b, 0xe4, 0x10, 0x3b, 0xeb, 0xb5, 0xaa,
+                    0x01, 0x6d, 0x47, 0x96, 0x1d, 0x4d, 0x1a, 0xa9, 0x06, 0x06, 0x13, 0x13, 0xb5,
+                    0x57, 0xf8, 0xaf, 0x2c, 0x3f, 0xaa,
+                ],
+            ),
+            (
+                168,
+                b"Sample message for keylen>blocklen",
+                [
+                    0x9b, 0xcf, 0x2c, 0x23, 0x8e, 0x23, 0x5c, 0x3c, 0xe8, 0x84, 0x04, 0xe8, 0x13,
+                    0xbd, 0x2f, 0x3a, 0x97, 0x18, 0x5a, 0xc6, 0xf2, 0x38, 0xc6, 0x3d, 0x62, 0x29,
+                    0xa0, 0x0b, 0x07, 0x97, 0x42, 0x58,
+                ],
+            ),
+        ];
+
+        for (key_length, message, expected) in cases {
+            let mut mac = [0u8; 32];
+            hmac(SHA3_256_ALG_ID, &key[..key_length], message, &mut mac);
+            assert_eq!(mac, expected);
+        }
+    }
+
+    #[cfg(feature = "sha3_512")]
+    #[test]
+    fn test_hmac_sha3_512_nist() {
+        // NIST HMAC_SHA3-512 example values, samples #1 to #3
+        let key: Vec<u8> = (0..=0x87).collect();
+        let cases: [(usize, &[u8], [u8; 64]); 3] = [
+            (
+                64,
+                b"Sample message for keylen<blocklen",
+                [
+                    0x4e, 0xfd, 0x62, 0x9d, 0x6c, 0x71, 0xbf, 0x86, 0x16, 0x26, 0x58, 0xf2, 0x99,
+                    0x43, 0xb1, 0xc3, 0x08, 0xce, 0x27, 0xcd, 0xfa, 0x6d, 0xb0, 0xd9, 0xc3, 0xce,
+                    0x81, 0x76, 0x3f, 0x9c, 0xbc, 0xe5, 0xf7, 0xeb, 0xe9, 0x86, 0x80, 0x31, 0xdb,
+                    0x1a, 0x8f, 0x8e, 0xb7, 0xb6, 0xb9, 0x5e, 0x5c, 0x5e, 0x3f, 0x65, 0x7a, 0x89,
+                    0x96, 0xc8, 0x6a, 0x2f, 0x65, 0x27, 0xe3, 0x07, 0xf0, 0x21, 0x31, 0x96,
+                ],
+            ),
+            (
+                72,
+                b"Sample message for keylen=blocklen",
+                [
+                    0x54, 0x4e, 0x25, 0x7e, 0xa2, 0xa3, 0xe5, 0xea, 0x19, 0xa5, 0x90, 0xe6, 0xa2,
+                    0x4b, 0x72, 0x4c, 0xe6, 0x32, 0x77, 0x57, 0x72, 0x3f, 0xe2, 0x75, 0x1b, 0x75,
+                    0xbf, 0x00, 0x7d, 0x80, 0xf6, 0xb3, 0x60, 0x74, 0x4b, 0xf1, 0xb7, 0xa8, 0x8e,
+                    0xa5, 0x85, 0xf9, 0x76, 0x5b, 0x47, 0x91, 0x19, 0x76, 0xd3, 0x19, 0x1c, 0xf8,
+                    0x3c, 0x03, 0x9f, 0x5f, 0xfa, 0xb0, 0xd2, 0x9c, 0xc9, 0xd9, 0xb6, 0xda,
+                ],
+            ),
+            (
+                136,
+                b"Sample message for keylen>blocklen",
+                [
+                    0x5f, 0x46, 0x4f, 0x5e, 0x5b, 0x78, 0x48, 0xe3, 0x88, 0x5e, 0x49, 0xb2, 0xc3,
+                    0x85, 0xf0, 0x69, 0x49, 0x85, 0xd0, 0xe3, 0x89, 0x66, 0x24, 0x2d, 0xc4, 0xa5,
+                    0xfe, 0x3f, 0xea, 0x4b, 0x37, 0xd4, 0x6b, 0x65, 0xce, 0xce, 0xd5, 0xdc, 0xf5,
+                    0x94, 0x38, 0xdd, 0x84, 0x0b, 0xab, 0x22, 0x26, 0x9f, 0x0b, 0xa7, 0xfe, 0xbd,
+                    0xb9, 0xfc, 0xf7, 0x46, 0x02, 0xa3, 0x56, 0x66, 0xb2, 0xa3, 0x29, 0x15,
+                ],
+            ),
+        ];
+
+        for (key_length, message, expected) in cases {
+            let mut mac = [0u8; 64];
+            hmac(SHA3_512_ALG_ID, &key[..key_length], message, &mut mac);
+            assert_eq!(mac, expected);
+        }
+    }
+
+    #[test]
+    fn test_hmac_reuse_after_finalize() {
+        // Finalize resets the context to its keyed state, so a second message gives the
+        // same MAC as a fresh context.
+        let key = b"Jefe";
+        let message = b"what do ya want for nothing?";
+        let mut expected = [0u8; 32];
+        hmac(SHA256_ALG_ID, key, message, &mut expected);
+
+        let mut state = CryptoContext {
+            state: [0u64; CRYPTO_CONTEXT_STATE_SIZE],
+        };
+        let ctx = &mut state as *mut CryptoContext;
+        let mut mac = [0u8; 32];
+        unsafe {
+            assert_eq!(
+                crypto_hmac_init(ctx, SHA256_ALG_ID, key.as_ptr(), key.len()),
+                CryptoResult::Success
+            );
+            for _ in 0..2 {
+                assert_eq!(
+                    crypto_update(ctx, message.as_ptr(), message.len()),
+                    CryptoResult::Success
+                );
+                assert_eq!(
+                    crypto_finalize(ctx, mac.as_mut_ptr(), mac.len()),
+                    CryptoResult::Success
+                );
+                assert_eq!(mac, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_hmac_init_errors() {
+        let mut state = CryptoContext {
+            state: [0u64; CRYPTO_CONTEXT_STATE_SIZE],
+        };
+        let key = [0u8; 16];
+        unsafe {
+            assert_eq!(
+                crypto_hmac_init(ptr::null_mut(), SHA256_ALG_ID, key.as_ptr(), key.len()),
+                CryptoResult::PointerCannotBeNull
+            );
+            assert_eq!(
+                crypto_hmac_init(&mut state, SHA256_ALG_ID, ptr::null(), 0),
+                CryptoResult::PointerCannotBeNull
+            );
+            assert_eq!(
+                crypto_hmac_init(&mut state, 0xff, key.as_ptr(), key.len()),
+                CryptoResult::BadOrUnsupportedAlgorithm
+            );
+        }
+    }
+
+    #[test]
+    fn test_hkdf_sha256_rfc5869() {
+        // RFC 5869 test cases 1, 2 and 3
+        let ikm_22 = [0x0b; 22];
+        let ikm_80: Vec<u8> = (0x00..=0x4f).collect();
+        let salt_13: Vec<u8> = (0x00..=0x0c).collect();
+        let salt_80: Vec<u8> = (0x60..=0xaf).collect();
+        let info_10: Vec<u8> = (0xf0..=0xf9).collect();
+        let info_80: Vec<u8> = (0xb0..=0xff).collect();
+        // (ikm, salt, info, prk, okm)
+        type Case<'a> = (&'a [u8], &'a [u8], &'a [u8], [u8; 32], &'a [u8]);
+        let cases: [Case; 3] = [
+            (
+                &ikm_22,
+                &salt_13,
+                &info_10,
+                [
+                    0x07, 0x77, 0x09, 0x36, 0x2c, 0x2e, 0x32, 0xdf, 0x0d, 0xdc, 0x3f, 0x0d, 0xc4,
+                    0x7b, 0xba, 0x63, 0x90, 0xb6, 0xc7, 0x3b, 0xb5, 0x0f, 0x9c, 0x31, 0x22, 0xec,
+                    0x84, 0x4a, 0xd7, 0xc2, 0xb3, 0xe5,
+                ],
+                &[
+                    0x3c, 0xb2, 0x5f, 0x25, 0xfa, 0xac, 0xd5, 0x7a, 0x90, 0x43, 0x4f, 0x64, 0xd0,
+                    0x36, 0x2f, 0x2a, 0x2d, 0x2d, 0x0a, 0x90, 0xcf, 0x1a, 0x5a, 0x4c, 0x5d, 0xb0,
+                    0x2d, 0x56, 0xec, 0xc4, 0xc5, 0xbf, 0x34, 0x00, 0x72, 0x08, 0xd5, 0xb8, 0x87,
+                    0x18, 0x58, 0x65,
+                ],
+            ),
+            (
+                &ikm_80,
+                &salt_80,
+                &info_80,
+                [
+                    0x06, 0xa6, 0xb8, 0x8c, 0x58, 0x53, 0x36, 0x1a, 0x06, 0x10, 0x4c, 0x9c, 0xeb,
+                    0x35, 0xb4, 0x5c, 0xef, 0x76, 0x00, 0x14, 0x90, 0x46, 0x71, 0x01, 0x4a, 0x19,
+                    0x3f, 0x40, 0xc1, 0x5f, 0xc2, 0x44,
+                ],
+                &[
+                    0xb1, 0x1e, 0x39, 0x8d, 0xc8, 0x03, 0x27, 0xa1, 0xc8, 0xe7, 0xf7, 0x8c, 0x59,
+                    0x6a, 0x49, 0x34, 0x4f, 0x01, 0x2e, 0xda, 0x2d, 0x4e, 0xfa, 0xd8, 0xa0, 0x50,
+                    0xcc, 0x4c, 0x19, 0xaf, 0xa9, 0x7c, 0x59, 0x04, 0x5a, 0x99, 0xca, 0xc7, 0x82,
+                    0x72, 0x71, 0xcb, 0x41, 0xc6, 0x5e, 0x59, 0x0e, 0x09, 0xda, 0x32, 0x75, 0x60,
+                    0x0c, 0x2f, 0x09, 0xb8, 0x36, 0x77, 0x93, 0xa9, 0xac, 0xa3, 0xdb, 0x71, 0xcc,
+                    0x30, 0xc5, 0x81, 0x79, 0xec, 0x3e, 0x87, 0xc1, 0x4c, 0x01, 0xd5, 0xc1, 0xf3,
+                    0x43, 0x4f, 0x1d, 0x87,
+                ],
+            ),
+            (
+                &ikm_22,
+                &[],
+                &[],
+                [
+                    0x19, 0xef, 0x24, 0xa3, 0x2c, 0x71, 0x7b, 0x16, 0x7f, 0x33, 0xa9, 0x1d, 0x6f,
+                    0x64, 0x8b, 0xdf, 0x96, 0x59, 0x67, 0x76, 0xaf, 0xdb, 0x63, 0x77, 0xac, 0x43,
+                    0x4c, 0x1c, 0x29, 0x3c, 0xcb, 0x04,
+                ],
+                &[
+                    0x8d, 0xa4, 0xe7, 0x75, 0xa5, 0x63, 0xc1, 0x8f, 0x71, 0x5f, 0x80, 0x2a, 0x06,
+                    0x3c, 0x5a, 0x31, 0xb8, 0xa1, 0x1f, 0x5c, 0x5e, 0xe1, 0x87, 0x9e, 0xc3, 0x45,
+                    0x4e, 0x5f, 0x3c, 0x73, 0x8d, 0x2d, 0x9d, 0x20, 0x13, 0x95, 0xfa, 0xa4, 0xb6,
+                    0x1a, 0x96, 0xc8,
+                ],
+            ),
+        ];
+
+        for (ikm, salt, info, expected_prk, expected_okm) in cases {
+            let mut prk = [0u8; 32];
+            let mut okm = vec![0u8; expected_okm.len()];
+            unsafe {
+                assert_eq!(
+                    crypto_hkdf_extract(
+                        SHA256_ALG_ID,
+                        salt.as_ptr(),
+                        salt.len(),
+                        ikm.as_ptr(),
+                        ikm.len(),
+                        prk.as_mut_ptr(),
+                        prk.len()
+                    ),
+                    CryptoResult::Success
+                );
+                assert_eq!(
+                    crypto_hkdf_expand(
+                        SHA256_ALG_ID,
+                        prk.as_ptr(),
+                        prk.len(),
+                        info.as_ptr(),
+                        info.len(),
+                        okm.as_mut_ptr(),
+                        okm.len()
+                    ),
+                    CryptoResult::Success
+                );
+            }
+            assert_eq!(prk, expected_prk);
+            assert_eq!(okm, expected_okm);
+        }
+    }
+
+    #[test]
+    fn test_hkdf_output_sizes() {
+        let prk = [0x0b; 32];
+        let mut okm = vec![0u8; 255 * 32 + 1];
+        unsafe {
+            // Largest allowed output for SHA3-256
+            assert_eq!(
+                crypto_hkdf_expand(
+                    SHA3_256_ALG_ID,
+                    prk.as_ptr(),
+                    prk.len(),
+                    [].as_ptr(),
+                    0,
+                    okm.as_mut_ptr(),
+                    255 * 32
+                ),
+                CryptoResult::Success
+            );
+            assert_eq!(
+                crypto_hkdf_expand(
+                    SHA3_256_ALG_ID,
+                    prk.as_ptr(),
+                    prk.len(),
+                    [].as_ptr(),
+                    0,
+                    okm.as_mut_ptr(),
+                    okm.len()
+                ),
+                CryptoResult::BadBufferOutputSize
+            );
+            // Extract output must match the digest size
+            assert_eq!(
+                crypto_hkdf_extract(
+                    SHA256_ALG_ID,
+                    [].as_ptr(),
+                    0,
+                    prk.as_ptr(),
+                    prk.len(),
+                    okm.as_mut_ptr(),
+                    31
+                ),
+                CryptoResult::BadBufferOutputSize
+            );
+            assert_eq!(
+                crypto_hkdf_extract(
+                    0xff,
+                    [].as_ptr(),
+                    0,
+                    prk.as_ptr(),
+                    prk.len(),
+                    okm.as_mut_ptr(),
+                    32
+                ),
+                CryptoResult::BadOrUnsupportedAlgorithm
+            );
+        }
+    }
+
+    /// Runs PBKDF2 through the C API and checks the derived key against `expected`.
+    fn check_pbkdf2(password: &[u8], salt: &[u8], iterations: u32, expected: &[u8]) {
+        let mut output = vec![0u8; expected.len()];
+        unsafe {
+            assert_eq!(
+                crypto_pbkdf2(
+                    SHA256_ALG_ID,
+                    password.as_ptr(),
+                    password.len(),
+                    salt.as_ptr(),
+                    salt.len(),
+                    iterations,
+                    output.as_mut_ptr(),
+                    output.len()
+                ),
+                CryptoResult::Success
+            );
+        }
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn test_pbkdf2_sha256_rfc6070() {
+        // RFC 6070 inputs with HMAC-SHA256 as PRF. The 16777216 iterations case is left out as
+        // it takes too long for a unit test.
+        check_pbkdf2(
+            b"password",
+            b"salt",
+            1,
+            &[
+                0x12, 0x0f, 0xb6, 0xcf, 0xfc, 0xf8, 0xb3, 0x2c, 0x43, 0xe7, 0x22, 0x52, 0x56, 0xc4,
+                0xf8, 0x37, 0xa8, 0x65, 0x48, 0xc9, 0x2c, 0xcc, 0x35, 0x48, 0x08, 0x05, 0x98, 0x7c,
+                0xb7, 0x0b, 0xe1, 0x7b,
+            ],
+        );
+        check_pbkdf2(
+            b"password",
+            b"salt",
+            2,
+            &[
+                0xae, 0x4d, 0x0c, 0x95, 0xaf, 0x6b, 0x46, 0xd3, 0x2d, 0x0a, 0xdf, 0xf9, 0x28, 0xf0,
+                0x6d, 0xd0, 0x2a, 0x30, 0x3f, 0x8e, 0xf3, 0xc2, 0x51, 0xdf, 0xd6, 0xe2, 0xd8, 0x5a,
+                0x95, 0x47, 0x4c, 0x43,
+            ],
+        );
+        check_pbkdf2(
+            b"password",
+            b"salt",
+            4096,
+            &[
+                0xc5, 0xe4, 0x78, 0xd5, 0x92, 0x88, 0xc8, 0x41, 0xaa, 0x53, 0x0d, 0xb6, 0x84, 0x5c,
+                0x4c, 0x8d, 0x96, 0x28, 0x93, 0xa0, 0x01, 0xce, 0x4e, 0x11, 0xa4, 0x96, 0x38, 0x73,
+                0xaa, 0x98, 0x13, 0x4a,
+            ],
+        );
+        check_pbkdf2(
+            b"passwordPASSWORDpassword",
+            b"saltSALTsaltSALTsaltSALTsaltSALTsalt",
+            4096,
+            &[
+                0x34, 0x8c, 0x89, 0xdb, 0xcb, 0xd3, 0x2b, 0x2f, 0x32, 0xd8, 0x14, 0xb8, 0x11, 0x6e,
+                0x84, 0xcf, 0x2b, 0x17, 0x34, 0x7e, 0xbc, 0x18, 0x00, 0x18, 0x1c, 0x4e, 0x2a, 0x1f,
+                0xb8, 0xdd, 0x53, 0xe1, 0xc6, 0x35, 0x51, 0x8c, 0x7d, 0xac, 0x47, 0xe9,
+            ],
+        );
+        check_pbkdf2(
+            b"pass\0word",
+            b"sa\0lt",
+            4096,
+            &[
+                0x89, 0xb6, 0x9d, 0x05, 0x16, 0xf8, 0x29, 0x89, 0x3c, 0x69, 0x62, 0x26, 0x65, 0x0a,
+                0x86, 0x87,
+            ],
+        );
+    }
+
+    #[test]
+    fn test_pbkdf2_sha256_rfc7914() {
+        // RFC 7914, section 11
+        check_pbkdf2(
+            b"passwd",
+            b"salt",
+            1,
+            &[
+                0x55, 0xac, 0x04, 0x6e, 0x56, 0xe3, 0x08, 0x9f, 0xec, 0x16, 0x91, 0xc2, 0x25, 0x44,
+                0xb6, 0x05, 0xf9, 0x41, 0x85, 0x21, 0x6d, 0xde, 0x04, 0x65, 0xe6, 0x8b, 0x9d, 0x57,
+                0xc2, 0x0d, 0xac, 0xbc, 0x49, 0xca, 0x9c, 0xcc, 0xf1, 0x79, 0xb6, 0x45, 0x99, 0x16,
+                0x64, 0xb3, 0x9d, 0x77, 0xef, 0x31, 0x7c, 0x71, 0xb8, 0x45, 0xb1, 0xe3, 0x0b, 0xd5,
+                0x09, 0x11, 0x20, 0x41, 0xd3, 0xa1, 0x97, 0x83,
+            ],
+        );
+        check_pbkdf2(
+            b"Password",
+            b"NaCl",
+            80000,
+            &[
+                0x4d, 0xdc, 0xd8, 0xf6, 0x0b, 0x98, 0xbe, 0x21, 0x83, 0x0c, 0xee, 0x5e, 0xf2, 0x27,
+                0x01, 0xf9, 0x64, 0x1a, 0x44, 0x18, 0xd0, 0x4c, 0x04, 0x14, 0xae, 0xff, 0x08, 0x87,
+                0x6b, 0x34, 0xab, 0x56, 0xa1, 0xd4, 0x25, 0xa1, 0x22, 0x58, 0x33, 0x54, 0x9a, 0xdb,
+                0x84, 0x1b, 0x51, 0xc9, 0xb3, 0x17, 0x6a, 0x27, 0x2b, 0xde, 0xbb, 0xa1, 0xd0, 0x78,
+                0x47, 0x8f, 0x62, 0xb3, 0x97, 0xf3, 0x3c, 0x8d,
+            ],
+        );
+    }
+
+    #[test]
+    fn test_pbkdf2_iteration_bounds() {
+        let mut output = [0u8; 32];
+        for iterations in [0, CRYPTO_PBKDF2_MAX_ITERATIONS + 1] {
+            unsafe {
+                assert_eq!(
+                    crypto_pbkdf2(
+                        SHA256_ALG_ID,
+                        b"password".as_ptr(),
+                        8,
+                        b"salt".as_ptr(),
+                        4,
+                        iterations,
+                        output.as_mut_ptr(),
+                        output.len()
+                    ),
+                    CryptoResult::BadParameter
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_shake_finalize() {
+        // FIPS 202 SHAKE128 and SHAKE256 of the empty message, at their default output sizes
+        let expected_128 = [
+            0x7f, 0x9c, 0x2b, 0xa4, 0xe8, 0x8f, 0x82, 0x7d, 0x61, 0x60, 0x45, 0x50, 0x76, 0x05,
+            0x85, 0x3e, 0xd7, 0x3b, 0x80, 0x93, 0xf6, 0xef, 0xbc, 0x88, 0xeb, 0x1a, 0x6e, 0xac,
+            0xfa, 0x66, 0xef, 0x26,
+        ];
+        let expected_256 = [
+            0x46, 0xb9, 0xdd, 0x2b, 0x0b, 0xa8, 0x8d, 0x13, 0x23, 0x3b, 0x3f, 0xeb, 0x74, 0x3e,
+            0xeb, 0x24, 0x3f, 0xcd, 0x52, 0xea, 0x62, 0xb8, 0x1b, 0x82, 0xb5, 0x0c, 0x27, 0x64,
+            0x6e, 0xd5, 0x76, 0x2f, 0xd7, 0x5d, 0xc4, 0xdd, 0xd8, 0xc0, 0xf2, 0x00, 0xcb, 0x05,
+            0x01, 0x9d, 0x67, 0xb5, 0x92, 0xf6, 0xfc, 0x82, 0x1c, 0x49, 0x47, 0x9a, 0xb4, 0x86,
+            0x40, 0x29, 0x2e, 0xac, 0xb3, 0xb7, 0xc4, 0xbe,
+        ];
+        let mut state = CryptoContext {
+            state: [0u64; CRYPTO_CONTEXT_STATE_SIZE],
+        };
+        let mut digest_128 = [0u8; 32];
+        let mut digest_256 = [0u8; 64];
+        unsafe {
+            assert_eq!(
+                crypto_init(&mut state, SHAKE128_ALG_ID),
+                CryptoResult::Success
+            );
+            assert_eq!(
+                crypto_finalize(&mut state, digest_128.as_mut_ptr(), digest_128.len()),
+                CryptoResult::Success
+            );
+            assert_eq!(
+                crypto_init(&mut state, SHAKE256_ALG_ID),
+                CryptoResult::Success
+            );
+            assert_eq!(
+                crypto_finalize(&mut state, digest_256.as_mut_ptr(), 32),
+                CryptoResult::BadBufferOutputSize
+            );
+            assert_eq!(
+                crypto_finalize(&mut state, digest_256.as_mut_ptr(), digest_256.len()),
+                CryptoResult::Success
+            );
+        }
+        assert_eq!(digest_128, expected_128);
+        assert_eq!(digest_256, expected_256);
+    }
+
+    #[test]
+    fn test_shake128_squeeze() {
+        // SHAKE128 of "hello", read in pieces that cross the 168 byte rate boundary
+        let expected = [
+            0x8e, 0xb4, 0xb6, 0xa9, 0x32, 0xf2, 0x80, 0x33, 0x5e, 0xe1, 0xa2, 0x79, 0xf8, 0xc2,
+            0x08, 0xa3, 0x49, 0xe7, 0xbc, 0x65, 0xda, 0xf8, 0x31, 0xd3, 0x02, 0x1c, 0x21, 0x38,
+            0x25, 0x29, 0x24, 0x63, 0xc5, 0x9e, 0x22, 0xd0, 0xfe, 0x2c, 0x76, 0x7c, 0xd7, 0xca,
+            0xcc, 0x4d, 0xf4, 0x2d, 0xd5, 0xf6, 0x14, 0x7f, 0x0c, 0x5c, 0x51, 0x2e, 0xcb, 0x9b,
+            0x93, 0x3d, 0x14, 0xb9, 0xcc, 0x1b, 0x29, 0x74, 0x6f, 0x28, 0xe3, 0x47, 0xe8, 0x99,
+            0xed, 0x9a, 0x1b, 0x48, 0x72, 0xf7, 0xba, 0xff, 0x92, 0xa3, 0x0f, 0xff, 0x41, 0x84,
+            0xd5, 0xdd, 0xc7, 0x05, 0x2e, 0xa0, 0xbd, 0x2b, 0x41, 0x3d, 0xdb, 0x8f, 0xd6, 0x66,
+            0x73, 0x92, 0x63, 0x22, 0xc0, 0x56, 0xae, 0x6b, 0x92, 0x3f, 0x7b, 0x9b, 0xeb, 0x6f,
+            0x60, 0x62, 0x60, 0x73, 0x57, 0xe0, 0x75, 0x00, 0xcd, 0xa1, 0x8f, 0x5b, 0x7c, 0xaf,
+            0x6d, 0xb1, 0x87, 0x6a, 0x0d, 0xc1, 0x29, 0x10, 0xda, 0x08, 0x36, 0x1f, 0xfe, 0xb8,
+            0x01, 0x7c, 0xaf, 0x8d, 0x69, 0xde, 0xb7, 0x36, 0xfe, 0x3c, 0x7b, 0x25, 0xf5, 0x22,
+            0xc7, 0xc9, 0x28, 0x7c, 0x9f, 0x2f, 0xe9, 0x71, 0xd2, 0xa1, 0xef, 0x44, 0x73, 0x82,
+            0xd4, 0x63, 0xfd, 0x72, 0xc2, 0xaa, 0x7a, 0xb9, 0x19, 0x5d, 0x7b, 0x85, 0xec, 0x6b,
+            0x1e, 0x76, 0x46, 0xd5, 0xd1, 0x53, 0x4a, 0xc4, 0xf4, 0x5c, 0x4c, 0x30, 0x3b, 0x84,
+            0x49, 0xd9, 0x3f, 0x98,
+        ];
+        let mut state = CryptoContext {
+            state: [0u64; CRYPTO_CONTEXT_STATE_SIZE],
+        };
+        let input = b"hello";
+        let mut output = [0u8; 200];
+        unsafe {
+            assert_eq!(
+                crypto_init(&mut state, SHAKE128_ALG_ID),
+                CryptoResult::Success
+            );
+            assert_eq!(
+                crypto_update(&mut state, input.as_ptr(), input.len()),
+                CryptoResult::Success
+            );
+            for chunk in output.chunks_mut(67) {
+                assert_eq!(
+                    crypto_squeeze(&mut state, chunk.as_mut_ptr(), chunk.len()),
+                    CryptoResult::Success
+                );
+            }
+        }
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn test_shake256_squeeze() {
+        // SHAKE256 of "hello", read one byte and then the rest
+        let expected = [
+            0x12, 0x34, 0x07, 0x5a, 0xe4, 0xa1, 0xe7, 0x73, 0x16, 0xcf, 0x2d, 0x80, 0x00, 0x97,
+            0x45, 0x81, 0xa3, 0x43, 0xb9, 0xeb, 0xbc, 0xa7, 0xe3, 0xd1, 0xdb, 0x83, 0x39, 0x4c,
+            0x30, 0xf2, 0x21, 0x62, 0x6f, 0x59, 0x4e, 0x4f, 0x0d, 0xe6, 0x39, 0x02, 0x34, 0x9a,
+            0x5e, 0xa5, 0x78, 0x12, 0x13, 0x21, 0x58, 0x13, 0x91, 0x9f, 0x92, 0xa4, 0xd8, 0x6d,
+            0x12, 0x74, 0x66, 0xe3, 0xd0, 0x7e, 0x8b, 0xe3, 0x8c, 0xe9, 0xf4, 0x57, 0xbf, 0x32,
+            0xe6, 0xb2, 0x6c, 0xf2, 0x79, 0x86, 0xb8, 0x61, 0xdd, 0x84, 0x0d, 0x16, 0x84, 0xf7,
+            0x94, 0x77, 0x97, 0x4c, 0xf3, 0x75, 0x71, 0x70, 0x66, 0x8f, 0xc6, 0xd6, 0x4d, 0x01,
+            0x87, 0xbd,
+        ];
+        let mut state = CryptoContext {
+            state: [0u64; CRYPTO_CONTEXT_STATE_SIZE],
+        };
+        let input = b"hello";
+        let mut output = [0u8; 100];
+        unsafe {
+            assert_eq!(
+                crypto_init(&mut state, SHAKE256_ALG_ID),
+                CryptoResult::Success
+            );
+            assert_eq!(
+                crypto_update(&mut state, input.as_ptr(), input.len()),
+                CryptoResult::Success
+            );
+            let (first, rest) = output.split_at_mut(1);
+            assert_eq!(
+                crypto_squeeze(&mut state, first.as_mut_ptr(), first.len()),
+                CryptoResult::Success
+            );
+            assert_eq!(
+                crypto_squeeze(&mut state, rest.as_mut_ptr(), rest.len()),
+                CryptoResult::Success
+            );
+        }
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn test_squeeze_phase_errors() {
+        let mut state = CryptoContext {
+            state: [0u64; CRYPTO_CONTEXT_STATE_SIZE],
+        };
+        let input = b"hello";
+        let mut output = [0u8; 32];
+        unsafe {
+            // Fixed-output algorithms cannot be squeezed
+            assert_eq!(
+                crypto_init(&mut state, SHA256_ALG_ID),
+                CryptoResult::Success
+            );
+            assert_eq!(
+                crypto_squeeze(&mut state, output.as_mut_ptr(), output.len()),
+                CryptoResult::BadOrUnsupportedAlgorithm
+            );
+
+            // A squeezing context rejects update and finalize
+            assert_eq!(
+                crypto_init(&mut state, SHAKE128_ALG_ID),
+                CryptoResult::Success
+            );
+            assert_eq!(
+                crypto_squeeze(&mut state, output.as_mut_ptr(), output.len()),
+                CryptoResult::Success
+            );
+            assert_eq!(
+                crypto_update(&mut state, input.as_ptr(), input.len()),
+                CryptoResult::UninitializedOrCorruptedContext
+            );
+            assert_eq!(
+                crypto_finalize(&mut state, output.as_mut_ptr(), output.len()),
+                CryptoResult::UninitializedOrCorruptedContext
+            );
+        }
+    }
+
+    /// Runs an initialized context over `input` and checks the digest against `expected`.
+    unsafe fn check_digest(ctx: *mut CryptoContext, input: &[u8], expected: &[u8]) {
+        let mut digest = vec![0u8; expected.len()];
+        assert_eq!(
+            crypto_update(ctx, input.as_ptr(), input.len()),
+            CryptoResult::Success
+        );
+        assert_eq!(
+            crypto_finalize(ctx, digest.as_mut_ptr(), digest.len()),
+            CryptoResult::Success
+        );
+        assert_eq!(digest, expected);
+    }
+
+    #[cfg(feature = "blake2")]
+    #[test]
+    fn test_blake2() {
+        // RFC 7693 appendix A/B examples, BLAKE2b-512 and BLAKE2s-256 of "abc"
+        let blake2b_abc = [
+            0xba, 0x80, 0xa5, 0x3f, 0x98, 0x1c, 0x4d, 0x0d, 0x6a, 0x27, 0x97, 0xb6, 0x9f, 0x12,
+            0xf6, 0xe9, 0x4c, 0x21, 0x2f, 0x14, 0x68, 0x5a, 0xc4, 0xb7, 0x4b, 0x12, 0xbb, 0x6f,
+            0xdb, 0xff, 0xa2, 0xd1, 0x7d, 0x87, 0xc5, 0x39, 0x2a, 0xab, 0x79, 0x2d, 0xc2, 0x52,
+            0xd5, 0xde, 0x45, 0x33, 0xcc, 0x95, 0x18, 0xd3, 0x8a, 0xa8, 0xdb, 0xf1, 0x92, 0x5a,
+            0xb9, 0x23, 0x86, 0xed, 0xd4, 0x00, 0x99, 0x23,
+        ];
+        let blake2s_abc = [
+            0x50, 0x8c, 0x5e, 0x8c, 0x32, 0x7c, 0x14, 0xe2, 0xe1, 0xa7, 0x2b, 0xa3, 0x4e, 0xeb,
+            0x45, 0x2f, 0x37, 0x45, 0x8b, 0x20, 0x9e, 0xd6, 0x3a, 0x29, 0x4d, 0x99, 0x9b, 0x4c,
+            0x86, 0x67, 0x59, 0x82,
+        ];
+        // Keyed KAT from the BLAKE2 reference repository, key 00..3f (00..1f for BLAKE2s) and
+        // input 00 01 02
+        let key: Vec<u8> = (0x00..=0x3f).collect();
+        let blake2b_keyed = [
+            0x33, 0xd0, 0x82, 0x5d, 0xdd, 0xf7, 0xad, 0xa9, 0x9b, 0x0e, 0x7e, 0x30, 0x71, 0x04,
+            0xad, 0x07, 0xca, 0x9c, 0xfd, 0x96, 0x92, 0x21, 0x4f, 0x15, 0x61, 0x35, 0x63, 0x15,
+            0xe7, 0x84, 0xf3, 0xe5, 0xa1, 0x7e, 0x36, 0x4a, 0xe9, 0xdb, 0xb1, 0x4c, 0xb2, 0x03,
+            0x6d, 0xf9, 0x32, 0xb7, 0x7f, 0x4b, 0x29, 0x27, 0x61, 0x36, 0x5f, 0xb3, 0x28, 0xde,
+            0x7a, 0xfd, 0xc6, 0xd8, 0x99, 0x8f, 0x5f, 0xc1,
+        ];
+        let blake2s_keyed = [
+            0x1d, 0x22, 0x0d, 0xbe, 0x2e, 0xe1, 0x34, 0x66, 0x1f, 0xdf, 0x6d, 0x9e, 0x74, 0xb4,
+            0x17, 0x04, 0x71, 0x05, 0x56, 0xf2, 0xf6, 0xe5, 0xa0, 0x91, 0xb2, 0x27, 0x69, 0x74,
+            0x45, 0xdb, 0xea, 0x6b,
+        ];
+        let mut state = CryptoContext {
+            state: [0u64; CRYPTO_CONTEXT_STATE_SIZE],
+        };
+        unsafe {
+            assert_eq!(
+                crypto_init(&mut state, BLAKE2B_512_ALG_ID),
+                CryptoResult::Success
+            );
+            check_digest(&mut state, b"abc", &blake2b_abc);
+            assert_eq!(
+                crypto_init(&mut state, BLAKE2S_256_ALG_ID),
+                CryptoResult::Success
+            );
+            check_digest(&mut state, b"abc", &blake2s_abc);
+            assert_eq!(
+                crypto_keyed_init(&mut state, BLAKE2B_512_ALG_ID, key.as_ptr(), 64),
+                CryptoResult::Success
+            );
+            check_digest(&mut state, &[0x00, 0x01, 0x02], &blake2b_keyed);
+            assert_eq!(
+                crypto_keyed_init(&mut state, BLAKE2S_256_ALG_ID, key.as_ptr(), 32),
+                CryptoResult::Success
+            );
+            check_digest(&mut state, &[0x00, 0x01, 0x02], &blake2s_keyed);
+
+            // Keys longer than the digest are rejected
+            assert_eq!(
+                crypto_keyed_init(&mut state, BLAKE2S_256_ALG_ID, key.as_ptr(), 33),
+                CryptoResult::BadParameter
+            );
+            assert_eq!(
+                crypto_keyed_init(&mut state, SHA256_ALG_ID, key.as_ptr(), 32),
+                CryptoResult::BadOrUnsupportedAlgorithm
+            );
+        }
+    }
+
+    #[cfg(feature = "blake3")]
+    #[test]
+    fn test_blake3() {
+        // Official BLAKE3 test vectors for the empty input
+        let key = b"whats the Elvish word for friend";
+        let context = b"BLAKE3 2019-12-27 16:29:52 test vectors context";
+        let hash = [
+            0xaf, 0x13, 0x49, 0xb9, 0xf5, 0xf9, 0xa1, 0xa6, 0xa0, 0x40, 0x4d, 0xea, 0x36, 0xdc,
+            0xc9, 0x49, 0x9b, 0xcb, 0x25, 0xc9, 0xad, 0xc1, 0x12, 0xb7, 0xcc, 0x9a, 0x93, 0xca,
+            0xe4, 0x1f, 0x32, 0x62,
+        ];
+        let keyed_hash = [
+            0x92, 0xb2, 0xb7, 0x56, 0x04, 0xed, 0x3c, 0x76, 0x1f, 0x9d, 0x6f, 0x62, 0x39, 0x2c,
+            0x8a, 0x92, 0x27, 0xad, 0x0e, 0xa3, 0xf0, 0x95, 0x73, 0xe7, 0x83, 0xf1, 0x49, 0x8a,
+            0x4e, 0xd6, 0x0d, 0x26,
+        ];
+        let derive_key = [
+            0x2c, 0xc3, 0x97, 0x83, 0xc2, 0x23, 0x15, 0x4f, 0xea, 0x8d, 0xfb, 0x7c, 0x1b, 0x16,
+            0x60, 0xf2, 0xac, 0x2d, 0xcb, 0xd1, 0xc1, 0xde, 0x82, 0x77, 0xb0, 0xb0, 0xdd, 0x39,
+            0xb7, 0xe5, 0x0d, 0x7d,
+        ];
+        let mut state = CryptoContext {
+            state: [0u64; CRYPTO_CONTEXT_STATE_SIZE],
+        };
+        unsafe {
+            assert_eq!(
+                crypto_init(&mut state, BLAKE3_ALG_ID),
+                CryptoResult::Success
+            );
+            check_digest(&mut state, &[], &hash);
+            assert_eq!(
+                crypto_keyed_init(&mut state, BLAKE3_ALG_ID, key.as_ptr(), key.len()),
+                CryptoResult::Success
+            );
+            check_digest(&mut state, &[], &keyed_hash);
+            assert_eq!(
+                crypto_derive_key_init(&mut state, context.as_ptr(), context.len()),
+                CryptoResult::Success
+            );
+            check_digest(&mut state, &[], &derive_key);
+
+            // Keys must be exactly 32 bytes and context strings valid UTF-8
+            assert_eq!(
+                crypto_keyed_init(&mut state, BLAKE3_ALG_ID, key.as_ptr(), 31),
+                CryptoResult::BadParameter
+            );
+            assert_eq!(
+                crypto_derive_key_init(&mut state, [0xff].as_ptr(), 1),
+                CryptoResult::BadParameter
+            );
+        }
+    }
+
+    #[cfg(feature = "blake3")]
+    #[test]
+    fn test_blake3_squeeze() {
+        // Extended output of the official BLAKE3 test vector for the empty input
+        let expected = [
+            0xaf, 0x13, 0x49, 0xb9, 0xf5, 0xf9, 0xa1, 0xa6, 0xa0, 0x40, 0x4d, 0xea, 0x36, 0xdc,
+            0xc9, 0x49, 0x9b, 0xcb, 0x25, 0xc9, 0xad, 0xc1, 0x12, 0xb7, 0xcc, 0x9a, 0x93, 0xca,
+            0xe4, 0x1f, 0x32, 0x62, 0xe0, 0x0f, 0x03, 0xe7, 0xb6, 0x9a, 0xf2, 0x6b, 0x7f, 0xaa,
+            0xf0, 0x9f, 0xcd, 0x33, 0x30, 0x50, 0x33, 0x8d, 0xdf, 0xe0, 0x85, 0xb8, 0xcc, 0x86,
+            0x9c, 0xa9, 0x8b, 0x20, 0x6c, 0x08, 0x24, 0x3a,
+        ];
+        let mut state = CryptoContext {
+            state: [0u64; CRYPTO_CONTEXT_STATE_SIZE],
+        };
+        let mut output = [0u8; 64];
+        unsafe {
+            assert_eq!(
+                crypto_init(&mut state, BLAKE3_ALG_ID),
+                CryptoResult::Success
+            );
+            for chunk in output.chunks_mut(24) {
+                assert_eq!(
+                    crypto_squeeze(&mut state, chunk.as_mut_ptr(), chunk.len()),
+                    CryptoResult::Success
+                );
+            }
+        }
+        assert_eq!(output, expected);
+    }
+
+    #[cfg(feature = "sha224")]
+    #[test]
+    fn test_sha224() {
+        // FIPS 180-4 example, SHA-224 of "abc"
+        let expected = [
+            0x23, 0x09, 0x7d, 0x22, 0x34, 0x05, 0xd8, 0x22, 0x86, 0x42, 0xa4, 0x77, 0xbd, 0xa2,
+            0x55, 0xb3, 0x2a, 0xad, 0xbc, 0xe4, 0xbd, 0xa0, 0xb3, 0xf7, 0xe3, 0x6c, 0x9d, 0xa7,
+        ];
+        let mut state = CryptoContext {
+            state: [0u64; CRYPTO_CONTEXT_STATE_SIZE],
+        };
+        unsafe {
+            assert_eq!(
+                crypto_init(&mut state, SHA224_ALG_ID),
+                CryptoResult::Success
+            );
+            check_digest(&mut state, b"abc", &expected);
+        }
+
+        // RFC 4231 test case 2
+        let mut mac = [0u8; 28];
+        hmac(
+            SHA224_ALG_ID,
+            b"Jefe",
+            b"what do ya want for nothing?",
+            &mut mac,
+        );
+        assert_eq!(
+            mac,
+            [
+                0xa3, 0x0e, 0x01, 0x09, 0x8b, 0xc6, 0xdb, 0xbf, 0x45, 0x69, 0x0f, 0x3a, 0x7e, 0x9e,
+                0x6d, 0x0f, 0x8b, 0xbe, 0xa2, 0xa3, 0x9e, 0x61, 0x48, 0x00, 0x8f, 0xd0, 0x5e, 0x44
+            ]
+        );
+    }
+
+    #[cfg(feature = "sha384")]
+    #[test]
+    fn test_sha384() {
+        // FIPS 180-4 example, SHA-384 of "abc"
+        let expected = [
+            0xcb, 0x00, 0x75, 0x3f, 0x45, 0xa3, 0x5e, 0x8b, 0xb5, 0xa0, 0x3d, 0x69, 0x9a, 0xc6,
+            0x50, 0x07, 0x27, 0x2c, 0x32, 0xab, 0x0e, 0xde, 0xd1, 0x63, 0x1a, 0x8b, 0x60, 0x5a,
+            0x43, 0xff, 0x5b, 0xed, 0x80, 0x86, 0x07, 0x2b, 0xa1, 0xe7, 0xcc, 0x23, 0x58, 0xba,
+            0xec, 0xa1, 0x34, 0xc8, 0x25, 0xa7,
+        ];
+        let mut state = CryptoContext {
+            state: [0u64; CRYPTO_CONTEXT_STATE_SIZE],
+        };
+        unsafe {
+            assert_eq!(
+                crypto_init(&mut state, SHA384_ALG_ID),
+                CryptoResult::Success
+            );
+            check_digest(&mut state, b"abc", &expected);
+        }
+
+        // RFC 4231 test case 2
+        let mut mac = [0u8; 48];
+        hmac(
+            SHA384_ALG_ID,
+            b"Jefe",
+            b"what do ya want for nothing?",
+            &mut mac,
+        );
+        assert_eq!(
+            mac,
+            [
+                0xaf, 0x45, 0xd2, 0xe3, 0x76, 0x48, 0x40, 0x31, 0x61, 0x7f, 0x78, 0xd2, 0xb5, 0x8a,
+                0x6b, 0x1b, 0x9c, 0x7e, 0xf4, 0x64, 0xf5, 0xa0, 0x1b, 0x47, 0xe4, 0x2e, 0xc3, 0x73,
+                0x63, 0x22, 0x44, 0x5e, 0x8e, 0x22, 0x40, 0xca, 0x5e, 0x69, 0xe2, 0xc7, 0x8b, 0x32,
+                0x39, 0xec, 0xfa, 0xb2, 0x16, 0x49
+            ]
+        );
+    }
+
+    #[cfg(feature = "sha512")]
+    #[test]
+    fn test_sha512() {
+        // FIPS 180-4 example, SHA-512 of "abc"
+        let expected = [
+            0xdd, 0xaf, 0x35, 0xa1, 0x93, 0x61, 0x7a, 0xba, 0xcc, 0x41, 0x73, 0x49, 0xae, 0x20,
+            0x41, 0x31, 0x12, 0xe6, 0xfa, 0x4e, 0x89, 0xa9, 0x7e, 0xa2, 0x0a, 0x9e, 0xee, 0xe6,
+            0x4b, 0x55, 0xd3, 0x9a, 0x21, 0x92, 0x99, 0x2a, 0x27, 0x4f, 0xc1, 0xa8, 0x36, 0xba,
+            0x3c, 0x23, 0xa3, 0xfe, 0xeb, 0xbd, 0x45, 0x4d, 0x44, 0x23, 0x64, 0x3c, 0xe8, 0x0e,
+            0x2a, 0x9a, 0xc9, 0x4f, 0xa5, 0x4c, 0xa4, 0x9f,
+        ];
+        let mut state = CryptoContext {
+            state: [0u64; CRYPTO_CONTEXT_STATE_SIZE],
+        };
+        unsafe {
+            assert_eq!(
+                crypto_init(&mut state, SHA512_ALG_ID),
+                CryptoResult::Success
+            );
+            check_digest(&mut state, b"abc", &expected);
+        }
+
+        // RFC 4231 test case 2
+        let mut mac = [0u8; 64];
+        hmac(
+            SHA512_ALG_ID,
+            b"Jefe",
+            b"what do ya want for nothing?",
+            &mut mac,
+        );
+        assert_eq!(
+            mac,
+            [
+                0x16, 0x4b, 0x7a, 0x7b, 0xfc, 0xf8, 0x19, 0xe2, 0xe3, 0x95, 0xfb, 0xe7, 0x3b, 0x56,
+                0xe0, 0xa3, 0x87, 0xbd, 0x64, 0x22, 0x2e, 0x83, 0x1f, 0xd6, 0x10, 0x27, 0x0c, 0xd7,
+                0xea, 0x25, 0x05, 0x54, 0x97, 0x58, 0xbf, 0x75, 0xc0, 0x5a, 0x99, 0x4a, 0x6d, 0x03,
+                0x4f, 0x65, 0xf8, 0xf0, 0xe6, 0xfd, 0xca, 0xea, 0xb1, 0xa3, 0x4d, 0x4a, 0x6b, 0x4b,
+                0x63, 0x6e, 0x07, 0x0a, 0x38, 0xbc, 0xe7, 0x37
+            ]
+        );
+    }
+
+    #[cfg(feature = "sha512_224")]
+    #[test]
+    fn test_sha512_224() {
+        // FIPS 180-4 example, SHA-512/224 of "abc"
+        let expected = [
+            0x46, 0x34, 0x27, 0x0f, 0x70, 0x7b, 0x6a, 0x54, 0xda, 0xae, 0x75, 0x30, 0x46, 0x08,
+            0x42, 0xe2, 0x0e, 0x37, 0xed, 0x26, 0x5c, 0xee, 0xe9, 0xa4, 0x3e, 0x89, 0x24, 0xaa,
+        ];
+        let mut state = CryptoContext {
+            state: [0u64; CRYPTO_CONTEXT_STATE_SIZE],
+        };
+        unsafe {
+            assert_eq!(
+                crypto_init(&mut state, SHA512_224_ALG_ID),
+                CryptoResult::Success
+            );
+            check_digest(&mut state, b"abc", &expected);
+        }
+    }
+
+    #[cfg(feature = "sha512_256")]
+    #[test]
+    fn test_sha512_256() {
+        // FIPS 180-4 example, SHA-512/256 of "abc"
+        let expected = [
+            0x53, 0x04, 0x8e, 0x26, 0x81, 0x94, 0x1e, 0xf9, 0x9b, 0x2e, 0x29, 0xb7, 0x6b, 0x4c,
+            0x7d, 0xab, 0xe4, 0xc2, 0xd0, 0xc6, 0x34, 0xfc, 0x6d, 0x46, 0xe0, 0xe2, 0xf1, 0x31,
+            0x07, 0xe7, 0xaf, 0x23,
+        ];
+        let mut state = CryptoContext {
+            state: [0u64; CRYPTO_CONTEXT_STATE_SIZE],
+        };
+        unsafe {
+            assert_eq!(
+                crypto_init(&mut state, SHA512_256_ALG_ID),
+                CryptoResult::Success
+            );
+            check_digest(&mut state, b"abc", &expected);
+        }
+    }
+
+    #[test]
+    fn test_hash_one_shot() {
+        // Same expected output as test_sha256
+        let expected: [u8; 32] = [
+            0x2c, 0xf2, 0x4d, 0xba, 0x5f, 0xb0, 0xa3, 0x0e, 0x26, 0xe8, 0x3b, 0x2a, 0xc5, 0xb9,
+            0xe2, 0x9e, 0x1b, 0x16, 0x1e, 0x5c, 0x1f, 0xa7, 0x42, 0x5e, 0x73, 0x04, 0x33, 0x62,
+            0x93, 0x8b, 0x98, 0x24,
+        ];
+        let input = b"hello";
+        let mut digest = [0u8; 32];
+        unsafe {
+            assert_eq!(
+                crypto_hash(
+                    SHA256_ALG_ID,
+                    input.as_ptr(),
+                    input.len(),
+                    digest.as_mut_ptr(),
+                    digest.len()
+                ),
+                CryptoResult::Success
+            );
+            assert_eq!(digest, expected);
+
+            // Scatter/gather input hashes the concatenation of the buffers
+            digest = [0u8; 32];
+            let buffers = [
+                CryptoBuffer {
+                    data: input.as_ptr(),
+                    length: 2,
+                },
+                CryptoBuffer {
+                    data: input.as_ptr(),
+                    length: 0,
+                },
+                CryptoBuffer {
+                    data: input[2..].as_ptr(),
+                    length: 3,
+                },
+            ];
+            assert_eq!(
+                crypto_hash_multi(
+                    SHA256_ALG_ID,
+                    buffers.as_ptr(),
+                    buffers.len(),
+                    digest.as_mut_ptr(),
+                    digest.len()
+                ),
+                CryptoResult::Success
+            );
+            assert_eq!(digest, expected);
+        }
+    }
+
+    #[test]
+    fn test_hash_one_shot_errors() {
+        let input = b"hello";
+        let mut digest = [0u8; 32];
+        let buffers = [CryptoBuffer {
+            data: ptr::null(),
+            length: 0,
+        }];
+        unsafe {
+            assert_eq!(
+                crypto_hash(
+                    SHA256_ALG_ID,
+                    input.as_ptr(),
+                    input.len(),
+                    digest.as_mut_ptr(),
+                    31
+                ),
+                CryptoResult::BadBufferOutputSize
+            );
+            assert_eq!(
+                crypto_hash(
+                    0xff,
+                    input.as_ptr(),
+                    input.len(),
+                    digest.as_mut_ptr(),
+                    digest.len()
+                ),
+                CryptoResult::BadOrUnsupportedAlgorithm
+            );
+            assert_eq!(
+                crypto_hash_multi(
+                    SHA256_ALG_ID,
+                    buffers.as_ptr(),
+                    buffers.len(),
+                    digest.as_mut_ptr(),
+                    digest.len()
+                ),
+                CryptoResult::PointerCannotBeNull
+            );
+        }
+    }
+
+    #[test]
+    fn test_algorithm_capabilities() {
+        assert!(crypto_algorithm_supported(SHA256_ALG_ID));
+        assert!(!crypto_algorithm_supported(0xff));
+        assert_eq!(crypto_output_size(SHA256_ALG_ID), 32);
+        assert_eq!(crypto_block_size(SHA256_ALG_ID), 64);
+        assert_eq!(crypto_output_size(SHA3_256_ALG_ID), 32);
+        assert_eq!(crypto_block_size(SHA3_256_ALG_ID), 136);
+        assert_eq!(crypto_output_size(SHAKE128_ALG_ID), 32);
+        assert_eq!(crypto_block_size(SHAKE128_ALG_ID), 168);
+        assert_eq!(crypto_output_size(0xff), 0);
+        assert_eq!(crypto_block_size(0xff), 0);
+        let name = unsafe { CStr::from_ptr(crypto_algorithm_name(SHA3_256_ALG_ID)) };
+        assert_eq!(name.to_str(), Ok("SHA3-256"));
+        assert!(crypto_algorithm_name(0xff).is_null());
+    }
+
+    #[test]
+    fn test_algorithm_enumeration() {
+        let count = crypto_algorithm_count();
+        let mut previous = None;
+        let mut id = 0u32;
+        for index in 0..count {
+            unsafe {
+                assert_eq!(crypto_algorithm_id(index, &mut id), CryptoResult::Success);
+            }
+            assert!(previous < Some(id));
+            previous = Some(id);
+            // Every enumerated algorithm must be initializable and agree with its output size.
+            let mut state = CryptoContext {
+                state: [0u64; CRYPTO_CONTEXT_STATE_SIZE],
+            };
+            let mut digest = vec![0u8; crypto_output_size(id)];
+            unsafe {
+                assert_eq!(crypto_init(&mut state, id), CryptoResult::Success);
+                assert_eq!(
+                    crypto_finalize(&mut state, digest.as_mut_ptr(), digest.len()),
+                    CryptoResult::Success
+                );
+            }
+        }
+        assert_eq!(previous, Some(id));
+        unsafe {
+            assert_eq!(
+                crypto_algorithm_id(count, &mut id),
+                CryptoResult::BadParameter
+            );
+            assert_eq!(
+                crypto_algorithm_id(0, ptr::null_mut()),
+                CryptoResult::PointerCannotBeNull
+            );
+        }
+    }
+
+    #[test]
+    fn test_api_version() {
+        let version = unsafe { CStr::from_ptr(crypto_api_version()) };
+        assert_eq!(version.to_str(), Ok(env!("CARGO_PKG_VERSION")));
+    }
+
+    #[test]
+    fn test_result_str() {
+        let description = |result| unsafe { CStr::from_ptr(crypto_result_str(result)) };
+        assert_eq!(description(CryptoResult::Success).to_str(), Ok("success"));
+        assert_eq!(
+            description(CryptoResult::BadBufferOutputSize).to_str(),
+            Ok("bad output buffer size")
+        );
+        assert_eq!(
+            description(CryptoResult::UninitializedOrCorruptedContext).to_str(),
+            Ok("context is uninitialized or corrupted")
+        );
+    }
+
+    #[test]
+    fn test_last_error_detail() {
+        let detail = || {
+            let detail = crypto_last_error_detail();
+            (!detail.is_null()).then(|| unsafe { CStr::from_ptr(detail) }.to_str().unwrap())
+        };
+        let mut state = CryptoContext {
+            state: [0u64; CRYPTO_CONTEXT_STATE_SIZE],
+        };
+        let mut digest = [0u8; 64];
+        unsafe {
+            assert_eq!(
+                crypto_update(&mut state, digest.as_ptr(), 0),
+                CryptoResult::UninitializedOrCorruptedContext
+            );
+            assert_eq!(
+                detail(),
+                Some("context was not initialized by an init function")
+            );
+            assert_eq!(
+                crypto_init(&mut state, 0xff),
+                CryptoResult::BadOrUnsupportedAlgorithm
+            );
+            assert_eq!(detail(), Some("algorithm ID 255 is not supported"));
+            assert_eq!(
+                crypto_init(&mut state, SHA256_ALG_ID),
+                CryptoResult::Success
+            );
+            assert_eq!(detail(), None);
+            assert_eq!(
+                crypto_finalize(&mut state, digest.as_mut_ptr(), digest.len()),
+                CryptoResult::BadBufferOutputSize
+            );
+            assert_eq!(
+                detail(),
+                Some("output buffer is 64 bytes, expected 32 bytes")
+            );
+            // The detail belongs to the calling thread only.
+            std::thread::spawn(move || assert_eq!(detail(), None))
+                .join()
+                .unwrap();
+            assert_eq!(
+                crypto_finalize(&mut state, ptr::null_mut(), 32),
+                CryptoResult::PointerCannotBeNull
+            );
+            assert_eq!(detail(), None);
+        }
+    }
+
+    #[test]
+    fn test_destroy() {
+        let key = [0x0bu8; 20];
+        let mut state = CryptoContext {
+            state: [0u64; CRYPTO_CONTEXT_STATE_SIZE],
+        };
+        let mut mac = [0u8; 32];
+        unsafe {
+            assert_eq!(
+                crypto_hmac_init(&mut state, SHA256_ALG_ID, key.as_ptr(), key.len()),
+                CryptoResult::Success
+            );
+            assert_eq!(
+                crypto_finalize(&mut state, mac.as_mut_ptr(), mac.len()),
+                CryptoResult::Success
+            );
+            assert_eq!(crypto_destroy(&mut state), CryptoResult::Success);
+            assert!(state.state.iter().all(|&word| word == 0));
+            assert_eq!(
+                crypto_update(&mut state, key.as_ptr(), key.len()),
+                CryptoResult::UninitializedOrCorruptedContext
+            );
+            assert_eq!(
+                crypto_destroy(ptr::null_mut()),
+                CryptoResult::PointerCannotBeNull
+            );
+        }
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn test_zeroize_on_drop() {
+        let mut context =
+            mem::MaybeUninit::new(Hasher::new_hmac(Algorithm::Sha256, b"key").unwrap());
+        unsafe {
+            ptr::drop_in_place(context.as_mut_ptr());
+            let bytes =
+                std::slice::from_raw_parts(context.as_ptr().cast::<u8>(), mem::size_of::<Hasher>());
+            assert!(bytes.iter().all(|&byte| byte == 0));
+        }
+    }
+
+    #[test]
+    fn test_handle() {
+        let input = b"hello";
+        let mut digest = [0u8; 32];
+        let expected = [
+            0x33, 0x38, 0xbe, 0x69, 0x4f, 0x50, 0xc5, 0xf3, 0x38, 0x81, 0x49, 0x86, 0xcd, 0xf0,
+            0x68, 0x64, 0x53, 0xa8, 0x88, 0xb8, 0x4f, 0x42, 0x4d, 0x79, 0x2a, 0xf4, 0xb9, 0x20,
+            0x23, 0x98, 0xf3, 0x92,
+        ];
+        unsafe {
+            let handle = crypto_new(SHA3_256_ALG_ID);
+            assert!(!handle.is_null());
+            assert_eq!(
+                crypto_handle_update(handle, input.as_ptr(), input.len()),
+                CryptoResult::Success
+            );
+            assert_eq!(
+                crypto_handle_finalize(handle, digest.as_mut_ptr(), 31),
+                CryptoResult::BadBufferOutputSize
+            );
+            assert_eq!(
+                crypto_handle_finalize(handle, digest.as_mut_ptr(), digest.len()),
+                CryptoResult::Success
+            );
+            assert_eq!(digest, expected);
+            assert_eq!(
+                crypto_handle_squeeze(handle, digest.as_mut_ptr(), digest.len()),
+                CryptoResult::BadOrUnsupportedAlgorithm
+            );
+            crypto_free(handle);
+
+            // Keyed handles give the same MAC as keyed contexts.
+            let key = [0x0bu8; 20];
+            let mut state = CryptoContext {
+                state: [0u64; CRYPTO_CONTEXT_STATE_SIZE],
+            };
+            let mut mac = [0u8; 32];
+            assert_eq!(
+                crypto_hmac_init(&mut state, SHA256_ALG_ID, key.as_ptr(), key.len()),
+                CryptoResult::Success
+            );
+            assert_eq!(
+                crypto_update(&mut state, input.as_ptr(), input.len()),
+                CryptoResult::Success
+            );
+            assert_eq!(
+                crypto_finalize(&mut state, mac.as_mut_ptr(), mac.len()),
+                CryptoResult::Success
+            );
+            let handle = crypto_hmac_new(SHA256_ALG_ID, key.as_ptr(), key.len());
+            assert!(!handle.is_null());
+            assert_eq!(
+                crypto_handle_update(handle, input.as_ptr(), input.len()),
+                CryptoResult::Success
+            );
+            assert_eq!(
+                crypto_handle_finalize(handle, digest.as_mut_ptr(), digest.len()),
+                CryptoResult::Success
+            );
+            assert_eq!(digest, mac);
+            crypto_free(handle);
+
+            let handle = crypto_new(SHAKE128_ALG_ID);
+            assert_eq!(
+                crypto_handle_squeeze(handle, digest.as_mut_ptr(), digest.len()),
+                CryptoResult::Success
+            );
+            crypto_free(handle);
+        }
+    }
+
+    #[test]
+    fn test_handle_errors() {
+        let input = b"hello";
+        unsafe {
+            assert!(crypto_new(0xff).is_null());
+            assert!(!crypto_last_error_detail().is_null());
+            assert!(crypto_hmac_new(0xff, input.as_ptr(), input.len()).is_null());
+            assert!(crypto_hmac_new(SHA256_ALG_ID, ptr::null(), 0).is_null());
+            assert!(crypto_hmac_new(SHA256_ALG_ID, ptr::null(), 16).is_null());
+            let detail = CStr::from_ptr(crypto_last_error_detail());
+            assert_eq!(detail.to_str(), Ok("key pointer is null"));
+            assert_eq!(
+                crypto_handle_update(ptr::null_mut(), input.as_ptr(), input.len()),
+                CryptoResult::PointerCannotBeNull
+            );
+            let handle = crypto_new(SHA256_ALG_ID);
+            assert_eq!(
+                crypto_handle_update(handle, ptr::null(), 0),
+                CryptoResult::PointerCannotBeNull
+            );
+            crypto_free(handle);
+            crypto_free(ptr::null_mut());
+        }
+    }
+
+    #[test]
+    fn test_safe_api() {
+        use std::io::Write;
+
+        let expected = [
+            0x33, 0x38, 0xbe, 0x69, 0x4f, 0x50, 0xc5, 0xf3, 0x38, 0x81, 0x49, 0x86, 0xcd, 0xf0,
+            0x68, 0x64, 0x53, 0xa8, 0x88, 0xb8, 0x4f, 0x42, 0x4d, 0x79, 0x2a, 0xf4, 0xb9, 0x20,
+            0x23, 0x98, 0xf3, 0x92,
+        ];
+        let mut hasher = Hasher::new(Algorithm::Sha3_256);
+        assert_eq!(hasher.output_size(), 32);
+        hasher.update(b"he").unwrap();
+        hasher.write_all(b"llo").unwrap();
+        assert_eq!(hasher.finalize_to_vec().unwrap(), expected);
+        assert_eq!(Hasher::digest(Algorithm::Sha3_256, b"hello"), expected);
+        let mut digest = [0u8; 31];
+        assert_eq!(
+            hasher.finalize(&mut digest),
+            Err(CryptoResult::BadBufferOutputSize)
+        );
+
+        // The C API gives the same MAC as the safe API.
+        let key = b"key";
+        let mut mac = [0u8; 32];
+        assert_eq!(
+            unsafe { crypto_hmac_init(ptr::null_mut(), SHA256_ALG_ID, key.as_ptr(), key.len()) },
+            CryptoResult::PointerCannotBeNull
+        );
+        let mut state = CryptoContext {
+            state: [0u64; CRYPTO_CONTEXT_STATE_SIZE],
+        };
+        unsafe {
+            assert_eq!(
+                crypto_hmac_init(&mut state, SHA256_ALG_ID, key.as_ptr(), key.len()),
+                CryptoResult::Success
+            );
+            assert_eq!(
+                crypto_update(&mut state, b"hello".as_ptr(), 5),
+                CryptoResult::Success
+            );
+            assert_eq!(
+                crypto_finalize(&mut state, mac.as_mut_ptr(), mac.len()),
+                CryptoResult::Success
+            );
+        }
+        let mut hmac = Hasher::new_hmac(Algorithm::Sha256, key).unwrap();
+        hmac.update(b"hello").unwrap();
+        assert_eq!(hmac.finalize_to_vec().unwrap(), mac);
+        assert_eq!(
+            Hasher::new_hmac(Algorithm::Shake128, key).err(),
+            Some(CryptoResult::BadOrUnsupportedAlgorithm)
+        );
+
+        let mut squeezed = [0u8; 16];
+        let mut shake = Hasher::new(Algorithm::Shake128);
+        shake.squeeze(&mut squeezed).unwrap();
+        assert_eq!(
+            shake.update(b"hello"),
+            Err(CryptoResult::UninitializedOrCorruptedContext)
+        );
+    }
+
+    #[test]
+    fn test_algorithm_ids() {
+        for algorithm in Algorithm::all() {
+            assert_eq!(Algorithm::try_from(algorithm.id()), Ok(algorithm));
+            assert_eq!(algorithm.output_size(), crypto_output_size(algorithm.id()));
+            let name = unsafe { CStr::from_ptr(crypto_algorithm_name(algorithm.id())) };
+            assert_eq!(name.to_str(), Ok(algorithm.name()));
+        }
+        assert_eq!(Algorithm::all().count(), crypto_algorithm_count());
+        assert_eq!(Algorithm::Sha256.to_string(), "SHA-256");
+        for name in ["SHA-256", "sha256", "Sha_256"] {
+            assert_eq!(name.parse(), Ok(Algorithm::Sha256));
+        }
+        assert_eq!("shake128".parse(), Ok(Algorithm::Shake128));
+        assert_eq!(
+            "md5".parse::<Algorithm>(),
+            Err(CryptoResult::BadOrUnsupportedAlgorithm)
+        );
+        assert!(Algorithm::Shake256.is_xof());
+        assert!(!Algorithm::Sha256.is_xof());
+        assert_eq!(
+            Algorithm::try_from(0xff),
+            Err(CryptoResult::BadOrUnsupportedAlgorithm)
+        );
+    }
+
+    #[test]
+    fn test_panic_guard() {
+        let result = error::guard(|| panic!("deliberate panic"));
+        assert_eq!(result, CryptoResult::Failure);
+        let detail = unsafe { CStr::from_ptr(crypto_last_error_detail()) };
+        assert_eq!(detail.to_str(), Ok("internal panic: deliberate panic"));
+        let handle = error::catch_panic(ptr::null_mut(), || -> *mut CryptoHandle {
+            panic!("{} panic", "formatted")
+        });
+        assert!(handle.is_null());
+        let detail = unsafe { CStr::from_ptr(crypto_last_error_detail()) };
+        assert_eq!(detail.to_str(), Ok("internal panic: formatted panic"));
+    }
+
+    #[test]
+    fn test_internal_panic() {
+        let mut state = CryptoContext {
+            state: [0u64; CRYPTO_CONTEXT_STATE_SIZE],
+        };
+        let input = [0xaau8; 5];
+        let mut digest = [0u8; 32];
+        unsafe {
+            assert_eq!(
+                crypto_init(&mut state, SHA256_ALG_ID),
+                CryptoResult::Success
+            );
+            error::INJECT_PANIC.with(|inject| inject.set(true));
+            assert_eq!(
+                crypto_update(&mut state, input.as_ptr(), input.len()),
+                CryptoResult::Failure
+            );
+            let detail = CStr::from_ptr(crypto_last_error_detail());
+            assert_eq!(detail.to_str().unwrap(), "internal panic: injected panic");
+            // The panic happened before the body ran, so the context is still usable.
+            assert_eq!(
+                crypto_update(&mut state, input.as_ptr(), input.len()),
+                CryptoResult::Success
+            );
+            assert_eq!(
+                crypto_finalize(&mut state, digest.as_mut_ptr(), digest.len()),
+                CryptoResult::Success
+            );
+        }
+        assert_eq!(digest[..], Hasher::digest(Algorithm::Sha256, &input)[..]);
+
+        // Functions that do not return a `CryptoResult` return their fallback value instead.
+        error::INJECT_PANIC.with(|inject| inject.set(true));
+        assert_eq!(crypto_output_size(SHA256_ALG_ID), 0);
+        assert_eq!(crypto_output_size(SHA256_ALG_ID), 32);
+    }
+
+    #[test]
+    fn test_verify() {
+        let a = [0x5au8; 32];
+        let mut b = a;
+        unsafe {
+            assert_eq!(
+                crypto_verify(a.as_ptr(), b.as_ptr(), a.len()),
+                CryptoResult::Success
+            );
+            b[31] ^= 1;
+            assert_eq!(
+                crypto_verify(a.as_ptr(), b.as_ptr(), a.len()),
+                CryptoResult::VerificationFailed
+            );
+            assert_eq!(
+                crypto_verify(a.as_ptr(), b.as_ptr(), 0),
+                CryptoResult::Success
+            );
+            assert_eq!(
+                crypto_verify(ptr::null(), b.as_ptr(), a.len()),
+                CryptoResult::PointerCannotBeNull
+            );
+        }
+        assert!(verify(&a, &a));
+        assert!(!verify(&a, &a[..31]));
+    }
+
+    #[test]
+    fn test_finalize_verify() {
+        // RFC 4231, test case 2.
+        let key = b"Jefe";
+        let message = b"what do ya want for nothing?";
+        let mut mac = [
+            0x5b, 0xdc, 0xc1, 0x46, 0xbf, 0x60, 0x75, 0x4e, 0x6a, 0x04, 0x24, 0x26, 0x08, 0x95,
+            0x75, 0xc7, 0x5a, 0x00, 0x3f, 0x08, 0x9d, 0x27, 0x39, 0x83, 0x9d, 0xec, 0x58, 0xb9,
+            0x64, 0xec, 0x38, 0x43,
+        ];
+        let mut state = CryptoContext {
+            state: [0u64; CRYPTO_CONTEXT_STATE_SIZE],
+        };
+        unsafe {
+            assert_eq!(
+                crypto_hmac_init(&mut state, SHA256_ALG_ID, key.as_ptr(), key.len()),
+                CryptoResult::Success
+            );
+            assert_eq!(
+                crypto_update(&mut state, message.as_ptr(), message.len()),
+                CryptoResult::Success
+            );
+            assert_eq!(
+                crypto_finalize_verify(&mut state, mac.as_ptr(), 31),
+                CryptoResult::BadBufferOutputSize
+            );
+            assert_eq!(
+                crypto_finalize_verify(&mut state, mac.as_ptr(), mac.len()),
+                CryptoResult::Success
+            );
+            // The context was reset to its keyed state.
+            assert_eq!(
+                crypto_update(&mut state, message.as_ptr(), message.len()),
+                CryptoResult::Success
+            );
+            mac[0] ^= 1;
+            assert_eq!(
+                crypto_finalize_verify(&mut state, mac.as_ptr(), mac.len()),
+                CryptoResult::VerificationFailed
+            );
+            mac[0] ^= 1;
+
+            let handle = crypto_hmac_new(SHA256_ALG_ID, key.as_ptr(), key.len());
+            assert_eq!(
+                crypto_handle_update(handle, message.as_ptr(), message.len()),
+                CryptoResult::Success
+            );
+            assert_eq!(
+                crypto_handle_finalize_verify(handle, mac.as_ptr(), mac.len()),
+                CryptoResult::Success
+            );
+            crypto_free(handle);
+        }
+        let mut hasher = Hasher::new_hmac(Algorithm::Sha256, key).unwrap();
+        hasher.update(message).unwrap();
+        assert_eq!(hasher.finalize_verify(&mac), Ok(()));
+        assert_eq!(
+            hasher.finalize_verify(&mac),
+            Err(CryptoResult::VerificationFailed)
+        );
+    }
+
+    #[test]
+    fn test_uninitialized_context() {
+        let input = b"hello";
+        let mut digest = [0u8; 32];
+        let mut zeroed = CryptoContext {
+            state: [0u64; CRYPTO_CONTEXT_STATE_SIZE],
+        };
+        // Deterministic pseudorandom garbage (xorshift64)
+        let mut seed = 0x9e37_79b9_7f4a_7c15u64;
+        let mut random = CryptoContext {
+            state: [0u64; CRYPTO_CONTEXT_STATE_SIZE],
+        };
+        for word in random.state.iter_mut() {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            *word = seed;
+        }
+        for state in [&mut zeroed, &mut random] {
+            unsafe {
+                assert_eq!(
+                    crypto_update(state, input.as_ptr(), input.len()),
+                    CryptoResult::UninitializedOrCorruptedContext
+                );
+                assert_eq!(
+                    crypto_finalize(state, digest.as_mut_ptr(), digest.len()),
+                    CryptoResult::UninitializedOrCorruptedContext
+                );
+                assert_eq!(
+                    crypto_squeeze(state, digest.as_mut_ptr(), digest.len()),
+                    CryptoResult::UninitializedOrCorruptedContext
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_corrupted_context() {
+        let input = b"hello";
+        let mut state = CryptoContext {
+            state: [0u64; CRYPTO_CONTEXT_STATE_SIZE],
+        };
+        unsafe {
+            // Clobbered magic word
+            assert_eq!(
+                crypto_init(&mut state, SHA256_ALG_ID),
+                CryptoResult::Success
+            );
+            state.state[0] ^= 1;
+            assert_eq!(
+                crypto_update(&mut state, input.as_ptr(), input.len()),
+                CryptoResult::UninitializedOrCorruptedContext
+            );
+
+            // Valid magic word but unknown discriminant
+            assert_eq!(
+                crypto_init(&mut state, SHA256_ALG_ID),
+                CryptoResult::Success
+            );
+            let checked: *mut CheckedContext = (&mut state as *mut CryptoContext).cast();
+            ptr::addr_of_mut!((*checked).hasher)
+                .cast::<u32>()
+                .write(0xdead);
+            assert_eq!(
+                crypto_update(&mut state, input.as_ptr(), input.len()),
+                CryptoResult::UninitializedOrCorruptedContext
+            );
+        }
+    }
+
+    #[test]
+    fn test_clone() {
+        // Same expected output as test_sha256
+        let expected: [u8; 32] = [
+            0x2c, 0xf2, 0x4d, 0xba, 0x5f, 0xb0, 0xa3, 0x0e, 0x26, 0xe8, 0x3b, 0x2a, 0xc5, 0xb9,
+            0xe2, 0x9e, 0x1b, 0x16, 0x1e, 0x5c, 0x1f, 0xa7, 0x42, 0x5e, 0x73, 0x04, 0x33, 0x62,
+            0x93, 0x8b, 0x98, 0x24,
+        ];
+        let mut prefix = CryptoContext {
+            state: [0u64; CRYPTO_CONTEXT_STATE_SIZE],
+        };
+        let mut copy = CryptoContext {
+            state: [0u64; CRYPTO_CONTEXT_STATE_SIZE],
+        };
+        unsafe {
+            assert_eq!(
+                crypto_clone(&mut copy, &prefix),
+                CryptoResult::UninitializedOrCorruptedContext
+            );
+            assert_eq!(
+                crypto_init(&mut prefix, SHA256_ALG_ID),
+                CryptoResult::Success
+            );
+            assert_eq!(
+                crypto_update(&mut prefix, b"hel".as_ptr(), 3),
+                CryptoResult::Success
+            );
+            assert_eq!(crypto_clone(&mut copy, &prefix), CryptoResult::Success);
+
+            // Both contexts continue independently from the common prefix
+            for state in [&mut prefix, &mut copy] {
+                check_digest(state, b"lo", &expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_export_import_state() {
+        // Same expected output as test_sha3_256
+        let expected: [u8; 32] = [
+            0x33, 0x38, 0xbe, 0x69, 0x4f, 0x50, 0xc5, 0xf3, 0x38, 0x81, 0x49, 0x86, 0xcd, 0xf0,
+            0x68, 0x64, 0x53, 0xa8, 0x88, 0xb8, 0x4f, 0x42, 0x4d, 0x79, 0x2a, 0xf4, 0xb9, 0x20,
+            0x23, 0x98, 0xf3, 0x92,
+        ];
+        let mut state = CryptoContext {
+            state: [0u64; CRYPTO_CONTEXT_STATE_SIZE],
+        };
+        let mut resumed = CryptoContext {
+            state: [0u64; CRYPTO_CONTEXT_STATE_SIZE],
+        };
+        let mut blob = [0u8; CRYPTO_STATE_MAX_SIZE];
+        let mut blob_length = 0;
+        unsafe {
+            assert_eq!(
+                crypto_export_state(&state, blob.as_mut_ptr(), blob.len(), &mut blob_length),
+                CryptoResult::UninitializedOrCorruptedContext
+            );
+            assert_eq!(
+                crypto_init(&mut state, SHA3_256_ALG_ID),
+                CryptoResult::Success
+            );
+            assert_eq!(
+                crypto_update(&mut state, b"hel".as_ptr(), 3),
+                CryptoResult::Success
+            );
+            assert_eq!(
+                crypto_export_state(&state, blob.as_mut_ptr(), 16, &mut blob_length),
+                CryptoResult::BadBufferOutputSize
+            );
+            assert!(blob_length > 16 && blob_length <= CRYPTO_STATE_MAX_SIZE);
+            assert_eq!(
+                crypto_export_state(&state, blob.as_mut_ptr(), blob.len(), &mut blob_length),
+                CryptoResult::Success
+            );
+            assert_eq!(
+                crypto_import_state(&mut resumed, blob.as_ptr(), blob_length),
+                CryptoResult::Success
+            );
+            check_digest(&mut resumed, b"lo", &expected);
+
+            assert_eq!(
+                crypto_hmac_init(&mut state, SHA256_ALG_ID, b"key".as_ptr(), 3),
+                CryptoResult::Success
+            );
+            assert_eq!(
+                crypto_export_state(&state, blob.as_mut_ptr(), blob.len(), &mut blob_length),
+                CryptoResult::BadOrUnsupportedAlgorithm
+            );
+        }
+    }
+
+    #[test]
+    fn test_export_import_state_all_algorithms() {
+        for algorithm in Algorithm::all() {
+            let mut hasher = Hasher::new(algorithm);
+            hasher.update(b"hel").unwrap();
+            let blob = match hasher.export_state() {
+                Ok(blob) => blob,
+                Err(error) => {
+                    assert_eq!(
+                        error,
+                        CryptoResult::BadOrUnsupportedAlgorithm,
+                        "{algorithm}"
+                    );
+                    assert!(algorithm.name().starts_with("BLAKE"), "{algorithm}");
+                    continue;
+                }
+            };
+            assert!(blob.len() <= CRYPTO_STATE_MAX_SIZE, "{algorithm}");
+            let mut resumed = Hasher::import_state(&blob).unwrap();
+            resumed.update(b"lo").unwrap();
+            assert_eq!(
+                resumed.finalize_to_vec().unwrap(),
+                Hasher::digest(algorithm, b"hello"),
+                "{algorithm}"
+            );
+        }
+
+        let mut shake = Hasher::new(Algorithm::Shake128);
+        shake.squeeze(&mut [0u8; 16]).unwrap();
+        assert_eq!(
+            shake.export_state().err(),
+            Some(CryptoResult::BadOrUnsupportedAlgorithm)
+        );
+    }
+
+    #[test]
+    fn test_import_corrupted_state() {
+        let mut hasher = Hasher::new(Algorithm::Sha256);
+        hasher.update(b"hel").unwrap();
+        let mut blob = hasher.export_state().unwrap();
+        let mut target = CryptoContext {
+            state: [0u64; CRYPTO_CONTEXT_STATE_SIZE],
+        };
         unsafe {
             assert_eq!(
-                crypto_update(
-                    &mut state as *mut CryptoContext,
-                    input.as_ptr(),
-                    input.len(),
+                crypto_import_state(&mut target, blob.as_ptr(), blob.len() - 1),
+                CryptoResult::UninitializedOrCorruptedContext
+            );
+            // Flipping any bit of the header, the state or the checksum is detected
+            for position in [0, 4, 8, 12, 16, 40, blob.len() - 1] {
+                blob[position] ^= 0x01;
+                assert_eq!(
+                    crypto_import_state(&mut target, blob.as_ptr(), blob.len()),
+                    CryptoResult::UninitializedOrCorruptedContext
+                );
+                blob[position] ^= 0x01;
+            }
+            // The target is left untouched by failed imports
+            assert_eq!(target.state, [0u64; CRYPTO_CONTEXT_STATE_SIZE]);
+        }
+
+        // Blobs of another format version or of an unknown algorithm, with a valid checksum
+        let resign = |blob: &mut Vec<u8>| {
+            let checksum_offset = blob.len() - 32;
+            let checksum = Hasher::digest(Algorithm::Sha256, &blob[..checksum_offset]);
+            blob[checksum_offset..].copy_from_slice(&checksum);
+        };
+        let mut other_version = blob.clone();
+        other_version[4] = 2;
+        resign(&mut other_version);
+        assert_eq!(
+            Hasher::import_state(&other_version).err(),
+            Some(CryptoResult::UninitializedOrCorruptedContext)
+        );
+        let mut unknown_algorithm = blob.clone();
+        unknown_algorithm[8] = 0xff;
+        resign(&mut unknown_algorithm);
+        assert_eq!(
+            Hasher::import_state(&unknown_algorithm).err(),
+            Some(CryptoResult::BadOrUnsupportedAlgorithm)
+        );
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_hash_file() {
+        let path = std::env::temp_dir().join(format!("cryptoapi-test-{}", std::process::id()));
+        let data: Vec<u8> = (0..300_000u32).map(|i| (i * 7 + i / 256) as u8).collect();
+        std::fs::write(&path, &data).unwrap();
+        let c_path = std::ffi::CString::new(path.to_str().unwrap()).unwrap();
+
+        for algorithm in Algorithm::all() {
+            let mut digest = vec![0u8; algorithm.output_size()];
+            unsafe {
+                assert_eq!(
+                    crypto_hash_file(
+                        algorithm.id(),
+                        c_path.as_ptr(),
+                        digest.as_mut_ptr(),
+                        digest.len()
+                    ),
+                    CryptoResult::Success
+                );
+            }
+            assert_eq!(digest, Hasher::digest(algorithm, &data), "{algorithm}");
+        }
+
+        // Three chunks: the root is H(1 || H(1 || L0 || L1) || L2) with Li = H(0 || chunk i).
+        let chunk_size = 100_000;
+        let leaf = |chunk: &[u8]| Hasher::digest(Algorithm::Sha256, &[&[0u8][..], chunk].concat());
+        let node = |left: &[u8], right: &[u8]| {
+            Hasher::digest(Algorithm::Sha256, &[&[1u8][..], left, right].concat())
+        };
+        let leaves: Vec<_> = data.chunks(chunk_size).map(leaf).collect();
+        let expected = node(&node(&leaves[0], &leaves[1]), &leaves[2]);
+        let mut digest = [0u8; 32];
+        unsafe {
+            assert_eq!(
+                crypto_hash_file_tree(
+                    SHA256_ALG_ID,
+                    c_path.as_ptr(),
+                    chunk_size,
+                    digest.as_mut_ptr(),
+                    digest.len()
                 ),
                 CryptoResult::Success
-            )
+            );
         }
+        assert_eq!(digest[..], expected[..]);
+        file::hash_tree(Algorithm::Sha256, &data, data.len(), &mut digest).unwrap();
+        assert_eq!(digest[..], leaf(&data)[..]);
+        file::hash_tree(Algorithm::Sha256, &[], 1, &mut digest).unwrap();
+        assert_eq!(digest[..], leaf(&[])[..]);
 
-        // Finalize and get the digest
-        let mut digest = [0u8; 32];
         unsafe {
             assert_eq!(
-                crypto_finalize(
-                    &mut state as *mut CryptoContext,
+                crypto_hash_file_tree(
+                    SHA256_ALG_ID,
+                    c_path.as_ptr(),
+                    0,
                     digest.as_mut_ptr(),
                     digest.len()
                 ),
+                CryptoResult::BadParameter
+            );
+            assert_eq!(
+                crypto_hash_file(SHA256_ALG_ID, c_path.as_ptr(), digest.as_mut_ptr(), 31),
+                CryptoResult::BadBufferOutputSize
+            );
+            assert_eq!(
+                crypto_hash_file(SHA256_ALG_ID, ptr::null(), digest.as_mut_ptr(), 32),
+                CryptoResult::PointerCannotBeNull
+            );
+        }
+
+        std::fs::write(&path, []).unwrap();
+        unsafe { file::hash_file(Algorithm::Sha256, &path, &mut digest) }.unwrap();
+        assert_eq!(digest[..], Hasher::digest(Algorithm::Sha256, &[])[..]);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            unsafe { file::hash_file(Algorithm::Sha256, &path, &mut digest) },
+            Err(CryptoResult::Failure)
+        );
+        let detail = unsafe { CStr::from_ptr(crypto_last_error_detail()) };
+        assert!(detail.to_str().unwrap().starts_with("cannot read "));
+    }
+
+    #[test]
+    fn test_merkle_tree() {
+        // Certificate Transparency test vectors: roots of the trees of the first 1 to 8 leaves.
+        let leaves: [&[u8]; 8] = [
+            b"",
+            b"\x00",
+            b"\x10",
+            b"\x20\x21",
+            b"\x30\x31",
+            b"\x40\x41\x42\x43",
+            b"\x50\x51\x52\x53\x54\x55\x56\x57",
+            b"\x60\x61\x62\x63\x64\x65\x66\x67\x68\x69\x6a\x6b\x6c\x6d\x6e\x6f",
+        ];
+        let roots = [
+            "6e340b9cffb37a989ca544e6bb780a2c78901d3fb33738768511a30617afa01d",
+            "fac54203e7cc696cf0dfcb42c92a1d9dbaf70ad9e621f4bd8d98662f00e3c125",
+            "aeb6bcfe274b70a14fb067a5e5578264db0fa9b51af5e0ba159158f329e06e77",
+            "d37ee418976dd95753c1c73862b9398fa2a2cf9b4ff0fdfe8b30cd95209614b7",
+            "4e3bbb1f7b478dcfe71fb631631519a3bca12c9aefca1612bfce4c13a86264d4",
+            "76e67dadbcdf1e10e1b74ddc608abd2f98dfb16fbce75277b5232a127f2087ef",
+            "ddb89be403809e325750d3d263cd78929c2942b7942a34b77e122c9594a74c8c",
+            "5dc9da79a70659a9ad559cb701ded9a2ab9d823aad2f4960cfe370eff4604328",
+        ];
+        let hex = |bytes: &[u8]| bytes.iter().map(|b| format!("{b:02x}")).collect::<String>();
+        let trees: Vec<_> = (1..=leaves.len())
+            .map(|size| {
+                merkle::MerkleTree::from_leaves(Algorithm::Sha256, leaves[..size].iter().copied())
+            })
+            .collect();
+        for (tree, root) in trees.iter().zip(roots) {
+            assert_eq!(hex(&tree.root()), root);
+        }
+        assert_eq!(
+            merkle::MerkleTree::new(Algorithm::Sha256).root(),
+            Hasher::digest(Algorithm::Sha256, b"")
+        );
+
+        for (size, tree) in (1..).zip(&trees) {
+            let root = tree.root();
+            for (index, leaf) in leaves[..size].iter().enumerate() {
+                let proof = tree.inclusion_proof(index).unwrap();
+                let verify = |leaf: &[u8], index, size, proof: &[u8]| {
+                    merkle::verify_inclusion(Algorithm::Sha256, leaf, index, size, proof, &root)
+                };
+                assert_eq!(verify(leaf, index, size, &proof), Ok(()));
+                let failed = Err(CryptoResult::VerificationFailed);
+                assert_eq!(verify(b"other", index, size, &proof), failed);
+                if size > 1 {
+                    assert_eq!(verify(leaf, index ^ 1, size, &proof), failed);
+                    let mut tampered = proof.clone();
+                    tampered[0] ^= 1;
+                    assert_eq!(verify(leaf, index, size, &tampered), failed);
+                    assert_eq!(verify(leaf, index, size, &proof[1..]), failed);
+                }
+            }
+            assert_eq!(tree.inclusion_proof(size), Err(CryptoResult::BadParameter));
+
+            for (old_size, old_tree) in (1..=size).zip(&trees) {
+                let proof = tree.consistency_proof(old_size).unwrap();
+                let verify = |old_size, proof: &[u8], old_root: &[u8]| {
+                    merkle::verify_consistency(
+                        Algorithm::Sha256,
+                        old_size,
+                        size,
+                        proof,
+                        old_root,
+                        &root,
+                    )
+                };
+                assert_eq!(verify(old_size, &proof, &old_tree.root()), Ok(()));
+                if old_size < size {
+                    let failed = Err(CryptoResult::VerificationFailed);
+                    assert_eq!(verify(old_size, &proof, &root), failed);
+                    assert_eq!(verify(old_size, &proof[32..], &old_tree.root()), failed);
+                }
+            }
+            assert_eq!(tree.consistency_proof(0), Err(CryptoResult::BadParameter));
+        }
+
+        // The C API gives the same proofs, and reports the size of proofs that do not fit.
+        let tree = crypto_merkle_new(SHA256_ALG_ID);
+        assert!(!tree.is_null());
+        assert!(crypto_merkle_new(0xff).is_null());
+        let mut root = [0u8; 32];
+        let mut proof = [0u8; 64 * 32];
+        let mut proof_length = 0;
+        unsafe {
+            for leaf in leaves {
+                assert_eq!(
+                    crypto_merkle_append(tree, leaf.as_ptr(), leaf.len()),
+                    CryptoResult::Success
+                );
+            }
+            assert_eq!(crypto_merkle_leaf_count(tree), 8);
+            assert_eq!(crypto_merkle_leaf_count(ptr::null()), 0);
+            assert_eq!(
+                crypto_merkle_root(tree, root.as_mut_ptr(), root.len()),
+                CryptoResult::Success
+            );
+            assert_eq!(hex(&root), roots[7]);
+
+            let check_inclusion = |proof: &[u8]| {
+                crypto_merkle_verify_inclusion(
+                    SHA256_ALG_ID,
+                    leaves[5].as_ptr(),
+                    leaves[5].len(),
+                    5,
+                    8,
+                    proof.as_ptr(),
+                    proof.len(),
+                    root.as_ptr(),
+                    root.len(),
+                )
+            };
+            assert_eq!(
+                crypto_merkle_inclusion_proof(tree, 5, proof.as_mut_ptr(), 64, &mut proof_length),
+                CryptoResult::BadBufferOutputSize
+            );
+            assert_eq!(proof_length, 3 * 32);
+            assert_eq!(
+                crypto_merkle_inclusion_proof(
+                    tree,
+                    5,
+                    proof.as_mut_ptr(),
+                    proof.len(),
+                    &mut proof_length
+                ),
+                CryptoResult::Success
+            );
+            assert_eq!(
+                proof[..proof_length],
+                trees[7].inclusion_proof(5).unwrap()[..]
+            );
+            assert_eq!(
+                check_inclusion(&proof[..proof_length]),
+                CryptoResult::Success
+            );
+            proof[0] ^= 1;
+            assert_eq!(
+                check_inclusion(&proof[..proof_length]),
+                CryptoResult::VerificationFailed
+            );
+
+            let old_root = trees[2].root();
+            assert_eq!(
+                crypto_merkle_consistency_proof(
+                    tree,
+                    3,
+                    proof.as_mut_ptr(),
+                    proof.len(),
+                    &mut proof_length
+                ),
+                CryptoResult::Success
+            );
+            assert_eq!(
+                crypto_merkle_verify_consistency(
+                    SHA256_ALG_ID,
+                    3,
+                    8,
+                    proof.as_ptr(),
+                    proof_length,
+                    old_root.as_ptr(),
+                    root.as_ptr(),
+                    root.len()
+                ),
+                CryptoResult::Success
+            );
+            assert_eq!(
+                crypto_merkle_verify_consistency(
+                    SHA256_ALG_ID,
+                    3,
+                    8,
+                    proof.as_ptr(),
+                    proof_length,
+                    old_root.as_ptr(),
+                    root.as_ptr(),
+                    31
+                ),
+                CryptoResult::BadBufferOutputSize
+            );
+            assert_eq!(
+                crypto_merkle_append(tree, ptr::null(), 0),
+                CryptoResult::PointerCannotBeNull
+            );
+            crypto_merkle_free(tree);
+            crypto_merkle_free(ptr::null_mut());
+        }
+    }
+
+    /// Decodes a hex test vector, ignoring whitespace.
+    fn from_hex(hex: &str) -> Vec<u8> {
+        let digits: Vec<u8> = hex.bytes().filter(|c| !c.is_ascii_whitespace()).collect();
+        digits
+            .chunks(2)
+            .map(|pair| u8::from_str_radix(std::str::from_utf8(pair).unwrap(), 16).unwrap())
+            .collect()
+    }
+
+    /// Seals with the C API, checks the ciphertext and tag, and opens it back in place.
+    #[cfg(feature = "aead")]
+    fn check_aead(
+        aead_id: u32,
+        key: &[u8],
+        nonce: &[u8],
+        associated_data: &[u8],
+        plaintext: &[u8],
+        expected: &[u8],
+    ) {
+        let mut sealed = vec![0u8; plaintext.len() + CRYPTO_AEAD_TAG_SIZE];
+        let mut opened = vec![0u8; plaintext.len()];
+        unsafe {
+            assert_eq!(
+                crypto_aead_seal(
+                    aead_id,
+                    key.as_ptr(),
+                    key.len(),
+                    nonce.as_ptr(),
+                    nonce.len(),
+                    associated_data.as_ptr(),
+                    associated_data.len(),
+                    plaintext.as_ptr(),
+                    plaintext.len(),
+                    sealed.as_mut_ptr(),
+                    sealed.len()
+                ),
+                CryptoResult::Success
+            );
+            assert_eq!(sealed, expected);
+            assert_eq!(
+                crypto_aead_open(
+                    aead_id,
+                    key.as_ptr(),
+                    key.len(),
+                    nonce.as_ptr(),
+                    nonce.len(),
+                    associated_data.as_ptr(),
+                    associated_data.len(),
+                    sealed.as_ptr(),
+                    sealed.len(),
+                    opened.as_mut_ptr(),
+                    opened.len()
+                ),
                 CryptoResult::Success
+            );
+        }
+        assert_eq!(opened, plaintext);
+
+        let algorithm = aead::AeadAlgorithm::try_from(aead_id).unwrap();
+        let mut buffer = expected[..plaintext.len()].to_vec();
+        let tag = &expected[plaintext.len()..];
+        aead::open_in_place(algorithm, key, nonce, associated_data, &mut buffer, tag).unwrap();
+        assert_eq!(buffer, plaintext);
+    }
+
+    #[cfg(feature = "aead")]
+    #[test]
+    fn test_chacha20_poly1305_rfc8439() {
+        // RFC 8439, section 2.8.2
+        let plaintext = b"Ladies and Gentlemen of the class of '99: If I could offer you only one \
+            tip for the future, sunscreen would be it.";
+        let key: Vec<u8> = (0x80..=0x9f).collect();
+        let nonce = from_hex("07000000 40414243 44454647");
+        let associated_data = from_hex("50515253 c0c1c2c3 c4c5c6c7");
+        let expected = from_hex(
+            "d31a8d34648e60db7b86afbc53ef7ec2 a4aded51296e08fea9e2b5a736ee62d6
+             3dbea45e8ca9671282fafb69da92728b 1a71de0a9e060b2905d6a5b67ecd3b36
+             92ddbd7f2d778b8c9803aee328091b58 fab324e4fad675945585808b4831d7bc
+             3ff4def08e4b7a9de576d26586cec64b 6116
+             1ae10b594f09e26a7e902ecbd0600691",
+        );
+        check_aead(
+            CHACHA20_POLY1305_AEAD_ID,
+            &key,
+            &nonce,
+            &associated_data,
+            plaintext,
+            &expected,
+        );
+    }
+
+    #[cfg(feature = "aead")]
+    #[test]
+    fn test_aes_256_gcm_nist() {
+        // Test cases 13, 14 and 16 of the GCM specification, for AES-256.
+        let zeros = [0u8; 32];
+        check_aead(
+            AES_256_GCM_AEAD_ID,
+            &zeros,
+            &zeros[..12],
+            &[],
+            &[],
+            &from_hex("530f8afbc74536b9a963b4f1c4cb738b"),
+        );
+        check_aead(
+            AES_256_GCM_AEAD_ID,
+            &zeros,
+            &zeros[..12],
+            &[],
+            &zeros[..16],
+            &from_hex("cea7403d4d606b6e074ec5d3baf39d18 d0d1c8a799996bf0265b98b5d48ab919"),
+        );
+        check_aead(
+            AES_256_GCM_AEAD_ID,
+            &from_hex("feffe9928665731c6d6a8f9467308308 feffe9928665731c6d6a8f9467308308"),
+            &from_hex("cafebabefacedbaddecaf888"),
+            &from_hex("feedfacedeadbeeffeedfacedeadbeef abaddad2"),
+            &from_hex(
+                "d9313225f88406e5a55909c5aff5269a 86a7a9531534f7da2e4c303d8a318a72
+                 1c3c0c95956809532fcf0e2449a6b525 b16aedf5aa0de657ba637b39",
+            ),
+            &from_hex(
+                "522dc1f099567d07f47f37a32a84427d 643a8cdcbfe5c0c97598a2bd2555d1aa
+                 8cb08e48590dbb3da7b08b1056828838 c5f61e6393ba7a0abcc9f662
+                 76fc6ece0f4e1768cddf8853bb2d551b",
+            ),
+        );
+    }
+
+    #[cfg(feature = "aead")]
+    #[test]
+    fn test_aead_errors() {
+        let key = [7u8; CRYPTO_AEAD_KEY_SIZE];
+        let nonce = [9u8; CRYPTO_AEAD_NONCE_SIZE];
+        let message = b"attack at dawn";
+        let length = message.len() + CRYPTO_AEAD_TAG_SIZE;
+        let seal = |aead_id, key: &[u8], nonce: &[u8], buffer: &mut [u8], output_length| unsafe {
+            // In place: the message is at the start of the buffer, followed by room for the tag.
+            crypto_aead_seal(
+                aead_id,
+                key.as_ptr(),
+                key.len(),
+                nonce.as_ptr(),
+                nonce.len(),
+                b"header".as_ptr(),
+                6,
+                buffer.as_ptr(),
+                message.len(),
+                buffer.as_mut_ptr(),
+                output_length,
+            )
+        };
+        let open = |associated_data: &[u8], buffer: &mut [u8]| unsafe {
+            crypto_aead_open(
+                AES_256_GCM_AEAD_ID,
+                key.as_ptr(),
+                key.len(),
+                nonce.as_ptr(),
+                nonce.len(),
+                associated_data.as_ptr(),
+                associated_data.len(),
+                buffer.as_ptr(),
+                length,
+                buffer.as_mut_ptr(),
+                message.len(),
             )
         };
 
-        // Expected output of SHA3_256 of hello string (utf8)
-        let expected: [u8; 32] = [
-            0x33, 0x38, 0xbe, 0x69, 0x4f, 0x50, 0xc5, 0xf3, 0x38, 0x81, 0x49, 0x86, 0xcd, 0xf0,
-            0x68, 0x64, 0x53, 0xa8, 0x88, 0xb8, 0x4f, 0x42, 0x4d, 0x79, 0x2a, 0xf4, 0xb9, 0x20,
-            0x23, 0x98, 0xf3, 0x92,
+        let mut buffer = [0u8; 64];
+        buffer[..message.len()].copy_from_slice(message);
+        assert_eq!(
+            seal(AES_256_GCM_AEAD_ID, &key, &nonce, &mut buffer, length),
+            CryptoResult::Success
+        );
+        let sealed = buffer;
+        let mut expected = vec![0u8; length];
+        aead::seal(
+            aead::AeadAlgorithm::Aes256Gcm,
+            &key,
+            &nonce,
+            b"header",
+            message,
+            &mut expected,
+        )
+        .unwrap();
+        assert_eq!(buffer[..length], expected[..]);
+        assert_eq!(open(b"header", &mut buffer), CryptoResult::Success);
+        assert_eq!(buffer[..message.len()], message[..]);
+
+        // Any change to the ciphertext, tag or associated data is detected, and the output wiped.
+        for position in [0, message.len()] {
+            buffer = sealed;
+            buffer[position] ^= 0x80;
+            assert_eq!(
+                open(b"header", &mut buffer),
+                CryptoResult::VerificationFailed
+            );
+            assert_eq!(buffer[..message.len()], [0u8; 14]);
+        }
+        buffer = sealed;
+        assert_eq!(
+            open(b"Header", &mut buffer),
+            CryptoResult::VerificationFailed
+        );
+        let mut opened = [1u8; 14];
+        assert_eq!(
+            aead::open(
+                aead::AeadAlgorithm::ChaCha20Poly1305,
+                &key,
+                &nonce,
+                b"header",
+                &expected,
+                &mut opened
+            ),
+            Err(CryptoResult::VerificationFailed)
+        );
+        assert_eq!(opened, [0u8; 14]);
+
+        buffer[..message.len()].copy_from_slice(message);
+        assert_eq!(
+            seal(AES_256_GCM_AEAD_ID, &key, &nonce, &mut buffer, length - 1),
+            CryptoResult::BadBufferOutputSize
+        );
+        assert_eq!(
+            seal(AES_256_GCM_AEAD_ID, &key[1..], &nonce, &mut buffer, length),
+            CryptoResult::BadParameter
+        );
+        assert_eq!(
+            seal(AES_256_GCM_AEAD_ID, &key, &nonce[1..], &mut buffer, length),
+            CryptoResult::BadParameter
+        );
+        assert_eq!(
+            seal(2, &key, &nonce, &mut buffer, length),
+            CryptoResult::BadOrUnsupportedAlgorithm
+        );
+        // Rejected parameters leave a separate output untouched, without plaintext in it.
+        let mut output = [0xaau8; 64];
+        assert_eq!(
+            unsafe {
+                crypto_aead_seal(
+                    CHACHA20_POLY1305_AEAD_ID,
+                    key.as_ptr(),
+                    key.len(),
+                    nonce.as_ptr(),
+                    nonce.len() - 1,
+                    b"header".as_ptr(),
+                    6,
+                    message.as_ptr(),
+                    message.len(),
+                    output.as_mut_ptr(),
+                    length,
+                )
+            },
+            CryptoResult::BadParameter
+        );
+        assert_eq!(output, [0xaau8; 64]);
+        assert_eq!(
+            aead::seal(
+                aead::AeadAlgorithm::Aes256Gcm,
+                &key[1..],
+                &nonce,
+                b"header",
+                message,
+                &mut output[..length]
+            ),
+            Err(CryptoResult::BadParameter)
+        );
+        assert_eq!(output, [0xaau8; 64]);
+        // The length check fails before anything is read, so the input length can be bogus.
+        assert_eq!(
+            unsafe {
+                crypto_aead_seal(
+                    AES_256_GCM_AEAD_ID,
+                    key.as_ptr(),
+                    key.len(),
+                    nonce.as_ptr(),
+                    nonce.len(),
+                    b"header".as_ptr(),
+                    6,
+                    buffer.as_ptr(),
+                    usize::MAX,
+                    buffer.as_mut_ptr(),
+                    length,
+                )
+            },
+            CryptoResult::BadParameter
+        );
+        assert_eq!(
+            unsafe {
+                crypto_aead_open(
+                    AES_256_GCM_AEAD_ID,
+                    key.as_ptr(),
+                    key.len(),
+                    nonce.as_ptr(),
+                    nonce.len(),
+                    ptr::null(),
+                    0,
+                    buffer.as_ptr(),
+                    length,
+                    buffer.as_mut_ptr(),
+                    message.len(),
+                )
+            },
+            CryptoResult::PointerCannotBeNull
+        );
+    }
+
+    #[cfg(feature = "ed25519")]
+    #[test]
+    fn test_ed25519_rfc8032() {
+        // RFC 8032, section 7.1, tests 1 to 3
+        let vectors = [
+            (
+                "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60",
+                "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+                "",
+                "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b",
+            ),
+            (
+                "4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb",
+                "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c",
+                "72",
+                "92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00",
+            ),
+            (
+                "c5aa8df43f9f837bedb7442f31dcb7b166d38535076f094b85ce3a2e0b4458f7",
+                "fc51cd8e6218a1a38da47ed00230f0580816ed13ba3303ac5deb911548908025",
+                "af82",
+                "6291d657deec24024827e69c3abe01a30ce548a284743a445e3680d7db5ac3ac18ff9b538d16f290ae67f760984dc6594a7c15e9716ed28dc027beceea1ec40a",
+            ),
         ];
+        for (seed, public_key, message, signature) in vectors {
+            let seed = CryptoEd25519Seed {
+                bytes: from_hex(seed).try_into().unwrap(),
+            };
+            let message = from_hex(message);
+            let expected_public_key = CryptoEd25519PublicKey {
+                bytes: from_hex(public_key).try_into().unwrap(),
+            };
+            let expected_signature = CryptoEd25519Signature {
+                bytes: from_hex(signature).try_into().unwrap(),
+            };
+            let mut public_key = CryptoEd25519PublicKey { bytes: [0u8; 32] };
+            let mut signature = CryptoEd25519Signature { bytes: [0u8; 64] };
+            unsafe {
+                assert_eq!(
+                    crypto_ed25519_public_key(&seed, &mut public_key),
+                    CryptoResult::Success
+                );
+                assert_eq!(public_key, expected_public_key);
+                assert_eq!(
+                    crypto_ed25519_sign(&seed, message.as_ptr(), message.len(), &mut signature),
+                    CryptoResult::Success
+                );
+                assert_eq!(signature, expected_signature);
+                assert_eq!(
+                    crypto_ed25519_verify(&public_key, message.as_ptr(), message.len(), &signature),
+                    CryptoResult::Success
+                );
+            }
+        }
+    }
 
-        assert_eq!(digest, expected);
+    #[cfg(feature = "ed25519")]
+    #[test]
+    fn test_ed25519_errors() {
+        let seed = CryptoEd25519Seed { bytes: [3u8; 32] };
+        let message = b"firmware image";
+        let mut public_key = CryptoEd25519PublicKey { bytes: [0u8; 32] };
+        let mut signature = CryptoEd25519Signature { bytes: [0u8; 64] };
+        unsafe {
+            assert_eq!(
+                crypto_ed25519_public_key(&seed, &mut public_key),
+                CryptoResult::Success
+            );
+            assert_eq!(
+                crypto_ed25519_sign(&seed, message.as_ptr(), message.len(), &mut signature),
+                CryptoResult::Success
+            );
+            let verify = |public_key: &CryptoEd25519PublicKey,
+                          message: &[u8],
+                          signature: &CryptoEd25519Signature| {
+                crypto_ed25519_verify(public_key, message.as_ptr(), message.len(), signature)
+            };
+            assert_eq!(
+                verify(&public_key, message, &signature),
+                CryptoResult::Success
+            );
+            assert_eq!(
+                verify(&public_key, b"firmware imagf", &signature),
+                CryptoResult::VerificationFailed
+            );
+            for byte in [0, 63] {
+                let mut tampered = signature;
+                tampered.bytes[byte] ^= 1;
+                assert_eq!(
+                    verify(&public_key, message, &tampered),
+                    CryptoResult::VerificationFailed
+                );
+            }
+
+            // The identity point has small order, and y = 2 is not on the curve.
+            let mut identity = CryptoEd25519PublicKey { bytes: [0u8; 32] };
+            identity.bytes[0] = 1;
+            assert_eq!(
+                verify(&identity, message, &signature),
+                CryptoResult::VerificationFailed
+            );
+            let mut invalid = CryptoEd25519PublicKey { bytes: [0u8; 32] };
+            invalid.bytes[0] = 2;
+            assert_eq!(
+                verify(&invalid, message, &signature),
+                CryptoResult::BadParameter
+            );
+
+            assert_eq!(
+                crypto_ed25519_verify(ptr::null(), message.as_ptr(), 0, &signature),
+                CryptoResult::PointerCannotBeNull
+            );
+        }
     }
 
+    #[cfg(feature = "x25519")]
     #[test]
-    fn test_sha256() {
-        // Initialize the state
-        let mut state = CryptoContext {
-            state: [0u64; CRYPTO_CONTEXT_STATE_SIZE],
+    fn test_x25519_rfc7748() {
+        // RFC 7748, section 6.1
+        let key = |hex: &str| -> [u8; 32] { from_hex(hex).try_into().unwrap() };
+        let alice = CryptoX25519SecretKey {
+            bytes: key("77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a"),
         };
+        let bob = CryptoX25519SecretKey {
+            bytes: key("5dab087e624a8a4b79e17f8b83800ee66f3bb1292618b6fd1c2f8b27ff88e0eb"),
+        };
+        let alice_public_key =
+            key("8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a");
+        let bob_public_key =
+            key("de9edb7d7b7dc1b4d35b61c2ece435373f8343c85b78674dadfc7e146f882b4f");
+        let expected = key("4a5d9d5ba4ce2de1728e3bf480350f25e07e21c947d19e3376f09b3c1e161742");
+
+        let mut public_keys = [CryptoX25519PublicKey { bytes: [0u8; 32] }; 2];
+        let mut shared_secrets = [CryptoX25519SharedSecret { bytes: [0u8; 32] }; 2];
         unsafe {
             assert_eq!(
-                crypto_init(&mut state as *mut CryptoContext, SHA256_ALG_ID),
+                crypto_x25519_public_key(&alice, &mut public_keys[0]),
+                CryptoResult::Success
+            );
+            assert_eq!(
+                crypto_x25519_public_key(&bob, &mut public_keys[1]),
+                CryptoResult::Success
+            );
+            assert_eq!(public_keys[0].bytes, alice_public_key);
+            assert_eq!(public_keys[1].bytes, bob_public_key);
+            assert_eq!(
+                crypto_x25519_shared_secret(&alice, &public_keys[1], &mut shared_secrets[0]),
+                CryptoResult::Success
+            );
+            assert_eq!(
+                crypto_x25519_shared_secret(&bob, &public_keys[0], &mut shared_secrets[1]),
                 CryptoResult::Success
             );
+        }
+        assert_eq!(shared_secrets[0].bytes, expected);
+        assert_eq!(shared_secrets[1].bytes, expected);
+
+        // Both sides derive the same key, the HKDF of the shared secret.
+        let (salt, info) = (b"salt", b"channel keys");
+        let mut prk = [0u8; 32];
+        let mut expected_okm = [0u8; 42];
+        kdf::hkdf_extract(Algorithm::Sha256, salt, &expected, &mut prk).unwrap();
+        kdf::hkdf_expand(Algorithm::Sha256, &prk, info, &mut expected_okm).unwrap();
+        for (secret_key, peer_public_key) in [(&alice, &public_keys[1]), (&bob, &public_keys[0])] {
+            let mut okm = [0u8; 42];
+            unsafe {
+                assert_eq!(
+                    crypto_x25519_hkdf(
+                        SHA256_ALG_ID,
+                        secret_key,
+                        peer_public_key,
+                        salt.as_ptr(),
+                        salt.len(),
+                        info.as_ptr(),
+                        info.len(),
+                        okm.as_mut_ptr(),
+                        okm.len()
+                    ),
+                    CryptoResult::Success
+                );
+            }
+            assert_eq!(okm, expected_okm);
+        }
+    }
+
+    #[cfg(feature = "x25519")]
+    #[test]
+    fn test_x25519_zero_shared_secret() {
+        let secret_key = CryptoX25519SecretKey { bytes: [5u8; 32] };
+        let mut shared_secret = CryptoX25519SharedSecret { bytes: [1u8; 32] };
+        // Zero and one are points of small order, as is p + 1, the non-canonical encoding of one.
+        let mut p_plus_one = [0xffu8; 32];
+        p_plus_one[0] = 0xee;
+        p_plus_one[31] = 0x7f;
+        let mut one = [0u8; 32];
+        one[0] = 1;
+        for bytes in [[0u8; 32], one, p_plus_one] {
+            let peer_public_key = CryptoX25519PublicKey { bytes };
+            unsafe {
+                assert_eq!(
+                    crypto_x25519_shared_secret(&secret_key, &peer_public_key, &mut shared_secret),
+                    CryptoResult::ZeroSharedSecret
+                );
+            }
+            assert_eq!(shared_secret.bytes, [0u8; 32]);
+            let mut okm = [0u8; 32];
+            let result = unsafe {
+                crypto_x25519_hkdf(
+                    SHA256_ALG_ID,
+                    &secret_key,
+                    &peer_public_key,
+                    okm.as_ptr(),
+                    0,
+                    okm.as_ptr(),
+                    0,
+                    okm.as_mut_ptr(),
+                    okm.len(),
+                )
+            };
+            assert_eq!(result, CryptoResult::ZeroSharedSecret);
+        }
+        let description =
+            unsafe { CStr::from_ptr(crypto_result_str(CryptoResult::ZeroSharedSecret)) };
+        assert_eq!(
+            description.to_str(),
+            Ok("key agreement gave an all-zero shared secret")
+        );
+    }
+
+    #[test]
+    fn test_hash_drbg_nist() {
+        // Hash_DRBG.rsp of the NIST CAVP DRBG vectors, [SHA-256], prediction resistance, COUNT
+        // 14: each request reseeds with its entropy and additional input, then generates.
+        let entropy = from_hex("066dc8ce75b28966a685163fe2a4d427fbdb616650616ba282fc332b4e6f1220");
+        let nonce = from_hex("559f7c64897083ec2d7370d9f0e5071f");
+        let personalization =
+            from_hex("886f549aad1ac63d18cbcc6685daa2c2f79eb0894cb4aef1ac544fce57f15e11");
+        let requests = [
+            (
+                "ff80b7d26a05bc8a7abe53286b0eeb733b715a205bfa4ff63703deadb6ea0ef4",
+                "b7215f14ac7bafd0a91772ba22f719afbd20b311636c2b1e83e4a823353fc6ea",
+            ),
+            (
+                "c73832534681ede37e03846d3c841767297d246c689241d2e775be7ec996293d",
+                "ced31f7e0dae5bb5c043e246b29473e2fd39512ead4569eee3e3803314aba7a3",
+            ),
+        ];
+        let expected = from_hex(
+            "60c234cfafb468033bf195e578ce266e1465326a96a9e03f8b893670ef62754d
+             5e80d553a1f84950208b9343079f2ef856e9c570618597b5dc82a2daeaa3fd9b
+             2fd2a0d71bc62935ccb83da0679805a0e31efee4f0e513b08317faca935e3829
+             48d272db763e6df32510ff1b99fff8c60eb0dd292ebcbbc80a016ed3b00e4eab",
+        );
+        unsafe {
+            let drbg = crypto_drbg_instantiate(
+                SHA256_ALG_ID,
+                entropy.as_ptr(),
+                entropy.len(),
+                nonce.as_ptr(),
+                nonce.len(),
+                personalization.as_ptr(),
+                personalization.len(),
+            );
+            assert!(!drbg.is_null());
+            let mut output = [0u8; 128];
+            for (entropy, additional_input) in requests {
+                let entropy = from_hex(entropy);
+                let additional_input = from_hex(additional_input);
+                assert_eq!(
+                    crypto_drbg_reseed(
+                        drbg,
+                        entropy.as_ptr(),
+                        entropy.len(),
+                        additional_input.as_ptr(),
+                        additional_input.len(),
+                    ),
+                    CryptoResult::Success
+                );
+                assert_eq!(
+                    crypto_drbg_generate(drbg, output.as_mut_ptr(), output.len(), [].as_ptr(), 0),
+                    CryptoResult::Success
+                );
+            }
+            assert_eq!(output.to_vec(), expected);
+            crypto_drbg_free(drbg);
+        }
+    }
+
+    #[test]
+    fn test_hash_drbg() {
+        let entropy: Vec<u8> = (0..32).collect();
+        let nonce: Vec<u8> = (32..48).collect();
+        let reseed_entropy: Vec<u8> = (48..80).collect();
+        // Computed with an independent implementation of SP 800-90A: 100 bytes with additional
+        // input "add", then 7 bytes after a reseed. SHA-384 and SHA-512 use the 888-bit seed.
+        // Only pushed to when optional algorithms are enabled.
+        #[allow(unused_mut)]
+        let mut vectors = vec![(
+            Algorithm::Sha3_256,
+            "a0a0935cea5c04cca5fb1a49d1fa0be26a85fd22b130576bc4da10340d29d2fbad1e791ad33cf0346de9
+             78bbe49c45869fe6e735beca21a39416167ee204764e3aa776ff0b60ce952f626b999d35da4b9a4a3d20
+             9b5af1151000f18d4a15b216f67a2fb4",
+            "5ffec5510f7434",
+        )];
+        #[cfg(feature = "sha3_512")]
+        vectors.push((
+            Algorithm::Sha3_512,
+            "0ea1167530a08e96d181e83dac94d695108ab26a9c05da989af9cd9874cceee811a7e76ab0aa504ab11f
+             b9758b6f1ed309dc7fb84c80be71ab2b0d043e4b758492030e65341bbfb8f5e6ff0ca16848a70c8465dd
+             f6b6c6420a059d708a8480277ec355a8",
+            "fee637d08e4fe1",
+        ));
+        #[cfg(feature = "sha384")]
+        vectors.push((
+            Algorithm::Sha384,
+            "437c577fc831bd8eb50e41819d53618aefd7d537b1523adc5bfeb4204e7c8cdabf0acbbede89d86963a2
+             3db7f9cf23704dfee5c9e5865808d6bf57c1ac2c738b002f01268a8aa76b1d8fce2505f0a1359b2139c9
+             73da2ca3463f38f6d90b100251f10c4b",
+            "bab990e7777b3b",
+        ));
+        #[cfg(feature = "sha512")]
+        vectors.push((
+            Algorithm::Sha512,
+            "c4bf506e06c22ec4c2eb57b4001b99f424db8d82a950178ada9b701ff46b00104da17e48acf2d78ebce2
+             517d3fff94b678f19c4bab44ee7e173f42d549d23f22fcd2acf198fbbcd0d9a1b93527df58e23d3cdcbd
+             e685925a762640eb600c2a80dffebe3e",
+            "d56310983ec5ad",
+        ));
+        for (algorithm, first, second) in vectors {
+            let mut drbg = random::HashDrbg::new(algorithm, &entropy, &nonce, b"pers").unwrap();
+            assert_eq!(drbg.algorithm(), algorithm);
+            let mut output = [0u8; 100];
+            drbg.generate(&mut output, b"add").unwrap();
+            assert_eq!(output.to_vec(), from_hex(first), "{algorithm}");
+            drbg.reseed(&reseed_entropy, &[]).unwrap();
+            let mut output = [0u8; 7];
+            drbg.generate(&mut output, &[]).unwrap();
+            assert_eq!(output.to_vec(), from_hex(second), "{algorithm}");
+        }
+
+        // Same seed, same stream; any input change gives another stream.
+        let stream = |personalization: &[u8], additional_input: &[u8]| {
+            let mut drbg =
+                random::HashDrbg::new(Algorithm::Sha256, &entropy, &nonce, personalization)
+                    .unwrap();
+            let mut output = [0u8; 64];
+            drbg.generate(&mut output[..32], additional_input).unwrap();
+            drbg.generate(&mut output[32..], additional_input).unwrap();
+            output
         };
+        assert_eq!(stream(b"", b""), stream(b"", b""));
+        assert_ne!(stream(b"", b""), stream(b"p", b""));
+        assert_ne!(stream(b"", b""), stream(b"", b"a"));
+        assert_ne!(stream(b"", b"")[..32], stream(b"", b"")[32..]);
+    }
 
-        // Update with the string "hello"
-        let input = b"hello";
+    #[test]
+    fn test_hash_drbg_errors() {
+        let entropy = [7u8; 32];
         unsafe {
+            let drbg = crypto_drbg_instantiate(
+                SHAKE128_ALG_ID,
+                entropy.as_ptr(),
+                entropy.len(),
+                entropy.as_ptr(),
+                0,
+                entropy.as_ptr(),
+                0,
+            );
+            assert!(drbg.is_null());
+            let detail = CStr::from_ptr(crypto_last_error_detail());
             assert_eq!(
-                crypto_update(
-                    &mut state as *mut CryptoContext,
-                    input.as_ptr(),
-                    input.len(),
+                detail.to_str(),
+                Ok("SHAKE128 cannot be used with Hash_DRBG")
+            );
+            let drbg = crypto_drbg_instantiate(
+                SHA256_ALG_ID,
+                entropy.as_ptr(),
+                31,
+                entropy.as_ptr(),
+                0,
+                entropy.as_ptr(),
+                0,
+            );
+            assert!(drbg.is_null());
+            let detail = CStr::from_ptr(crypto_last_error_detail());
+            assert_eq!(
+                detail.to_str(),
+                Ok("entropy input is 31 bytes, expected at least 32 bytes")
+            );
+            let drbg = crypto_drbg_instantiate(
+                SHA256_ALG_ID,
+                entropy.as_ptr(),
+                entropy.len(),
+                ptr::null(),
+                0,
+                entropy.as_ptr(),
+                0,
+            );
+            assert!(drbg.is_null());
+            let detail = CStr::from_ptr(crypto_last_error_detail());
+            assert_eq!(
+                detail.to_str(),
+                Ok("entropy, nonce or personalization pointer is null")
+            );
+
+            let drbg = crypto_drbg_instantiate(
+                SHA3_256_ALG_ID,
+                entropy.as_ptr(),
+                entropy.len(),
+                entropy.as_ptr(),
+                0,
+                entropy.as_ptr(),
+                0,
+            );
+            assert!(!drbg.is_null());
+            assert_eq!(
+                crypto_drbg_reseed(drbg, entropy.as_ptr(), 16, entropy.as_ptr(), 0),
+                CryptoResult::BadParameter
+            );
+            let mut output = vec![0u8; CRYPTO_DRBG_MAX_REQUEST_SIZE + 1];
+            assert_eq!(
+                crypto_drbg_generate(drbg, output.as_mut_ptr(), output.len(), entropy.as_ptr(), 0),
+                CryptoResult::BadBufferOutputSize
+            );
+            assert_eq!(
+                crypto_drbg_generate(
+                    drbg,
+                    output.as_mut_ptr(),
+                    CRYPTO_DRBG_MAX_REQUEST_SIZE,
+                    entropy.as_ptr(),
+                    0
                 ),
                 CryptoResult::Success
-            )
+            );
+            assert_eq!(
+                crypto_drbg_generate(drbg, ptr::null_mut(), 0, entropy.as_ptr(), 0),
+                CryptoResult::PointerCannotBeNull
+            );
+            crypto_drbg_free(drbg);
+            crypto_drbg_free(ptr::null_mut());
         }
+    }
 
-        // Finalize and get the digest
-        let mut digest = [0u8; 32];
+    #[cfg(feature = "random")]
+    #[test]
+    fn test_random_bytes() {
+        let mut first = [0u8; 32];
+        let mut second = [0u8; 32];
         unsafe {
             assert_eq!(
-                crypto_finalize(
-                    &mut state as *mut CryptoContext,
-                    digest.as_mut_ptr(),
-                    digest.len()
-                ),
+                crypto_random_bytes(first.as_mut_ptr(), first.len()),
                 CryptoResult::Success
-            )
-        };
+            );
+            assert_eq!(
+                crypto_random_bytes(second.as_mut_ptr(), second.len()),
+                CryptoResult::Success
+            );
+            assert_eq!(
+                crypto_random_bytes(ptr::null_mut(), 0),
+                CryptoResult::PointerCannotBeNull
+            );
+        }
+        assert_ne!(first, second);
 
-        // Expected output of SHA256 of hello string (utf8)
-        let expected: [u8; 32] = [
-            0x2c, 0xf2, 0x4d, 0xba, 0x5f, 0xb0, 0xa3, 0x0e, 0x26, 0xe8, 0x3b, 0x2a, 0xc5, 0xb9,
-            0xe2, 0x9e, 0x1b, 0x16, 0x1e, 0x5c, 0x1f, 0xa7, 0x42, 0x5e, 0x73, 0x04, 0x33, 0x62,
-            0x93, 0x8b, 0x98, 0x24,
+        let mut drbg = random::HashDrbg::from_entropy(Algorithm::Sha256, b"test").unwrap();
+        let mut other = random::HashDrbg::from_entropy(Algorithm::Sha256, b"test").unwrap();
+        drbg.generate(&mut first, &[]).unwrap();
+        other.generate(&mut second, &[]).unwrap();
+        assert_ne!(first, second);
+    }
+
+    #[cfg(feature = "argon2")]
+    #[test]
+    fn test_argon2id_reference() {
+        // Argon2id v0x13 vectors of the reference implementation, with 256 KiB of memory.
+        let vectors = [
+            (
+                1,
+                "$argon2id$v=19$m=256,t=2,p=1$c29tZXNhbHQ$nf65EOgLrQMR/uIPnA4rEsF5h7TKyQwu9U1bMCHGi/4",
+            ),
+            (
+                2,
+                "$argon2id$v=19$m=256,t=2,p=2$c29tZXNhbHQ$bQk8UB/VmZZF4Oo79iDXuL5/0ttZwg2f/5U52iv1cDc",
+            ),
         ];
+        for (parallelism, expected) in vectors {
+            let params = CryptoArgon2Params {
+                memory_kib: 256,
+                iterations: 2,
+                parallelism,
+                output_length: 32,
+            };
+            let mut phc = [0 as c_char; CRYPTO_ARGON2_PHC_MAX_SIZE];
+            let password = b"password";
+            let salt = b"somesalt";
+            unsafe {
+                assert_eq!(
+                    crypto_argon2id_hash(
+                        password.as_ptr(),
+                        password.len(),
+                        salt.as_ptr(),
+                        salt.len(),
+                        &params,
+                        phc.as_mut_ptr(),
+                        phc.len(),
+                    ),
+                    CryptoResult::Success
+                );
+                assert_eq!(CStr::from_ptr(phc.as_ptr()).to_str(), Ok(expected));
+                assert_eq!(
+                    crypto_argon2id_verify(password.as_ptr(), password.len(), phc.as_ptr()),
+                    CryptoResult::Success
+                );
+                assert_eq!(
+                    crypto_argon2id_verify(password.as_ptr(), password.len() - 1, phc.as_ptr()),
+                    CryptoResult::VerificationFailed
+                );
+            }
+        }
 
-        assert_eq!(digest, expected);
+        // Any salt and hash length round-trip, and the string carries them to verification.
+        let params = CryptoArgon2Params {
+            memory_kib: 64,
+            iterations: 1,
+            parallelism: 1,
+            output_length: 64,
+        };
+        let phc = password::hash_password(b"secret", &[0xa5; 48], &params).unwrap();
+        assert!(phc.starts_with("$argon2id$v=19$m=64,t=1,p=1$"));
+        assert!(phc.len() < CRYPTO_ARGON2_PHC_MAX_SIZE);
+        assert_eq!(password::verify_password(b"secret", &phc), Ok(()));
+        assert_eq!(
+            password::verify_password(b"Secret", &phc),
+            Err(CryptoResult::VerificationFailed)
+        );
+        assert_eq!(CryptoArgon2Params::default().memory_kib, 19456);
+    }
+
+    #[cfg(feature = "argon2")]
+    #[test]
+    fn test_argon2id_errors() {
+        let params = CryptoArgon2Params {
+            memory_kib: 64,
+            iterations: 1,
+            parallelism: 1,
+            output_length: 32,
+        };
+        let hash = |salt: &[u8], params| password::hash_password(b"password", salt, &params);
+        assert_eq!(hash(b"short", params), Err(CryptoResult::BadParameter));
+        let detail = unsafe { CStr::from_ptr(crypto_last_error_detail()) };
+        assert_eq!(
+            detail.to_str(),
+            Ok("salt is 5 bytes, expected 8 to 48 bytes")
+        );
+        assert_eq!(hash(&[0; 49], params), Err(CryptoResult::BadParameter));
+        for invalid in [
+            CryptoArgon2Params {
+                output_length: 3,
+                ..params
+            },
+            CryptoArgon2Params {
+                output_length: 65,
+                ..params
+            },
+            CryptoArgon2Params {
+                iterations: 0,
+                ..params
+            },
+            CryptoArgon2Params {
+                parallelism: 0,
+                ..params
+            },
+            CryptoArgon2Params {
+                parallelism: 9,
+                ..params
+            },
+            CryptoArgon2Params {
+                memory_kib: CRYPTO_ARGON2_MAX_MEMORY_KIB + 1,
+                ..params
+            },
+            CryptoArgon2Params {
+                iterations: CRYPTO_ARGON2_MAX_ITERATIONS + 1,
+                ..params
+            },
+            CryptoArgon2Params {
+                memory_kib: 8 * (CRYPTO_ARGON2_MAX_PARALLELISM + 1),
+                parallelism: CRYPTO_ARGON2_MAX_PARALLELISM + 1,
+                ..params
+            },
+        ] {
+            assert_eq!(
+                hash(b"somesalt", invalid),
+                Err(CryptoResult::BadParameter),
+                "{invalid:?}"
+            );
+        }
+
+        let phc = hash(b"somesalt", params).unwrap();
+        let verify = |phc: &str| password::verify_password(b"password", phc);
+        assert_eq!(verify(&phc), Ok(()));
+        // Changing any parameter changes the hash.
+        assert_eq!(
+            verify(&phc.replace("t=1", "t=2")),
+            Err(CryptoResult::VerificationFailed)
+        );
+        assert_eq!(
+            verify(&phc.replace("v=19", "v=16")),
+            Err(CryptoResult::VerificationFailed)
+        );
+        assert_eq!(
+            verify(&phc.replace("argon2id", "argon2i")),
+            Err(CryptoResult::BadOrUnsupportedAlgorithm)
+        );
+        // Costs over the limits are rejected before any work is done.
+        assert_eq!(
+            verify(&phc.replace("m=64", "m=4194304")),
+            Err(CryptoResult::BadParameter)
+        );
+        assert_eq!(
+            verify(&phc.replace("t=1", "t=4294967295")),
+            Err(CryptoResult::BadParameter)
+        );
+        let detail = unsafe { CStr::from_ptr(crypto_last_error_detail()) };
+        assert_eq!(
+            detail.to_str(),
+            Ok("iteration count 4294967295 is not between 1 and 64")
+        );
+        assert_eq!(
+            verify(&phc.replace("m=64,t=1,p=1", "m=8192,t=1,p=1024")),
+            Err(CryptoResult::BadParameter)
+        );
+        assert_eq!(verify("$argon2id$v=19"), Err(CryptoResult::BadParameter));
+        assert_eq!(verify("not a hash"), Err(CryptoResult::BadParameter));
+        assert_eq!(verify(""), Err(CryptoResult::BadParameter));
+
+        let mut phc = [0 as c_char; 64];
+        let password = b"password";
+        unsafe {
+            assert_eq!(
+                crypto_argon2id_hash(
+                    password.as_ptr(),
+                    password.len(),
+                    password.as_ptr(),
+                    password.len(),
+                    &params,
+                    phc.as_mut_ptr(),
+                    phc.len(),
+                ),
+                CryptoResult::BadBufferOutputSize
+            );
+            assert_eq!(
+                crypto_argon2id_hash(
+                    password.as_ptr(),
+                    password.len(),
+                    password.as_ptr(),
+                    password.len(),
+                    ptr::null(),
+                    phc.as_mut_ptr(),
+                    phc.len(),
+                ),
+                CryptoResult::PointerCannotBeNull
+            );
+            let invalid_utf8 = [0xffu8 as c_char, 0];
+            assert_eq!(
+                crypto_argon2id_verify(password.as_ptr(), password.len(), invalid_utf8.as_ptr()),
+                CryptoResult::BadParameter
+            );
+        }
     }
 }
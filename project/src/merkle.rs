@@ -0,0 +1,337 @@
+use crate::{verify, Algorithm, CryptoResult, Hasher};
+
+/// Prefix of leaf hashes (RFC 6962, section 2.1).
+const LEAF_PREFIX: u8 = 0x00;
+/// Prefix of interior node hashes (RFC 6962, section 2.1).
+const NODE_PREFIX: u8 = 0x01;
+
+/// Append-only Merkle tree over a list of leaves, as used by Certificate Transparency logs
+/// (RFC 6962, section 2.1), with any supported hash algorithm.
+///
+/// Leaf and interior node hashes are domain separated, so a leaf can never be passed off as an
+/// interior node. Proofs are the concatenation of `output_size` byte hashes, in the order of
+/// RFC 6962.
+///
+/// The roots of complete subtrees are kept as leaves are appended, so the root and proofs take
+/// O(log n) hashes rather than rehashing the whole tree.
+#[derive(Debug, Clone)]
+pub struct MerkleTree {
+    algorithm: Algorithm,
+    /// Concatenated roots of the complete subtrees of 2^k leaves, in leaf order, for each level
+    /// k: `levels[0]` holds the leaf hashes.
+    levels: Vec<Vec<u8>>,
+}
+
+impl MerkleTree {
+    /// Creates an empty tree.
+    pub fn new(algorithm: Algorithm) -> Self {
+        MerkleTree {
+            algorithm,
+            levels: Vec::new(),
+        }
+    }
+
+    /// Creates a tree from a list of leaves.
+    pub fn from_leaves<'a>(
+        algorithm: Algorithm,
+        leaves: impl IntoIterator<Item = &'a [u8]>,
+    ) -> Self {
+        let mut tree = MerkleTree::new(algorithm);
+        for leaf in leaves {
+            tree.push(leaf);
+        }
+        tree
+    }
+
+    /// Hash algorithm of the tree.
+    pub fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    /// Appends a leaf, and the roots of the subtrees it completes.
+    pub fn push(&mut self, leaf: &[u8]) {
+        let hash_size = self.algorithm.output_size();
+        let mut hash = leaf_hash(self.algorithm, leaf);
+        for level in 0.. {
+            if level == self.levels.len() {
+                self.levels.push(Vec::new());
+            }
+            let nodes = &mut self.levels[level];
+            nodes.extend_from_slice(&hash);
+            if (nodes.len() / hash_size) % 2 == 1 {
+                break;
+            }
+            let (left, right) = nodes[nodes.len() - 2 * hash_size..].split_at(hash_size);
+            hash = node_hash(self.algorithm, left, right);
+        }
+    }
+
+    /// Number of leaves.
+    pub fn len(&self) -> usize {
+        self.levels
+            .first()
+            .map_or(0, |leaves| leaves.len() / self.algorithm.output_size())
+    }
+
+    /// Returns true if the tree has no leaves.
+    pub fn is_empty(&self) -> bool {
+        self.levels.is_empty()
+    }
+
+    /// Root hash of the tree. The root of the empty tree is the hash of the empty string.
+    pub fn root(&self) -> Vec<u8> {
+        if self.is_empty() {
+            return Hasher::digest(self.algorithm, &[]);
+        }
+        self.subtree_root(0, self.len())
+    }
+
+    /// Audit path of the leaf at `index` (RFC 6962, section 2.1.1), to be checked against the
+    /// current root with `verify_inclusion`.
+    ///
+    /// Fails with `BadParameter` if there is no leaf at `index`.
+    pub fn inclusion_proof(&self, index: usize) -> Result<Vec<u8>, CryptoResult> {
+        if index >= self.len() {
+            return Err(CryptoResult::BadParameter.with_detail(format_args!(
+                "leaf index {index} is out of range, the tree has {} leaves",
+                self.len()
+            )));
+        }
+        let mut proof = Vec::new();
+        self.audit_path(index, 0, self.len(), &mut proof);
+        Ok(proof)
+    }
+
+    /// Proof that the tree made of the first `old_size` leaves is a prefix of the current tree
+    /// (RFC 6962, section 2.1.2), to be checked with `verify_consistency`.
+    ///
+    /// Fails with `BadParameter` unless `old_size` is between 1 and the number of leaves. The
+    /// proof is empty when `old_size` is the number of leaves.
+    pub fn consistency_proof(&self, old_size: usize) -> Result<Vec<u8>, CryptoResult> {
+        if old_size == 0 || old_size > self.len() {
+            return Err(CryptoResult::BadParameter.with_detail(format_args!(
+                "old tree size {old_size} is out of range, the tree has {} leaves",
+                self.len()
+            )));
+        }
+        let mut proof = Vec::new();
+        self.subproof(old_size, 0, self.len(), true, &mut proof);
+        Ok(proof)
+    }
+
+    /// Root of the `index`th complete subtree of 2^`level` leaves.
+    fn node(&self, level: usize, index: usize) -> &[u8] {
+        let hash_size = self.algorithm.output_size();
+        &self.levels[level][index * hash_size..(index + 1) * hash_size]
+    }
+
+    /// MTH(D[start:end]), for a non-empty range.
+    fn subtree_root(&self, start: usize, end: usize) -> Vec<u8> {
+        let size = end - start;
+        if size.is_power_of_two() {
+            // The ranges of the recursion start at a multiple of their size when it is a power of
+            // two, so they are cached complete subtrees.
+            let level = size.trailing_zeros() as usize;
+            return self.node(level, start >> level).to_vec();
+        }
+        let split = start + split_point(end - start);
+        let left = self.subtree_root(start, split);
+        let right = self.subtree_root(split, end);
+        node_hash(self.algorithm, &left, &right)
+    }
+
+    /// PATH(index, D[start:end]), with `index` relative to the whole tree.
+    fn audit_path(&self, index: usize, start: usize, end: usize, proof: &mut Vec<u8>) {
+        if end - start == 1 {
+            return;
+        }
+        let split = start + split_point(end - start);
+        if index < split {
+            self.audit_path(index, start, split, proof);
+            proof.extend(self.subtree_root(split, end));
+        } else {
+            self.audit_path(index, split, end, proof);
+            proof.extend(self.subtree_root(start, split));
+        }
+    }
+
+    /// SUBPROOF(old_size, D[start:end], complete), with `old_size` relative to `start`.
+    fn subproof(
+        &self,
+        old_size: usize,
+        start: usize,
+        end: usize,
+        complete: bool,
+        proof: &mut Vec<u8>,
+    ) {
+        if old_size == end - start {
+            if !complete {
+                proof.extend(self.subtree_root(start, end));
+            }
+            return;
+        }
+        let split = split_point(end - start);
+        if old_size <= split {
+            self.subproof(old_size, start, start + split, complete, proof);
+            proof.extend(self.subtree_root(start + split, end));
+        } else {
+            self.subproof(old_size - split, start + split, end, false, proof);
+            proof.extend(self.subtree_root(start, start + split));
+        }
+    }
+}
+
+/// Largest power of two smaller than `size`, which must be at least 2: the number of leaves in
+/// the left subtree.
+pub(crate) fn split_point(size: usize) -> usize {
+    1 << (size - 1).ilog2()
+}
+
+/// Hash of a leaf, `H(0x00 || leaf)`.
+pub fn leaf_hash(algorithm: Algorithm, leaf: &[u8]) -> Vec<u8> {
+    Hasher::digest_concat(algorithm, [&[LEAF_PREFIX], leaf])
+}
+
+/// Hash of an interior node, `H(0x01 || left || right)`.
+pub fn node_hash(algorithm: Algorithm, left: &[u8], right: &[u8]) -> Vec<u8> {
+    Hasher::digest_concat(algorithm, [&[NODE_PREFIX], left, right])
+}
+
+/// Verifies that `leaf` is the leaf at `index` of the tree of `tree_size` leaves with the given
+/// root, using an audit path from `MerkleTree::inclusion_proof` (RFC 9162, section 2.1.3.2).
+///
+/// Fails with `VerificationFailed` if the proof does not hold, and with `BadBufferOutputSize` if
+/// the root is not `output_size` bytes long.
+pub fn verify_inclusion(
+    algorithm: Algorithm,
+    leaf: &[u8],
+    index: usize,
+    tree_size: usize,
+    proof: &[u8],
+    root: &[u8],
+) -> Result<(), CryptoResult> {
+    check_root_size(algorithm, root)?;
+    let proof = split_proof(algorithm, proof)?;
+    if index >= tree_size {
+        return Err(failed("leaf index is out of range"));
+    }
+    let (mut index, mut last) = (index, tree_size - 1);
+    let mut hash = leaf_hash(algorithm, leaf);
+    for sibling in proof {
+        if last == 0 {
+            return Err(failed("proof is too long"));
+        }
+        if index & 1 == 1 || index == last {
+            hash = node_hash(algorithm, sibling, &hash);
+            while index & 1 == 0 && index != 0 {
+                index >>= 1;
+                last >>= 1;
+            }
+        } else {
+            hash = node_hash(algorithm, &hash, sibling);
+        }
+        index >>= 1;
+        last >>= 1;
+    }
+    if last != 0 {
+        return Err(failed("proof is too short"));
+    }
+    check_root(&hash, root)
+}
+
+/// Verifies that the tree of `old_size` leaves with root `old_root` is a prefix of the tree of
+/// `new_size` leaves with root `new_root`, using a proof from `MerkleTree::consistency_proof`
+/// (RFC 9162, section 2.1.4.2).
+///
+/// Fails with `VerificationFailed` if the proof does not hold, and with `BadBufferOutputSize` if
+/// a root is not `output_size` bytes long.
+pub fn verify_consistency(
+    algorithm: Algorithm,
+    old_size: usize,
+    new_size: usize,
+    proof: &[u8],
+    old_root: &[u8],
+    new_root: &[u8],
+) -> Result<(), CryptoResult> {
+    check_root_size(algorithm, old_root)?;
+    check_root_size(algorithm, new_root)?;
+    let mut proof = split_proof(algorithm, proof)?.peekable();
+    if old_size == 0 || old_size > new_size {
+        return Err(failed("tree sizes are out of range"));
+    }
+    if old_size == new_size {
+        if proof.peek().is_some() {
+            return Err(failed("proof is too long"));
+        }
+        return check_root(old_root, new_root);
+    }
+    // The old root is the first node of the path when the old tree is a complete subtree.
+    let first = if old_size.is_power_of_two() {
+        old_root
+    } else {
+        proof.next().ok_or_else(|| failed("proof is too short"))?
+    };
+    let (mut index, mut last) = (old_size - 1, new_size - 1);
+    while index & 1 == 1 {
+        index >>= 1;
+        last >>= 1;
+    }
+    let (mut old_hash, mut new_hash) = (first.to_vec(), first.to_vec());
+    for node in proof {
+        if last == 0 {
+            return Err(failed("proof is too long"));
+        }
+        if index & 1 == 1 || index == last {
+            old_hash = node_hash(algorithm, node, &old_hash);
+            new_hash = node_hash(algorithm, node, &new_hash);
+            while index & 1 == 0 && index != 0 {
+                index >>= 1;
+                last >>= 1;
+            }
+        } else {
+            new_hash = node_hash(algorithm, &new_hash, node);
+        }
+        index >>= 1;
+        last >>= 1;
+    }
+    if last != 0 {
+        return Err(failed("proof is too short"));
+    }
+    check_root(&old_hash, old_root)?;
+    check_root(&new_hash, new_root)
+}
+
+fn split_proof(
+    algorithm: Algorithm,
+    proof: &[u8],
+) -> Result<impl Iterator<Item = &[u8]>, CryptoResult> {
+    let hash_size = algorithm.output_size();
+    if !proof.len().is_multiple_of(hash_size) {
+        return Err(failed(format_args!(
+            "proof is {} bytes, not a multiple of the {hash_size} byte hash size",
+            proof.len()
+        )));
+    }
+    Ok(proof.chunks_exact(hash_size))
+}
+
+fn check_root_size(algorithm: Algorithm, root: &[u8]) -> Result<(), CryptoResult> {
+    if root.len() != algorithm.output_size() {
+        return Err(CryptoResult::bad_output_size(
+            algorithm.output_size(),
+            root.len(),
+        ));
+    }
+    Ok(())
+}
+
+fn check_root(computed: &[u8], root: &[u8]) -> Result<(), CryptoResult> {
+    if !verify(computed, root) {
+        return Err(failed("root hash does not match"));
+    }
+    Ok(())
+}
+
+fn failed(detail: impl std::fmt::Display) -> CryptoResult {
+    CryptoResult::VerificationFailed.with_detail(detail)
+}
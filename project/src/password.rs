@@ -0,0 +1,132 @@
+use crate::{
+    CryptoArgon2Params, CryptoResult, CRYPTO_ARGON2_MAX_ITERATIONS, CRYPTO_ARGON2_MAX_MEMORY_KIB,
+    CRYPTO_ARGON2_MAX_PARALLELISM,
+};
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::{Argon2, Params, Version, ARGON2ID_IDENT};
+
+/// Minimum salt length, in bytes (RFC 9106, section 3.1).
+const MIN_SALT_SIZE: usize = 8;
+/// Maximum salt length, in bytes: the longest salt a PHC string can hold (64 base64 characters).
+const MAX_SALT_SIZE: usize = 48;
+/// Minimum hash length, in bytes (RFC 9106, section 3.1).
+const MIN_OUTPUT_SIZE: usize = 4;
+/// Maximum hash length, in bytes: the longest hash a PHC string can hold.
+const MAX_OUTPUT_SIZE: usize = 64;
+
+impl Default for CryptoArgon2Params {
+    /// 19 MiB of memory, 2 iterations and 1 lane, for a 32 byte hash: the minimum configuration
+    /// recommended by OWASP for password storage.
+    fn default() -> Self {
+        CryptoArgon2Params {
+            memory_kib: 19456,
+            iterations: 2,
+            parallelism: 1,
+            output_length: 32,
+        }
+    }
+}
+
+/// Hashes a password with Argon2id (RFC 9106, version 0x13) and returns it in PHC string
+/// format, e.g. `$argon2id$v=19$m=19456,t=2,p=1$<salt>$<hash>`, with the salt and hash in
+/// unpadded base64.
+///
+/// The salt must be unique per password, ideally 16 bytes from `random::fill_random`, and
+/// between 8 and 48 bytes long. Lanes are computed one after the other, so the parallelism
+/// changes the hash but not the time it takes.
+///
+/// Fails with `BadParameter` if the salt length or a cost parameter is out of range.
+pub fn hash_password(
+    password: &[u8],
+    salt: &[u8],
+    params: &CryptoArgon2Params,
+) -> Result<String, CryptoResult> {
+    if !(MIN_SALT_SIZE..=MAX_SALT_SIZE).contains(&salt.len()) {
+        return Err(CryptoResult::BadParameter.with_detail(format_args!(
+            "salt is {} bytes, expected {MIN_SALT_SIZE} to {MAX_SALT_SIZE} bytes",
+            salt.len()
+        )));
+    }
+    let argon2 = argon2id(params)?;
+    // The salt length is checked above, so it always fits a PHC string.
+    let salt = SaltString::encode_b64(salt).map_err(|_| CryptoResult::Failure)?;
+    argon2
+        .hash_password(password, &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|error| CryptoResult::Failure.with_detail(format_args!("Argon2id: {error}")))
+}
+
+/// Verifies a password against an Argon2id hash in PHC string format, as returned by
+/// `hash_password`, using the cost parameters, salt and hash length of the string.
+///
+/// Fails with `VerificationFailed` if the password does not match, with `BadParameter` if the
+/// string cannot be parsed or its parameters are out of range, and with
+/// `BadOrUnsupportedAlgorithm` if it is not an Argon2id hash.
+pub fn verify_password(password: &[u8], phc: &str) -> Result<(), CryptoResult> {
+    let hash = PasswordHash::new(phc).map_err(|error| {
+        CryptoResult::BadParameter.with_detail(format_args!("invalid PHC string: {error}"))
+    })?;
+    if hash.algorithm != ARGON2ID_IDENT {
+        return Err(CryptoResult::BadOrUnsupportedAlgorithm
+            .with_detail(format_args!("{} hashes are not supported", hash.algorithm)));
+    }
+    let output_length = hash.hash.map_or(0, |output| output.len());
+    let params = Params::try_from(&hash).map_err(|error| {
+        CryptoResult::BadParameter.with_detail(format_args!("invalid PHC string: {error}"))
+    })?;
+    argon2id(&CryptoArgon2Params {
+        memory_kib: params.m_cost(),
+        iterations: params.t_cost(),
+        parallelism: params.p_cost(),
+        output_length,
+    })?
+    .verify_password(password, &hash)
+    .map_err(|error| match error {
+        argon2::password_hash::Error::Password => {
+            CryptoResult::VerificationFailed.with_detail("password does not match")
+        }
+        error => {
+            CryptoResult::BadParameter.with_detail(format_args!("invalid PHC string: {error}"))
+        }
+    })
+}
+
+/// Builds an Argon2id hasher, after checking the parameters against the limits of the library.
+fn argon2id(params: &CryptoArgon2Params) -> Result<Argon2<'static>, CryptoResult> {
+    if params.memory_kib > CRYPTO_ARGON2_MAX_MEMORY_KIB {
+        return Err(CryptoResult::BadParameter.with_detail(format_args!(
+            "memory size of {} KiB is over the {CRYPTO_ARGON2_MAX_MEMORY_KIB} KiB limit",
+            params.memory_kib
+        )));
+    }
+    if params.iterations == 0 || params.iterations > CRYPTO_ARGON2_MAX_ITERATIONS {
+        return Err(CryptoResult::BadParameter.with_detail(format_args!(
+            "iteration count {} is not between 1 and {CRYPTO_ARGON2_MAX_ITERATIONS}",
+            params.iterations
+        )));
+    }
+    if params.parallelism == 0 || params.parallelism > CRYPTO_ARGON2_MAX_PARALLELISM {
+        return Err(CryptoResult::BadParameter.with_detail(format_args!(
+            "parallelism {} is not between 1 and {CRYPTO_ARGON2_MAX_PARALLELISM}",
+            params.parallelism
+        )));
+    }
+    if !(MIN_OUTPUT_SIZE..=MAX_OUTPUT_SIZE).contains(&params.output_length) {
+        return Err(CryptoResult::BadParameter.with_detail(format_args!(
+            "hash length is {} bytes, expected {MIN_OUTPUT_SIZE} to {MAX_OUTPUT_SIZE} bytes",
+            params.output_length
+        )));
+    }
+    let params = Params::new(
+        params.memory_kib,
+        params.iterations,
+        params.parallelism,
+        Some(params.output_length),
+    )
+    .map_err(|error| CryptoResult::BadParameter.with_detail(format_args!("Argon2id: {error}")))?;
+    Ok(Argon2::new(
+        argon2::Algorithm::Argon2id,
+        Version::V0x13,
+        params,
+    ))
+}
@@ -0,0 +1,227 @@
+use crate::zeroize::{zeroize, zeroize_temporary};
+use crate::{Algorithm, CryptoResult, Hasher, CRYPTO_DRBG_MAX_REQUEST_SIZE};
+
+/// Number of requests after which a reseed is required (SP 800-90A, table 2).
+const RESEED_INTERVAL: u64 = 1 << 48;
+
+/// Fills `output` with random bytes from the operating system (`getrandom` on Linux, the
+/// equivalent call elsewhere), blocking only until the system entropy pool is initialized.
+///
+/// Fails with `Failure`, and the OS error as error detail, if the system call fails.
+#[cfg(feature = "random")]
+pub fn fill_random(output: &mut [u8]) -> Result<(), CryptoResult> {
+    getrandom::getrandom(output).map_err(|error| {
+        CryptoResult::Failure.with_detail(format_args!("cannot read OS entropy: {error}"))
+    })
+}
+
+/// Hash_DRBG deterministic random bit generator (NIST SP 800-90A Rev. 1, section 10.1.1), with
+/// a SHA-2 or SHA-3 hash function.
+///
+/// The output depends only on the entropy, nonce, personalization string and additional inputs,
+/// so a generator instantiated from a fixed seed gives reproducible streams for tests. It is only
+/// as unpredictable as its entropy input: in production, seed it with `from_entropy`.
+///
+/// Prediction resistance is left to the caller, who reseeds with fresh entropy when needed. The
+/// internal state is zeroized on drop.
+pub struct HashDrbg {
+    algorithm: Algorithm,
+    /// Secret value V, `seedlen` bits.
+    v: Vec<u8>,
+    /// Secret constant C, `seedlen` bits.
+    c: Vec<u8>,
+    /// Number of generate requests since instantiation or the last reseed, plus one.
+    reseed_counter: u64,
+}
+
+impl HashDrbg {
+    /// Instantiates a generator (SP 800-90A, section 10.1.1.2).
+    ///
+    /// The entropy input must be at least as long as the security strength of the algorithm (32
+    /// bytes, or 24 bytes for SHA-224 and SHA-512/224). The nonce and the personalization string
+    /// can be empty, though SP 800-90A asks for a nonce of half the security strength, unless the
+    /// entropy input is that much longer.
+    ///
+    /// Fails with `BadOrUnsupportedAlgorithm` for algorithms other than SHA-2 and SHA-3, and with
+    /// `BadParameter` if the entropy input is too short.
+    pub fn new(
+        algorithm: Algorithm,
+        entropy: &[u8],
+        nonce: &[u8],
+        personalization: &[u8],
+    ) -> Result<Self, CryptoResult> {
+        let (seed_size, _) = parameters(algorithm)?;
+        check_entropy(algorithm, entropy)?;
+        let v = hash_df(algorithm, [entropy, nonce, personalization], seed_size);
+        let c = hash_df(algorithm, [&[0x00], &v], seed_size);
+        Ok(HashDrbg {
+            algorithm,
+            v,
+            c,
+            reseed_counter: 1,
+        })
+    }
+
+    /// Instantiates a generator from OS entropy, as `new` does with a 48 byte entropy input (the
+    /// security strength and half of it, for the nonce) from `fill_random`.
+    #[cfg(feature = "random")]
+    pub fn from_entropy(
+        algorithm: Algorithm,
+        personalization: &[u8],
+    ) -> Result<Self, CryptoResult> {
+        let mut entropy = [0u8; 48];
+        fill_random(&mut entropy)?;
+        let drbg = HashDrbg::new(algorithm, &entropy, &[], personalization);
+        zeroize(&mut entropy);
+        drbg
+    }
+
+    /// Hash algorithm of the generator.
+    pub fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    /// Reseeds the generator with fresh entropy and optional additional input (SP 800-90A,
+    /// section 10.1.1.3).
+    ///
+    /// Fails with `BadParameter` if the entropy input is shorter than for `new`.
+    pub fn reseed(&mut self, entropy: &[u8], additional_input: &[u8]) -> Result<(), CryptoResult> {
+        check_entropy(self.algorithm, entropy)?;
+        let seed_size = self.v.len();
+        let v = hash_df(
+            self.algorithm,
+            [&[0x01], &self.v, entropy, additional_input],
+            seed_size,
+        );
+        let c = hash_df(self.algorithm, [&[0x00], &v], seed_size);
+        self.replace_state(v, c);
+        self.reseed_counter = 1;
+        Ok(())
+    }
+
+    /// Fills `output` with pseudorandom bytes, after mixing in optional additional input (SP
+    /// 800-90A, section 10.1.1.4).
+    ///
+    /// Fails with `BadBufferOutputSize` if more than `CRYPTO_DRBG_MAX_REQUEST_SIZE` bytes are
+    /// requested, and with `Failure` once 2^48 requests were served since the last reseed.
+    pub fn generate(
+        &mut self,
+        output: &mut [u8],
+        additional_input: &[u8],
+    ) -> Result<(), CryptoResult> {
+        if output.len() > CRYPTO_DRBG_MAX_REQUEST_SIZE {
+            return Err(CryptoResult::bad_output_size(
+                format_args!("at most {CRYPTO_DRBG_MAX_REQUEST_SIZE}"),
+                output.len(),
+            ));
+        }
+        if self.reseed_counter > RESEED_INTERVAL {
+            return Err(CryptoResult::Failure.with_detail("Hash_DRBG must be reseeded"));
+        }
+        if !additional_input.is_empty() {
+            let mut w =
+                Hasher::digest_concat(self.algorithm, [&[0x02][..], &self.v, additional_input]);
+            add_assign(&mut self.v, &w);
+            zeroize_temporary(&mut w);
+        }
+        self.hashgen(output);
+        let mut h = Hasher::digest_concat(self.algorithm, [&[0x03][..], &self.v]);
+        add_assign(&mut self.v, &h);
+        add_assign(&mut self.v, &self.c);
+        add_assign(&mut self.v, &self.reseed_counter.to_be_bytes());
+        zeroize_temporary(&mut h);
+        self.reseed_counter += 1;
+        Ok(())
+    }
+
+    /// Hashgen: hashes of V, V + 1, V + 2... (SP 800-90A, section 10.1.1.4).
+    fn hashgen(&self, output: &mut [u8]) {
+        let mut data = self.v.clone();
+        for chunk in output.chunks_mut(self.algorithm.output_size()) {
+            let mut block = Hasher::digest(self.algorithm, &data);
+            chunk.copy_from_slice(&block[..chunk.len()]);
+            add_assign(&mut data, &[1]);
+            zeroize_temporary(&mut block);
+        }
+        zeroize_temporary(&mut data);
+    }
+
+    fn replace_state(&mut self, v: Vec<u8>, c: Vec<u8>) {
+        zeroize(&mut self.v);
+        zeroize(&mut self.c);
+        self.v = v;
+        self.c = c;
+    }
+}
+
+impl Drop for HashDrbg {
+    fn drop(&mut self) {
+        zeroize(&mut self.v);
+        zeroize(&mut self.c);
+    }
+}
+
+/// Hash_DRBG parameters of the algorithm (SP 800-90A, table 2): the byte length of V and C
+/// (`seedlen`, 440 bits for digests up to 256 bits and 888 bits for longer ones), and the
+/// security strength in bytes.
+fn parameters(algorithm: Algorithm) -> Result<(usize, usize), CryptoResult> {
+    match algorithm {
+        Algorithm::Sha256 | Algorithm::Sha3_256 => Ok((55, 32)),
+        #[cfg(feature = "sha3_512")]
+        Algorithm::Sha3_512 => Ok((111, 32)),
+        #[cfg(feature = "sha224")]
+        Algorithm::Sha224 => Ok((55, 24)),
+        #[cfg(feature = "sha384")]
+        Algorithm::Sha384 => Ok((111, 32)),
+        #[cfg(feature = "sha512")]
+        Algorithm::Sha512 => Ok((111, 32)),
+        #[cfg(feature = "sha512_224")]
+        Algorithm::Sha512_224 => Ok((55, 24)),
+        #[cfg(feature = "sha512_256")]
+        Algorithm::Sha512_256 => Ok((55, 32)),
+        _ => Err(CryptoResult::BadOrUnsupportedAlgorithm
+            .with_detail(format_args!("{algorithm} cannot be used with Hash_DRBG"))),
+    }
+}
+
+/// Checks that the entropy input is at least as long as the security strength.
+fn check_entropy(algorithm: Algorithm, entropy: &[u8]) -> Result<(), CryptoResult> {
+    let (_, min_size) = parameters(algorithm)?;
+    if entropy.len() < min_size {
+        return Err(CryptoResult::BadParameter.with_detail(format_args!(
+            "entropy input is {} bytes, expected at least {min_size} bytes",
+            entropy.len()
+        )));
+    }
+    Ok(())
+}
+
+/// Hash_df derivation function (SP 800-90A, section 10.3.1), returning `size` bytes.
+fn hash_df<const N: usize>(algorithm: Algorithm, inputs: [&[u8]; N], size: usize) -> Vec<u8> {
+    // The seed sizes are at most 111 bytes, so the bit count fits in 32 bits and the counter in
+    // a byte.
+    let bits = (size as u32 * 8).to_be_bytes();
+    let blocks = size.div_ceil(algorithm.output_size());
+    let mut output = Vec::with_capacity(blocks * algorithm.output_size());
+    for counter in 1..=blocks as u8 {
+        output.extend(Hasher::digest_concat(
+            algorithm,
+            [&[counter], &bits[..]].into_iter().chain(inputs),
+        ));
+    }
+    zeroize_temporary(&mut output[size..]);
+    output.truncate(size);
+    output
+}
+
+/// Adds the big-endian number `addend` to the big-endian number `value`, modulo 2^(8 *
+/// `value.len()`). The addend must not be longer than the value.
+fn add_assign(value: &mut [u8], addend: &[u8]) {
+    let mut carry = 0u16;
+    let mut addend = addend.iter().rev();
+    for byte in value.iter_mut().rev() {
+        let sum = u16::from(*byte) + u16::from(*addend.next().unwrap_or(&0)) + carry;
+        *byte = sum as u8;
+        carry = sum >> 8;
+    }
+}
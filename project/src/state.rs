@@ -0,0 +1,73 @@
+use crate::internal_hash_context::InternalHashContext;
+use crate::zeroize::zeroize_temporary;
+use crate::{verify, Algorithm, CryptoResult, Hasher, CRYPTO_STATE_MAX_SIZE};
+use sha2::digest::common::hazmat::SerializableState;
+use sha2::digest::typenum::Unsigned;
+use static_assertions as sa;
+
+/// Identifies a serialized context blob.
+const STATE_MAGIC: [u8; 4] = *b"CXST";
+
+/// Version of the blob format. Must be increased on any change of the header layout, or of the
+/// state serialization of the hash implementations.
+const STATE_FORMAT_VERSION: u32 = 1;
+
+/// Header layout: magic (4) | format version (4) | algorithm ID (4) | state length (4), with
+/// integers in little-endian order. The serialized state follows, then the checksum.
+const HEADER_SIZE: usize = 16;
+
+/// Length of the SHA-256 checksum closing the blob, which covers the header and the state.
+const CHECKSUM_SIZE: usize = 32;
+
+// SHAKE128 has the largest serialized state: the Keccak state and its 168 byte block buffer.
+sa::const_assert!(
+    HEADER_SIZE + <sha3::Shake128 as SerializableState>::SerializedStateSize::USIZE + CHECKSUM_SIZE
+        <= CRYPTO_STATE_MAX_SIZE
+);
+
+/// Serializes `context` into a blob tagged with its algorithm.
+pub fn export(context: &InternalHashContext) -> Result<Vec<u8>, CryptoResult> {
+    let (algorithm_id, mut state) = context.export_state()?;
+    let mut blob = Vec::with_capacity(HEADER_SIZE + state.len() + CHECKSUM_SIZE);
+    blob.extend_from_slice(&STATE_MAGIC);
+    blob.extend_from_slice(&STATE_FORMAT_VERSION.to_le_bytes());
+    blob.extend_from_slice(&algorithm_id.to_le_bytes());
+    blob.extend_from_slice(&(state.len() as u32).to_le_bytes());
+    blob.extend_from_slice(&state);
+    zeroize_temporary(&mut state);
+    let checksum = Hasher::digest(Algorithm::Sha256, &blob);
+    blob.extend_from_slice(&checksum);
+    Ok(blob)
+}
+
+/// Validates `blob` and restores the context it holds.
+///
+/// Blobs that are damaged or of another format version fail with
+/// `UninitializedOrCorruptedContext`. Blobs of algorithms that are not compiled in fail with
+/// `BadOrUnsupportedAlgorithm`.
+pub fn import(blob: &[u8]) -> Result<InternalHashContext, CryptoResult> {
+    if blob.len() < HEADER_SIZE + CHECKSUM_SIZE || blob[..4] != STATE_MAGIC {
+        return Err(corrupted("input is not a state blob"));
+    }
+    let (content, checksum) = blob.split_at(blob.len() - CHECKSUM_SIZE);
+    if !verify(&Hasher::digest(Algorithm::Sha256, content), checksum) {
+        return Err(corrupted("state blob checksum does not match"));
+    }
+    let (header, state) = content.split_at(HEADER_SIZE);
+    let field =
+        |index: usize| u32::from_le_bytes(header[4 * index..4 * index + 4].try_into().unwrap());
+    if field(1) != STATE_FORMAT_VERSION {
+        return Err(corrupted(format_args!(
+            "state blob has format version {}, expected {STATE_FORMAT_VERSION}",
+            field(1)
+        )));
+    }
+    if field(3) as usize != state.len() {
+        return Err(corrupted("state blob length does not match its header"));
+    }
+    InternalHashContext::import_state(field(2), state)
+}
+
+fn corrupted(detail: impl std::fmt::Display) -> CryptoResult {
+    CryptoResult::UninitializedOrCorruptedContext.with_detail(detail)
+}
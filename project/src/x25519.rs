@@ -0,0 +1,55 @@
+use crate::kdf::{hkdf_expand, hkdf_extract};
+use crate::zeroize::zeroize_temporary;
+use crate::{verify, Algorithm, CryptoResult, CRYPTO_X25519_KEY_SIZE as KEY_SIZE};
+use x25519_dalek::{x25519, X25519_BASEPOINT_BYTES};
+
+/// Derives the public key of an X25519 secret key (RFC 7748, section 6.1).
+///
+/// The secret key is 32 bytes from a cryptographically secure random source. It is clamped
+/// when used, so any 32 bytes are a valid key.
+pub fn public_key(secret_key: &[u8; KEY_SIZE]) -> [u8; KEY_SIZE] {
+    x25519(*secret_key, X25519_BASEPOINT_BYTES)
+}
+
+/// Computes the X25519 shared secret of our secret key and the peer public key.
+///
+/// Fails with `ZeroSharedSecret` if the secret is all zeros, which happens when the peer sent a
+/// low-order point: the secret would then not depend on our key, and must not be used (RFC
+/// 7748, section 6.1). The shared secret is not uniformly random, and should go through a KDF
+/// such as `derive_key` before use.
+pub fn shared_secret(
+    secret_key: &[u8; KEY_SIZE],
+    peer_public_key: &[u8; KEY_SIZE],
+) -> Result<[u8; KEY_SIZE], CryptoResult> {
+    let mut shared_secret = x25519(*secret_key, *peer_public_key);
+    // The check takes the same time whatever the secret is.
+    if verify(&shared_secret, &[0u8; KEY_SIZE]) {
+        zeroize_temporary(&mut shared_secret);
+        return Err(
+            CryptoResult::ZeroSharedSecret.with_detail("peer public key is a low-order point")
+        );
+    }
+    Ok(shared_secret)
+}
+
+/// Computes the X25519 shared secret as `shared_secret` does, and derives `okm.len()` bytes of
+/// key material from it with HKDF (RFC 5869): `HKDF-Expand(HKDF-Extract(salt, secret), info)`
+/// with the HMAC of `algorithm`.
+///
+/// The shared secret and the pseudorandom key are not kept, so that callers never handle them.
+pub fn derive_key(
+    algorithm: Algorithm,
+    secret_key: &[u8; KEY_SIZE],
+    peer_public_key: &[u8; KEY_SIZE],
+    salt: &[u8],
+    info: &[u8],
+    okm: &mut [u8],
+) -> Result<(), CryptoResult> {
+    let mut shared_secret = shared_secret(secret_key, peer_public_key)?;
+    let mut prk = vec![0u8; algorithm.output_size()];
+    let result = hkdf_extract(algorithm, salt, &shared_secret, &mut prk)
+        .and_then(|()| hkdf_expand(algorithm, &prk, info, okm));
+    zeroize_temporary(&mut shared_secret);
+    zeroize_temporary(&mut prk);
+    result
+}
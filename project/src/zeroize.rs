@@ -0,0 +1,35 @@
+use std::sync::atomic::{compiler_fence, Ordering};
+use std::{mem, ptr};
+
+/// Overwrites `bytes` with zeros.
+///
+/// The writes are volatile and followed by a compiler fence, so the optimizer cannot elide them
+/// even when the memory is never read again.
+pub fn zeroize(bytes: &mut [u8]) {
+    // SAFETY: the slice is valid for writes of its whole length.
+    unsafe { zeroize_bytes(bytes.as_mut_ptr(), bytes.len()) }
+}
+
+/// Overwrites the memory of `value` with zeros, as `zeroize` does.
+///
+/// # Safety
+/// Pointer must be valid for writes, and all-zero bytes must be a valid `T` if the value is used
+/// or dropped afterwards.
+pub unsafe fn zeroize_raw<T>(value: *mut T) {
+    zeroize_bytes(value.cast(), mem::size_of::<T>());
+}
+
+/// Zeroizes a temporary buffer holding secret or message dependent data, when the `zeroize`
+/// feature is enabled.
+pub fn zeroize_temporary(bytes: &mut [u8]) {
+    if cfg!(feature = "zeroize") {
+        zeroize(bytes);
+    }
+}
+
+unsafe fn zeroize_bytes(bytes: *mut u8, length: usize) {
+    for offset in 0..length {
+        ptr::write_volatile(bytes.add(offset), 0);
+    }
+    compiler_fence(Ordering::SeqCst);
+}